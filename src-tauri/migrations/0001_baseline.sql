@@ -0,0 +1,245 @@
+-- Esquema inicial (equivalente às migrações sem versão anteriores)
+
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    login TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'admin',
+    is_active INTEGER NOT NULL DEFAULT 1,
+    must_change_password INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS storage_units (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    label TEXT NOT NULL,
+    type TEXT NOT NULL,
+    section TEXT,
+    capacity INTEGER DEFAULT 0,
+    occupancy INTEGER DEFAULT 0,
+    metadata TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    reference TEXT,
+    item_label TEXT,
+    from_unit TEXT,
+    to_unit TEXT,
+    action TEXT NOT NULL,
+    note TEXT,
+    actor TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS departments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    code TEXT,
+    description TEXT,
+    is_active INTEGER DEFAULT 1,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS employees (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    full_name TEXT NOT NULL,
+    registration TEXT NOT NULL UNIQUE,
+    cpf TEXT UNIQUE,
+    department_id INTEGER REFERENCES departments(id),
+    admission_date TEXT NOT NULL,
+    termination_date TEXT,
+    status TEXT DEFAULT 'ACTIVE',
+    drawer_position_id INTEGER REFERENCES drawer_positions(id),
+    notes TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS file_cabinets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    number TEXT NOT NULL UNIQUE,
+    location TEXT,
+    num_drawers INTEGER NOT NULL DEFAULT 4,
+    description TEXT,
+    is_active INTEGER DEFAULT 1,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS drawers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_cabinet_id INTEGER NOT NULL REFERENCES file_cabinets(id),
+    number INTEGER NOT NULL,
+    capacity INTEGER NOT NULL DEFAULT 30,
+    label TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(file_cabinet_id, number)
+);
+
+CREATE TABLE IF NOT EXISTS drawer_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    drawer_id INTEGER NOT NULL REFERENCES drawers(id),
+    position INTEGER NOT NULL,
+    employee_id INTEGER REFERENCES employees(id),
+    is_occupied INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(drawer_id, position)
+);
+
+CREATE TABLE IF NOT EXISTS document_categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    code TEXT NOT NULL UNIQUE,
+    description TEXT,
+    icon TEXT,
+    color TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO document_categories (name, code, description)
+    VALUES
+    ('Pessoal', 'PESSOAL', 'Documentos pessoais, contratos, admissão'),
+    ('Medicina do Trabalho', 'MEDICINA', 'Exames, ASOs, atestados'),
+    ('Segurança do Trabalho', 'SEGURANCA', 'EPIs, treinamentos de segurança'),
+    ('Treinamento', 'TREINAMENTO', 'Certificados, capacitações');
+
+CREATE TABLE IF NOT EXISTS document_types (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL REFERENCES document_categories(id),
+    name TEXT NOT NULL,
+    retention_years INTEGER DEFAULT 5,
+    is_required INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(category_id, name)
+);
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Contrato de Trabalho', 10 FROM document_categories WHERE code = 'PESSOAL';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'RG', 5 FROM document_categories WHERE code = 'PESSOAL';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'CPF', 5 FROM document_categories WHERE code = 'PESSOAL';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Comprovante de Residência', 2 FROM document_categories WHERE code = 'PESSOAL';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Certidão de Nascimento/Casamento', 5 FROM document_categories WHERE code = 'PESSOAL';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'ASO Admissional', 20 FROM document_categories WHERE code = 'MEDICINA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'ASO Periódico', 20 FROM document_categories WHERE code = 'MEDICINA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'ASO Demissional', 20 FROM document_categories WHERE code = 'MEDICINA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Atestado Médico', 5 FROM document_categories WHERE code = 'MEDICINA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Ficha de EPI', 5 FROM document_categories WHERE code = 'SEGURANCA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Treinamento NR', 5 FROM document_categories WHERE code = 'SEGURANCA';
+
+INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
+    SELECT id, 'Certificado de Curso', 5 FROM document_categories WHERE code = 'TREINAMENTO';
+
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    employee_id INTEGER NOT NULL REFERENCES employees(id),
+    category_id INTEGER NOT NULL REFERENCES document_categories(id),
+    type_id INTEGER NOT NULL REFERENCES document_types(id),
+    description TEXT,
+    document_date TEXT,
+    filing_date TEXT DEFAULT CURRENT_TIMESTAMP,
+    expiration_date TEXT,
+    notes TEXT,
+    filed_by TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS loans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    employee_id INTEGER NOT NULL REFERENCES employees(id),
+    requester_name TEXT NOT NULL,
+    requester_department_id INTEGER REFERENCES departments(id),
+    reason TEXT NOT NULL,
+    loan_date TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expected_return_date TEXT NOT NULL,
+    actual_return_date TEXT,
+    status TEXT DEFAULT 'BORROWED',
+    return_notes TEXT,
+    loaned_by TEXT NOT NULL,
+    returned_by TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS dead_archive_boxes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    box_number TEXT NOT NULL UNIQUE,
+    year INTEGER NOT NULL,
+    period TEXT,
+    letter_range TEXT,
+    location TEXT,
+    capacity INTEGER DEFAULT 50,
+    current_count INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS dead_archive_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    employee_id INTEGER NOT NULL REFERENCES employees(id),
+    box_id INTEGER NOT NULL REFERENCES dead_archive_boxes(id),
+    transfer_date TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    disposal_eligible_date TEXT,
+    disposed INTEGER DEFAULT 0,
+    disposal_date TEXT,
+    disposal_term_number TEXT,
+    transferred_by TEXT NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER REFERENCES users(id),
+    action TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id INTEGER,
+    old_values TEXT,
+    new_values TEXT,
+    ip_address TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_updated_at ON storage_units(updated_at);
+
+CREATE INDEX IF NOT EXISTS idx_movements_created_at ON movements(created_at);
+
+CREATE INDEX IF NOT EXISTS idx_users_login ON users(login);
+
+CREATE INDEX IF NOT EXISTS idx_employees_registration ON employees(registration);
+
+CREATE INDEX IF NOT EXISTS idx_employees_status ON employees(status);
+
+CREATE INDEX IF NOT EXISTS idx_employees_name ON employees(full_name);
+
+CREATE INDEX IF NOT EXISTS idx_documents_employee ON documents(employee_id);
+
+CREATE INDEX IF NOT EXISTS idx_loans_status ON loans(status);
+
+CREATE INDEX IF NOT EXISTS idx_loans_employee ON loans(employee_id);
+
+CREATE INDEX IF NOT EXISTS idx_dead_archive_employee ON dead_archive_items(employee_id);
+
+CREATE INDEX IF NOT EXISTS idx_audit_created ON audit_logs(created_at);
@@ -0,0 +1,3 @@
+-- Motivo informado no desligamento do funcionário
+
+ALTER TABLE employees ADD COLUMN termination_reason TEXT;
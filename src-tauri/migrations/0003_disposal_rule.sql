@@ -0,0 +1,27 @@
+-- Regra de temporalidade que gerou a data de descarte de cada pasta
+
+ALTER TABLE dead_archive_items ADD COLUMN disposal_rule TEXT;
+
+-- Recalcula as datas informadas manualmente para pastas ainda não descartadas
+UPDATE dead_archive_items AS i
+SET disposal_eligible_date = date(r.termination_date, '+' || r.years || ' years'),
+    disposal_rule = r.years || ' anos após o desligamento (' || r.source || ')'
+FROM (
+    SELECT e.id AS employee_id,
+        e.termination_date,
+        COALESCE((
+            SELECT MAX(dt.retention_years) FROM documents d
+            JOIN document_types dt ON dt.id = d.type_id
+            WHERE d.employee_id = e.id
+        ), 5) AS years,
+        COALESCE((
+            SELECT dt.name FROM documents d
+            JOIN document_types dt ON dt.id = d.type_id
+            WHERE d.employee_id = e.id
+            ORDER BY dt.retention_years DESC, dt.name
+            LIMIT 1
+        ), 'prazo padrão') AS source
+    FROM employees e
+    WHERE date(e.termination_date) IS NOT NULL
+) AS r
+WHERE i.employee_id = r.employee_id AND i.disposed = 0;
@@ -0,0 +1,44 @@
+-- CPF passa a ser gravado apenas com dígitos. Registros que colidiriam com outro
+-- funcionário após a normalização ficam como estão e são listados em cpf_conflicts.
+
+CREATE TABLE cpf_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    employee_id INTEGER NOT NULL,
+    original_cpf TEXT NOT NULL,
+    normalized_cpf TEXT NOT NULL,
+    detected_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (employee_id) REFERENCES employees(id)
+);
+
+-- Mesma regra de `cpf::normalize`: sobra apenas o que for dígito, qualquer que
+-- seja o separador usado.
+CREATE TEMP TABLE normalized_cpfs AS
+WITH RECURSIVE walk(id, rest, digits) AS (
+    SELECT id, cpf, '' FROM employees WHERE cpf IS NOT NULL
+    UNION ALL
+    SELECT id,
+        substr(rest, 2),
+        digits || CASE WHEN substr(rest, 1, 1) BETWEEN '0' AND '9' THEN substr(rest, 1, 1) ELSE '' END
+    FROM walk
+    WHERE rest <> ''
+)
+SELECT w.id, e.cpf, w.digits
+FROM walk w
+JOIN employees e ON e.id = w.id
+WHERE w.rest = '';
+
+INSERT INTO cpf_conflicts (employee_id, original_cpf, normalized_cpf)
+SELECT n.id, n.cpf, n.digits
+FROM normalized_cpfs n
+WHERE n.digits <> ''
+  AND EXISTS (
+    SELECT 1 FROM normalized_cpfs o WHERE o.digits = n.digits AND o.id <> n.id
+  )
+ORDER BY n.digits, n.id;
+
+UPDATE employees
+SET cpf = (SELECT NULLIF(n.digits, '') FROM normalized_cpfs n WHERE n.id = employees.id)
+WHERE cpf IS NOT NULL
+  AND id NOT IN (SELECT employee_id FROM cpf_conflicts);
+
+DROP TABLE normalized_cpfs;
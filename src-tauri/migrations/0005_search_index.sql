@@ -0,0 +1,97 @@
+-- Índice de busca textual. O tokenizador unicode61 ignora maiúsculas e acentos,
+-- então "joao" encontra "João". O rowid codifica o registro de origem
+-- (id * 10 + tipo: 1 funcionário, 2 documento, 3 empréstimo, 4 caixa) para que
+-- os gatilhos atualizem a entrada sem varrer o índice.
+
+CREATE VIRTUAL TABLE search_index USING fts5(
+    entity_type UNINDEXED,
+    entity_id UNINDEXED,
+    title,
+    body,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+SELECT id * 10 + 1, 'employee', id, full_name,
+    registration || ' ' || COALESCE(cpf, '') || ' ' || COALESCE(notes, '')
+FROM employees;
+
+INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+SELECT id * 10 + 2, 'document', id, COALESCE(description, ''), COALESCE(notes, '')
+FROM documents;
+
+INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+SELECT id * 10 + 3, 'loan', id, requester_name,
+    reason || ' ' || COALESCE(return_notes, '')
+FROM loans;
+
+INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+SELECT id * 10 + 4, 'box', id, box_number,
+    COALESCE(period, '') || ' ' || COALESCE(letter_range, '') || ' ' || COALESCE(location, '')
+FROM dead_archive_boxes;
+
+CREATE TRIGGER search_employees_insert AFTER INSERT ON employees BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 1, 'employee', new.id, new.full_name,
+        new.registration || ' ' || COALESCE(new.cpf, '') || ' ' || COALESCE(new.notes, ''));
+END;
+
+CREATE TRIGGER search_employees_update AFTER UPDATE OF full_name, registration, cpf, notes ON employees BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 1;
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 1, 'employee', new.id, new.full_name,
+        new.registration || ' ' || COALESCE(new.cpf, '') || ' ' || COALESCE(new.notes, ''));
+END;
+
+CREATE TRIGGER search_employees_delete AFTER DELETE ON employees BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 1;
+END;
+
+CREATE TRIGGER search_documents_insert AFTER INSERT ON documents BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 2, 'document', new.id, COALESCE(new.description, ''), COALESCE(new.notes, ''));
+END;
+
+CREATE TRIGGER search_documents_update AFTER UPDATE OF description, notes ON documents BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 2;
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 2, 'document', new.id, COALESCE(new.description, ''), COALESCE(new.notes, ''));
+END;
+
+CREATE TRIGGER search_documents_delete AFTER DELETE ON documents BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 2;
+END;
+
+CREATE TRIGGER search_loans_insert AFTER INSERT ON loans BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 3, 'loan', new.id, new.requester_name,
+        new.reason || ' ' || COALESCE(new.return_notes, ''));
+END;
+
+CREATE TRIGGER search_loans_update AFTER UPDATE OF requester_name, reason, return_notes ON loans BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 3;
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 3, 'loan', new.id, new.requester_name,
+        new.reason || ' ' || COALESCE(new.return_notes, ''));
+END;
+
+CREATE TRIGGER search_loans_delete AFTER DELETE ON loans BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 3;
+END;
+
+CREATE TRIGGER search_boxes_insert AFTER INSERT ON dead_archive_boxes BEGIN
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 4, 'box', new.id, new.box_number,
+        COALESCE(new.period, '') || ' ' || COALESCE(new.letter_range, '') || ' ' || COALESCE(new.location, ''));
+END;
+
+CREATE TRIGGER search_boxes_update AFTER UPDATE OF box_number, period, letter_range, location ON dead_archive_boxes BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 4;
+    INSERT INTO search_index (rowid, entity_type, entity_id, title, body)
+    VALUES (new.id * 10 + 4, 'box', new.id, new.box_number,
+        COALESCE(new.period, '') || ' ' || COALESCE(new.letter_range, '') || ' ' || COALESCE(new.location, ''));
+END;
+
+CREATE TRIGGER search_boxes_delete AFTER DELETE ON dead_archive_boxes BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 4;
+END;
@@ -0,0 +1,20 @@
+-- Arquivos digitalizados dos documentos. O conteúdo fica no armazenamento local
+-- endereçado pelo SHA-256 (anexos/ab/cd/<hash>); aqui ficam só o vínculo com o
+-- documento e os dados do arquivo original. O mesmo conteúdo pode estar em
+-- vários documentos, mas não duas vezes no mesmo.
+
+CREATE TABLE IF NOT EXISTS document_files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL REFERENCES documents(id),
+    sha256 TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    attached_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (document_id, sha256)
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_files_document ON document_files(document_id);
+
+CREATE INDEX IF NOT EXISTS idx_document_files_sha256 ON document_files(sha256);
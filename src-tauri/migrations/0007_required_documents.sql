@@ -0,0 +1,6 @@
+-- Checagem de documentos obrigatórios. Quais tipos são obrigatórios é política
+-- de cada empresa: todos começam opcionais (`is_required = 0`) e o administrador
+-- os marca no cadastro de tipos. Aqui fica só o índice usado para achar o
+-- documento mais recente de cada tipo por funcionário.
+
+CREATE INDEX IF NOT EXISTS idx_documents_employee_type ON documents(employee_id, type_id);
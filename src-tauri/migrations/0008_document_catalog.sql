@@ -0,0 +1,7 @@
+-- Categorias e tipos de documento passam a ser cadastrados pelo RH. Itens em
+-- uso não podem ser excluídos, apenas desativados: somem das listas de
+-- cadastro e da checagem de conformidade, mas os documentos já arquivados
+-- continuam apontando para eles.
+
+ALTER TABLE document_categories ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE document_types ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,13 @@
+-- Documentos passam a ser corrigidos e excluídos pelo sistema. A exclusão é
+-- lógica: a linha fica com data, responsável e motivo, e o histórico completo
+-- permanece na auditoria. Documentos excluídos saem da busca.
+
+ALTER TABLE documents ADD COLUMN updated_at TEXT;
+ALTER TABLE documents ADD COLUMN deleted_at TEXT;
+ALTER TABLE documents ADD COLUMN deleted_by TEXT;
+ALTER TABLE documents ADD COLUMN deletion_reason TEXT;
+
+CREATE TRIGGER search_documents_soft_delete AFTER UPDATE OF deleted_at ON documents
+WHEN new.deleted_at IS NOT NULL BEGIN
+    DELETE FROM search_index WHERE rowid = old.id * 10 + 2;
+END;
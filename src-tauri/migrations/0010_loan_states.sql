@@ -0,0 +1,21 @@
+-- Ciclo de vida do empréstimo: além de BORROWED e RETURNED, a pasta pode ser
+-- dada como extraviada (LOST) ou o empréstimo cancelado (CANCELLED). Cada
+-- renovação do prazo fica registrada com o motivo.
+
+ALTER TABLE loans ADD COLUMN renewal_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE loans ADD COLUMN incident_notes TEXT;
+ALTER TABLE loans ADD COLUMN cancellation_reason TEXT;
+
+UPDATE loans SET status = 'BORROWED' WHERE status IS NULL;
+
+CREATE TABLE loan_renewals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    loan_id INTEGER NOT NULL REFERENCES loans(id),
+    previous_return_date TEXT NOT NULL,
+    new_return_date TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    renewed_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_loan_renewals_loan ON loan_renewals(loan_id);
@@ -0,0 +1,19 @@
+-- Fila de espera por pastas emprestadas. Na devolução, a primeira reserva
+-- aguardando passa a NOTIFIED; o empréstimo seguinte, feito a quem reservou,
+-- marca a reserva como FULFILLED.
+
+CREATE TABLE loan_reservations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    employee_id INTEGER NOT NULL REFERENCES employees(id),
+    requester_name TEXT NOT NULL,
+    requester_department_id INTEGER REFERENCES departments(id),
+    reason TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'WAITING',
+    loan_id INTEGER REFERENCES loans(id),
+    reserved_by TEXT NOT NULL,
+    notified_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_loan_reservations_employee ON loan_reservations(employee_id, status);
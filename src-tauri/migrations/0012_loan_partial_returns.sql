@@ -0,0 +1,19 @@
+-- Devolução parcial: parte dos documentos da pasta volta e o restante segue
+-- com o solicitante. O empréstimo continua BORROWED, então a pasta não pode
+-- ser emprestada nem transferida e a fila de espera só é avisada na devolução
+-- completa. `outstanding_items` descreve o que ainda está fora.
+
+ALTER TABLE loans ADD COLUMN outstanding_items TEXT;
+
+CREATE TABLE loan_partial_returns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    loan_id INTEGER NOT NULL REFERENCES loans(id),
+    returned_items TEXT NOT NULL,
+    outstanding_items TEXT NOT NULL,
+    return_date TEXT NOT NULL,
+    notes TEXT,
+    received_by TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_loan_partial_returns_loan ON loan_partial_returns(loan_id);
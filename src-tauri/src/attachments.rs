@@ -0,0 +1,211 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Maior arquivo aceito como anexo (50 MB).
+const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Tipos aceitos: digitalizações em PDF ou imagem.
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("webp", "image/webp"),
+];
+
+/// Situação do arquivo guardado em relação ao hash registrado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobStatus {
+    Intact,
+    Missing,
+    Corrupted,
+}
+
+/// Armazenamento local dos anexos, endereçado pelo SHA-256 do conteúdo
+/// (`<raiz>/ab/cd/abcd...`). Arquivos iguais ocupam espaço uma única vez.
+pub struct AttachmentStore {
+    root: PathBuf,
+    mutations: Mutex<()>,
+}
+
+impl AttachmentStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            mutations: Mutex::new(()),
+        }
+    }
+
+    /// Trava as alterações do armazenamento. Como o mesmo conteúdo pode servir
+    /// a vários anexos, quem grava (`put` até o registro) e quem apaga (exclusão
+    /// até a contagem de referências) segura a trava o tempo todo.
+    pub async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.mutations.lock().await
+    }
+
+    pub fn path_for(&self, sha256: &str) -> PathBuf {
+        self.root
+            .join(&sha256[..2])
+            .join(&sha256[2..4])
+            .join(sha256)
+    }
+
+    /// Copia o arquivo para o armazenamento e devolve o hash e o tamanho. A cópia
+    /// é feita num arquivo temporário e só depois renomeada, para que um arquivo
+    /// interrompido no meio nunca apareça com o nome de um hash.
+    pub fn put(&self, source: &Path) -> Result<(String, i64)> {
+        let size = fs::metadata(source)
+            .with_context(|| format!("Arquivo não encontrado: {}", source.display()))?
+            .len();
+        if size == 0 {
+            bail!("Arquivo vazio");
+        }
+        if size > MAX_FILE_BYTES {
+            bail!(
+                "Arquivo maior que o limite de {} MB",
+                MAX_FILE_BYTES / 1024 / 1024
+            );
+        }
+
+        let incoming = self.root.join("tmp");
+        fs::create_dir_all(&incoming)?;
+        let temp_path = incoming.join(uuid::Uuid::new_v4().to_string());
+        let copied = File::create(&temp_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut file| {
+                let result = copy_hashing(source, &mut file)?;
+                file.sync_all()?;
+                Ok(result)
+            });
+        let (sha256, written) = match copied {
+            Ok(result) => result,
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        };
+
+        let target = self.path_for(&sha256);
+        if target.exists() {
+            fs::remove_file(&temp_path)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&temp_path, &target)?;
+        }
+        Ok((sha256, written as i64))
+    }
+
+    pub fn verify(&self, sha256: &str) -> Result<BlobStatus> {
+        let path = self.path_for(sha256);
+        if !path.exists() {
+            return Ok(BlobStatus::Missing);
+        }
+        Ok(if hash_file(&path)? == sha256 {
+            BlobStatus::Intact
+        } else {
+            BlobStatus::Corrupted
+        })
+    }
+
+    pub fn remove(&self, sha256: &str) -> Result<()> {
+        let path = self.path_for(sha256);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Copia o conteúdo para `destination`, conferindo antes a integridade.
+    pub fn copy_out(&self, sha256: &str, destination: &Path) -> Result<()> {
+        match self.verify(sha256)? {
+            BlobStatus::Intact => {}
+            BlobStatus::Missing => bail!("Arquivo anexado não está mais no armazenamento"),
+            BlobStatus::Corrupted => bail!("Arquivo anexado está corrompido"),
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(self.path_for(sha256), destination)?;
+        Ok(())
+    }
+}
+
+/// Tipo MIME pela extensão; extensões fora da lista são recusadas.
+pub fn mime_type(file_name: &str) -> Result<&'static str> {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match MIME_TYPES.iter().find(|(ext, _)| *ext == extension) {
+        Some((_, mime)) => Ok(mime),
+        None => bail!("Tipo de arquivo não aceito; envie PDF ou imagem (JPG, PNG, TIFF, WEBP)"),
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let (sha256, _) = copy_hashing(path, &mut std::io::sink())?;
+    Ok(sha256)
+}
+
+/// Lê `source` uma única vez, gravando em `writer` e calculando o SHA-256.
+fn copy_hashing(source: &Path, writer: &mut impl Write) -> Result<(String, u64)> {
+    let mut reader = BufReader::new(File::open(source)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+    Ok((hex(&hasher.finalize()), total))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_verify_and_remove() {
+        let root = std::env::temp_dir().join(format!("anexos-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let source = root.join("aso.pdf");
+        fs::write(&source, b"%PDF-1.4 conteudo").unwrap();
+        let store = AttachmentStore::new(root.join("store"));
+
+        let (sha256, size) = store.put(&source).unwrap();
+        assert_eq!(
+            sha256,
+            "4151433e36cb1edfae4bbfa342db1b25f84920c0289469ac7f57181826685814"
+        );
+        assert_eq!(size, 17);
+        assert_eq!(store.put(&source).unwrap().0, sha256);
+        assert_eq!(store.verify(&sha256).unwrap(), BlobStatus::Intact);
+
+        fs::write(store.path_for(&sha256), b"adulterado").unwrap();
+        assert_eq!(store.verify(&sha256).unwrap(), BlobStatus::Corrupted);
+
+        store.remove(&sha256).unwrap();
+        assert_eq!(store.verify(&sha256).unwrap(), BlobStatus::Missing);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
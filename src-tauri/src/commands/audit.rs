@@ -0,0 +1,108 @@
+use crate::db::ArchiveDatabase;
+use crate::exports;
+use crate::sessions::SessionStore;
+use crate::types::{
+    ApiResponse, AuditExportPayload, AuditLogPage, AuditLogQueryPayload, EntityHistory,
+    EntityHistoryPayload, FileExportResult,
+};
+use chrono::Utc;
+use tauri::{AppHandle, State};
+use validator::Validate;
+
+#[tauri::command]
+pub async fn list_audit_logs(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: AuditLogQueryPayload,
+) -> Result<ApiResponse<AuditLogPage>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_audit_logs") {
+        return Ok(denied.into());
+    }
+
+    match db
+        .list_audit_logs(&payload.filter, payload.cursor, payload.limit.unwrap_or(50))
+        .await
+    {
+        Ok(page) => Ok(ApiResponse::success(page)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar auditoria: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn get_entity_history(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: EntityHistoryPayload,
+) -> Result<ApiResponse<EntityHistory>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "get_entity_history") {
+        return Ok(denied.into());
+    }
+
+    match db
+        .get_entity_history(&payload.entity_type, payload.entity_id)
+        .await
+    {
+        Ok(history) => Ok(ApiResponse::success(history)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao carregar histórico: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn export_audit_logs(
+    app: AppHandle,
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: AuditExportPayload,
+) -> Result<ApiResponse<FileExportResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "export_audit_logs") {
+        return Ok(denied.into());
+    }
+
+    let logs = match db.export_audit_logs(&payload.filter).await {
+        Ok(logs) => logs,
+        Err(e) => {
+            return Ok(ApiResponse::error(format!(
+                "Erro ao exportar auditoria: {}",
+                e
+            )))
+        }
+    };
+
+    let written = exports::export_path(&app, payload.path.as_deref(), "auditoria", &payload.format)
+        .and_then(|path| {
+            match payload.format.as_str() {
+                "json" => exports::write_json(&path, &logs)?,
+                _ => exports::write_audit_csv(&path, &logs)?,
+            }
+            Ok(path)
+        });
+
+    match written {
+        Ok(path) => Ok(ApiResponse::success(FileExportResult {
+            path: path.display().to_string(),
+            generated_at: Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao gravar arquivo de auditoria: {}",
+            e
+        ))),
+    }
+}
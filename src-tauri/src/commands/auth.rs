@@ -63,6 +63,7 @@ pub async fn auth_login(
             match db.snapshot().await {
                 Ok(snapshot) => Ok(ApiResponse::success(LoginResult {
                     token: session.token,
+                    must_change_password: profile.must_change_password,
                     profile,
                     snapshot,
                 })),
@@ -89,6 +90,7 @@ pub async fn auth_session(
         Ok(session) => match db.snapshot().await {
             Ok(snapshot) => Ok(ApiResponse::success(LoginResult {
                 token: session.token,
+                must_change_password: session.profile.must_change_password,
                 profile: session.profile,
                 snapshot,
             })),
@@ -1,8 +1,9 @@
 use crate::db::ArchiveDatabase;
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, ArchiveBoxCreatePayload, ArchiveBoxRecord, ArchiveItemRecord,
-    ArchiveTransferPayload, DisposalCandidate, DisposalRegisterPayload, DisposalTerm, TokenPayload,
+    ApiResponse, ArchiveBoxCreatePayload, ArchiveBoxListPayload, ArchiveBoxRecord,
+    ArchiveItemRecord, ArchiveTransferPayload, DisposalCandidate, DisposalRegisterPayload,
+    DisposalTerm, Page, TokenPayload,
 };
 use tauri::State;
 use validator::Validate;
@@ -17,12 +18,12 @@ pub async fn create_archive_box(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_archive_box") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_archive_box(&payload.data).await {
+    match db.create_archive_box(&payload.data, &session.profile).await {
         Ok(archive_box) => Ok(ApiResponse::success(archive_box)),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao criar caixa: {}", e))),
     }
@@ -32,17 +33,20 @@ pub async fn create_archive_box(
 pub async fn list_archive_boxes(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TokenPayload,
-) -> Result<ApiResponse<Vec<ArchiveBoxRecord>>, String> {
+    payload: ArchiveBoxListPayload,
+) -> Result<ApiResponse<Page<ArchiveBoxRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_archive_boxes") {
+        return Ok(denied.into());
     }
 
-    match db.list_archive_boxes().await {
+    match db
+        .list_archive_boxes(&payload.filter, &payload.paging)
+        .await
+    {
         Ok(boxes) => Ok(ApiResponse::success(boxes)),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao listar caixas: {}", e))),
     }
@@ -58,18 +62,13 @@ pub async fn transfer_to_archive(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "transfer_to_archive") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
     match db
-        .transfer_to_archive(
-            payload.employee_id,
-            payload.box_id,
-            payload.disposal_eligible_date.as_deref(),
-            &session.profile.login,
-        )
+        .transfer_to_archive(payload.employee_id, payload.box_id, &session.profile)
         .await
     {
         Ok(item) => Ok(ApiResponse::success(item)),
@@ -90,8 +89,8 @@ pub async fn get_disposal_candidates(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_disposal_candidates") {
+        return Ok(denied.into());
     }
 
     match db.get_disposal_candidates().await {
@@ -113,13 +112,17 @@ pub async fn register_disposal(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "register_disposal") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
     match db
-        .register_disposal(&payload.item_ids, payload.term_number.as_deref())
+        .register_disposal(
+            &payload.item_ids,
+            payload.term_number.as_deref(),
+            &session.profile,
+        )
         .await
     {
         Ok(term) => Ok(ApiResponse::success(term)),
@@ -16,8 +16,8 @@ pub async fn list_departments(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_departments") {
+        return Ok(denied.into());
     }
 
     match db.list_departments().await {
@@ -39,12 +39,12 @@ pub async fn create_department(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_department") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_department(&payload.data).await {
+    match db.create_department(&payload.data, &session.profile).await {
         Ok(department) => Ok(ApiResponse::success(department)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao criar departamento: {}",
@@ -64,12 +64,15 @@ pub async fn update_department(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "update_department") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.update_department(payload.id, &data).await {
+    match db
+        .update_department(payload.id, &data, &session.profile)
+        .await
+    {
         Ok(department) => Ok(ApiResponse::success(department)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao atualizar departamento: {}",
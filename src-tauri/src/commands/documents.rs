@@ -1,9 +1,17 @@
-use crate::db::ArchiveDatabase;
+use crate::attachments::AttachmentStore;
+use crate::db::{ArchiveDatabase, EXPIRATION_ALERT_DAYS};
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, DocumentCategoryRecord, DocumentPayload, DocumentRecord, DocumentTypeRecord,
-    EmployeeDocumentsPayload, IdPayload, TokenPayload,
+    ApiResponse, ComplianceReport, ComplianceReportPayload, DocumentCategoryPayload,
+    DocumentCategoryRecord, DocumentCategoryUpsertPayload, DocumentDeletePayload,
+    DocumentFileAttachPayload, DocumentFileAttachResult, DocumentFileIntegrityReport,
+    DocumentFileRecord, DocumentFileVerifyPayload, DocumentPayload, DocumentRecord,
+    DocumentTypePayload, DocumentTypeRecord, DocumentTypeUpsertPayload, DocumentUpdatePayload,
+    EmployeeCompliance, EmployeeDocumentsPayload, ExpiringDocumentsPayload,
+    ExpiringDocumentsReport, FileExportResult, IdPayload, Page, TokenPayload,
 };
+use chrono::Utc;
+use std::path::Path;
 use tauri::State;
 use validator::Validate;
 
@@ -12,16 +20,20 @@ pub async fn list_document_categories(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: TokenPayload,
+    include_inactive: Option<bool>,
 ) -> Result<ApiResponse<Vec<DocumentCategoryRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_document_categories") {
+        return Ok(denied.into());
     }
 
-    match db.list_document_categories().await {
+    match db
+        .list_document_categories(include_inactive.unwrap_or(false))
+        .await
+    {
         Ok(categories) => Ok(ApiResponse::success(categories)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao listar categorias: {}",
@@ -35,13 +47,14 @@ pub async fn list_document_types(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: IdPayload,
+    include_inactive: Option<bool>,
 ) -> Result<ApiResponse<Vec<DocumentTypeRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_document_types") {
+        return Ok(denied.into());
     }
 
     // id = 0 means all types, otherwise filter by category
@@ -51,12 +64,224 @@ pub async fn list_document_types(
         Some(payload.id)
     };
 
-    match db.list_document_types(category_id).await {
+    match db
+        .list_document_types(category_id, include_inactive.unwrap_or(false))
+        .await
+    {
         Ok(types) => Ok(ApiResponse::success(types)),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao listar tipos: {}", e))),
     }
 }
 
+#[tauri::command]
+pub async fn create_document_category(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentCategoryUpsertPayload,
+) -> Result<ApiResponse<DocumentCategoryRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "create_document_category") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .create_document_category(&payload.data, &session.profile)
+        .await
+    {
+        Ok(category) => Ok(ApiResponse::success(category)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao criar categoria: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn update_document_category(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+    data: DocumentCategoryPayload,
+) -> Result<ApiResponse<DocumentCategoryRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+    if let Err(e) = data.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "update_document_category") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .update_document_category(payload.id, &data, &session.profile)
+        .await
+    {
+        Ok(category) => Ok(ApiResponse::success(category)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao atualizar categoria: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn deactivate_document_category(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<DocumentCategoryRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "deactivate_document_category") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .deactivate_document_category(payload.id, &session.profile)
+        .await
+    {
+        Ok(category) => Ok(ApiResponse::success(category)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao desativar categoria: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn delete_document_category(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<()>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "delete_document_category") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .delete_document_category(payload.id, &session.profile)
+        .await
+    {
+        Ok(()) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao excluir categoria: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn create_document_type(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentTypeUpsertPayload,
+) -> Result<ApiResponse<DocumentTypeRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "create_document_type") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .create_document_type(&payload.data, &session.profile)
+        .await
+    {
+        Ok(document_type) => Ok(ApiResponse::success(document_type)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao criar tipo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn update_document_type(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+    data: DocumentTypePayload,
+) -> Result<ApiResponse<DocumentTypeRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+    if let Err(e) = data.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "update_document_type") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .update_document_type(payload.id, &data, &session.profile)
+        .await
+    {
+        Ok(document_type) => Ok(ApiResponse::success(document_type)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao atualizar tipo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn deactivate_document_type(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<DocumentTypeRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "deactivate_document_type") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .deactivate_document_type(payload.id, &session.profile)
+        .await
+    {
+        Ok(document_type) => Ok(ApiResponse::success(document_type)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao desativar tipo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn delete_document_type(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<()>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "delete_document_type") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.delete_document_type(payload.id, &session.profile).await {
+        Ok(()) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao excluir tipo: {}", e))),
+    }
+}
+
 #[tauri::command]
 pub async fn create_document(
     db: State<'_, ArchiveDatabase>,
@@ -67,12 +292,12 @@ pub async fn create_document(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_document") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_document(&payload, &session.profile.login).await {
+    match db.create_document(&payload, &session.profile).await {
         Ok(document) => Ok(ApiResponse::success(document)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao criar documento: {}",
@@ -81,21 +306,75 @@ pub async fn create_document(
     }
 }
 
+#[tauri::command]
+pub async fn update_document(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentUpdatePayload,
+) -> Result<ApiResponse<DocumentRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "update_document") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.update_document(&payload, &session.profile).await {
+        Ok(document) => Ok(ApiResponse::success(document)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao atualizar documento: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn delete_document(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentDeletePayload,
+) -> Result<ApiResponse<DocumentRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "delete_document") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .delete_document(payload.id, &payload.reason, &session.profile)
+        .await
+    {
+        Ok(document) => Ok(ApiResponse::success(document)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao excluir documento: {}",
+            e
+        ))),
+    }
+}
+
 #[tauri::command]
 pub async fn list_employee_documents(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: EmployeeDocumentsPayload,
-) -> Result<ApiResponse<Vec<DocumentRecord>>, String> {
+) -> Result<ApiResponse<Page<DocumentRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_employee_documents") {
+        return Ok(denied.into());
     }
 
-    match db.get_employee_documents(payload.employee_id).await {
+    match db
+        .get_employee_documents(payload.employee_id, &payload.paging)
+        .await
+    {
         Ok(documents) => Ok(ApiResponse::success(documents)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao listar documentos: {}",
@@ -103,3 +382,218 @@ pub async fn list_employee_documents(
         ))),
     }
 }
+
+#[tauri::command]
+pub async fn list_expiring_documents(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: ExpiringDocumentsPayload,
+) -> Result<ApiResponse<ExpiringDocumentsReport>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_expiring_documents") {
+        return Ok(denied.into());
+    }
+
+    match db
+        .list_expiring_documents(
+            payload.days.unwrap_or(EXPIRATION_ALERT_DAYS),
+            payload.include_expired.unwrap_or(true),
+            payload.department_id,
+        )
+        .await
+    {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar vencimentos: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn get_employee_compliance(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<EmployeeCompliance>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "get_employee_compliance") {
+        return Ok(denied.into());
+    }
+
+    match db.get_employee_compliance(payload.id).await {
+        Ok(compliance) => Ok(ApiResponse::success(compliance)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao verificar conformidade: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn get_compliance_report(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: ComplianceReportPayload,
+) -> Result<ApiResponse<ComplianceReport>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "get_compliance_report") {
+        return Ok(denied.into());
+    }
+
+    match db
+        .get_compliance_report(
+            payload.department_id,
+            payload.include_compliant.unwrap_or(false),
+        )
+        .await
+    {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao gerar relatório de conformidade: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn attach_document_file(
+    db: State<'_, ArchiveDatabase>,
+    store: State<'_, AttachmentStore>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentFileAttachPayload,
+) -> Result<ApiResponse<DocumentFileAttachResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "attach_document_file") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .attach_document_file(
+            &store,
+            payload.document_id,
+            Path::new(payload.path.trim()),
+            &session.profile,
+        )
+        .await
+    {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao anexar arquivo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn list_document_files(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<Vec<DocumentFileRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_document_files") {
+        return Ok(denied.into());
+    }
+
+    match db.list_document_files(payload.id).await {
+        Ok(files) => Ok(ApiResponse::success(files)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao listar anexos: {}", e))),
+    }
+}
+
+/// Copia o anexo, já conferido, para a pasta temporária com o nome original, para
+/// ser aberto pelo visualizador do sistema sem expor o armazenamento.
+#[tauri::command]
+pub async fn open_document_file(
+    db: State<'_, ArchiveDatabase>,
+    store: State<'_, AttachmentStore>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<FileExportResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "open_document_file") {
+        return Ok(denied.into());
+    }
+
+    let file = match db.get_document_file(payload.id).await {
+        Ok(file) => file,
+        Err(e) => return Ok(ApiResponse::error(format!("Erro ao abrir anexo: {}", e))),
+    };
+    let destination = std::env::temp_dir()
+        .join("arquivo-inteligente")
+        .join(format!("{}-{}", file.id, file.file_name));
+
+    match store.copy_out(&file.sha256, &destination) {
+        Ok(()) => Ok(ApiResponse::success(FileExportResult {
+            path: destination.display().to_string(),
+            generated_at: Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao abrir anexo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn remove_document_file(
+    db: State<'_, ArchiveDatabase>,
+    store: State<'_, AttachmentStore>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<DocumentFileRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "remove_document_file") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .remove_document_file(&store, payload.id, &session.profile)
+        .await
+    {
+        Ok(file) => Ok(ApiResponse::success(file)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao remover anexo: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn verify_document_files(
+    db: State<'_, ArchiveDatabase>,
+    store: State<'_, AttachmentStore>,
+    sessions: State<'_, SessionStore>,
+    payload: DocumentFileVerifyPayload,
+) -> Result<ApiResponse<DocumentFileIntegrityReport>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "verify_document_files") {
+        return Ok(denied.into());
+    }
+
+    match db.verify_document_files(&store, payload.document_id).await {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao verificar anexos: {}",
+            e
+        ))),
+    }
+}
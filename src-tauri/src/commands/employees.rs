@@ -1,8 +1,10 @@
 use crate::db::ArchiveDatabase;
+use crate::imports;
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, EmployeeCreatePayload, EmployeeDetail, EmployeeFilterPayload, EmployeeRecord,
-    EmployeeUpdatePayload, IdPayload, SearchPayload, TerminationPayload, TerminationResult,
+    ApiResponse, CpfConflictRecord, EmployeeCreatePayload, EmployeeDetail, EmployeeFilterPayload,
+    EmployeeImportPayload, EmployeeImportReport, EmployeeRecord, EmployeeUpdatePayload, IdPayload,
+    Page, SearchPayload, TerminationPayload, TerminationResult, TokenPayload,
 };
 use tauri::State;
 use validator::Validate;
@@ -17,12 +19,12 @@ pub async fn create_employee(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_employee") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_employee(&payload.data).await {
+    match db.create_employee(&payload.data, &session.profile).await {
         Ok(employee) => Ok(ApiResponse::success(employee)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao criar funcionário: {}",
@@ -41,12 +43,15 @@ pub async fn update_employee(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "update_employee") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.update_employee(payload.employee_id, &payload.data).await {
+    match db
+        .update_employee(payload.employee_id, &payload.data, &session.profile)
+        .await
+    {
         Ok(employee) => Ok(ApiResponse::success(employee)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao atualizar funcionário: {}",
@@ -56,42 +61,67 @@ pub async fn update_employee(
 }
 
 #[tauri::command]
-pub async fn terminate_employee(
+pub async fn import_employees(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TerminationPayload,
-) -> Result<ApiResponse<TerminationResult>, String> {
+    payload: EmployeeImportPayload,
+) -> Result<ApiResponse<EmployeeImportReport>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "import_employees") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    // Terminate employee
-    let employee = match db
-        .terminate_employee(payload.employee_id, &payload.termination_date)
-        .await
+    let rows = match std::fs::read(&payload.path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| imports::parse_employee_csv(&bytes))
     {
-        Ok(emp) => emp,
+        Ok(rows) => rows,
         Err(e) => {
             return Ok(ApiResponse::error(format!(
-                "Erro ao demitir funcionário: {}",
+                "Erro ao ler arquivo de importação: {}",
                 e
             )))
         }
     };
 
-    // TODO: Transfer to archive if box_id provided
-    // TODO: Generate label data
+    match db
+        .import_employees(rows, payload.dry_run, &session.profile)
+        .await
+    {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao importar funcionários: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn terminate_employee(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: TerminationPayload,
+) -> Result<ApiResponse<TerminationResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "terminate_employee") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
 
-    Ok(ApiResponse::success(TerminationResult {
-        employee,
-        archive_item: None,
-        label: None,
-    }))
+    match db.terminate_employee(&payload, &session.profile).await {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao demitir funcionário: {}",
+            e
+        ))),
+    }
 }
 
 #[tauri::command]
@@ -99,27 +129,16 @@ pub async fn list_employees(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: EmployeeFilterPayload,
-) -> Result<ApiResponse<Vec<EmployeeRecord>>, String> {
+) -> Result<ApiResponse<Page<EmployeeRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_employees") {
+        return Ok(denied.into());
     }
 
-    let page = payload.page.unwrap_or(1);
-    let page_size = payload.page_size.unwrap_or(50);
-
-    match db
-        .list_employees(
-            payload.status.as_deref(),
-            payload.department_id,
-            page,
-            page_size,
-        )
-        .await
-    {
+    match db.list_employees(&payload.filter, &payload.paging).await {
         Ok(employees) => Ok(ApiResponse::success(employees)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao listar funcionários: {}",
@@ -138,8 +157,8 @@ pub async fn search_employees(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "search_employees") {
+        return Ok(denied.into());
     }
 
     let limit = payload.limit.unwrap_or(20);
@@ -163,40 +182,38 @@ pub async fn get_employee(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_employee") {
+        return Ok(denied.into());
     }
 
-    // Get basic employee info
-    let basic = match db.get_employee_by_id(payload.id).await {
-        Ok(emp) => emp,
-        Err(e) => {
-            return Ok(ApiResponse::error(format!(
-                "Funcionário não encontrado: {}",
-                e
-            )))
-        }
-    };
+    match db.get_employee_detail(payload.id).await {
+        Ok(detail) => Ok(ApiResponse::success(detail)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Funcionário não encontrado: {}",
+            e
+        ))),
+    }
+}
 
-    // Get related data
-    let documents = db
-        .get_employee_documents(payload.id)
-        .await
-        .unwrap_or_default();
-    let active_loans = db
-        .get_employee_active_loans(payload.id)
-        .await
-        .unwrap_or_default();
-    let drawer_position = db
-        .get_employee_drawer_position(payload.id)
-        .await
-        .ok()
-        .flatten();
-
-    Ok(ApiResponse::success(EmployeeDetail {
-        basic,
-        documents,
-        active_loans,
-        drawer_position,
-    }))
+#[tauri::command]
+pub async fn list_cpf_conflicts(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: TokenPayload,
+) -> Result<ApiResponse<Vec<CpfConflictRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_cpf_conflicts") {
+        return Ok(denied.into());
+    }
+
+    match db.list_cpf_conflicts().await {
+        Ok(conflicts) => Ok(ApiResponse::success(conflicts)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar conflitos de CPF: {}",
+            e
+        ))),
+    }
 }
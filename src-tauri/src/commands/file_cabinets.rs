@@ -18,12 +18,15 @@ pub async fn create_file_cabinet(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_file_cabinet") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_file_cabinet(&payload.data).await {
+    match db
+        .create_file_cabinet(&payload.data, &session.profile)
+        .await
+    {
         Ok(cabinet) => Ok(ApiResponse::success(cabinet)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao criar gaveteiro: {}",
@@ -42,12 +45,12 @@ pub async fn create_drawer(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_drawer") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_drawer(&payload.data).await {
+    match db.create_drawer(&payload.data, &session.profile).await {
         Ok(drawer) => Ok(ApiResponse::success(drawer)),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao criar gaveta: {}", e))),
     }
@@ -63,8 +66,8 @@ pub async fn list_file_cabinets(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_file_cabinets") {
+        return Ok(denied.into());
     }
 
     match db.list_file_cabinets().await {
@@ -86,8 +89,8 @@ pub async fn get_occupation_map(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_occupation_map") {
+        return Ok(denied.into());
     }
 
     match db.get_occupation_map().await {
@@ -109,13 +112,18 @@ pub async fn assign_employee_position(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let _session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "assign_employee_position") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
     match db
-        .assign_employee_position(payload.employee_id, payload.drawer_id, payload.position)
+        .assign_employee_position(
+            payload.employee_id,
+            payload.drawer_id,
+            payload.position,
+            &session.profile,
+        )
         .await
     {
         Ok(position) => Ok(ApiResponse::success(position)),
@@ -136,8 +144,8 @@ pub async fn suggest_reorganization(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "suggest_reorganization") {
+        return Ok(denied.into());
     }
 
     let threshold = payload.critical_threshold.unwrap_or(90);
@@ -1,71 +1,171 @@
 use crate::db::ArchiveDatabase;
+use crate::exports;
+use crate::labels;
 use crate::sessions::SessionStore;
-use crate::types::{ApiResponse, LabelData, LabelRequestPayload};
-use tauri::State;
+use crate::types::{
+    ApiResponse, LabelBatchPayload, LabelData, LabelOutput, LabelPrintOptions, LabelRequestPayload,
+    ScanPayload, ScanResult,
+};
+use tauri::{AppHandle, State};
 use validator::Validate;
 
 #[tauri::command]
 pub async fn generate_folder_label(
+    app: AppHandle,
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: LabelRequestPayload,
-) -> Result<ApiResponse<LabelData>, String> {
+) -> Result<ApiResponse<LabelOutput>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "generate_folder_label") {
+        return Ok(denied.into());
     }
 
     match db.generate_folder_label(payload.entity_id).await {
-        Ok(label) => Ok(ApiResponse::success(label)),
+        Ok(label) => Ok(render_labels(&app, &payload.options, "data", vec![label])),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao gerar etiqueta: {}", e))),
     }
 }
 
 #[tauri::command]
 pub async fn generate_envelope_label(
+    app: AppHandle,
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: LabelRequestPayload,
-) -> Result<ApiResponse<LabelData>, String> {
+) -> Result<ApiResponse<LabelOutput>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "generate_envelope_label") {
+        return Ok(denied.into());
     }
 
-    // Use format as category, default to "Pessoal"
-    let category = payload.format.as_deref().unwrap_or("Pessoal");
+    let category = payload
+        .category
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("Pessoal");
 
     match db
         .generate_envelope_label(payload.entity_id, category)
         .await
     {
-        Ok(label) => Ok(ApiResponse::success(label)),
+        Ok(label) => Ok(render_labels(&app, &payload.options, "data", vec![label])),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao gerar etiqueta: {}", e))),
     }
 }
 
 #[tauri::command]
 pub async fn generate_box_label(
+    app: AppHandle,
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: LabelRequestPayload,
-) -> Result<ApiResponse<LabelData>, String> {
+) -> Result<ApiResponse<LabelOutput>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "generate_box_label") {
+        return Ok(denied.into());
     }
 
     match db.generate_box_label(payload.entity_id).await {
-        Ok(label) => Ok(ApiResponse::success(label)),
+        Ok(label) => Ok(render_labels(&app, &payload.options, "data", vec![label])),
         Err(e) => Ok(ApiResponse::error(format!("Erro ao gerar etiqueta: {}", e))),
     }
 }
+
+#[tauri::command]
+pub async fn generate_batch_labels(
+    app: AppHandle,
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LabelBatchPayload,
+) -> Result<ApiResponse<LabelOutput>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "generate_batch_labels") {
+        return Ok(denied.into());
+    }
+
+    match db
+        .generate_folder_labels(&payload.scope, payload.scope_id)
+        .await
+    {
+        Ok(labels) => Ok(render_labels(&app, &payload.options, "pdf", labels)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao gerar etiquetas: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn resolve_scan(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: ScanPayload,
+) -> Result<ApiResponse<ScanResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "resolve_scan") {
+        return Ok(denied.into());
+    }
+
+    match db.resolve_scan(&payload.code).await {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao ler código: {}", e))),
+    }
+}
+
+/// Devolve os dados ou grava o arquivo no formato pedido (PDF em folha Pimaco ou ZPL).
+fn render_labels(
+    app: &AppHandle,
+    options: &LabelPrintOptions,
+    default_format: &str,
+    labels: Vec<LabelData>,
+) -> ApiResponse<LabelOutput> {
+    let format = options
+        .format
+        .as_deref()
+        .unwrap_or(default_format)
+        .to_string();
+    let path = options.path.as_deref();
+    let symbology = labels::Symbology::from_name(options.barcode.as_deref());
+
+    let written = match format.as_str() {
+        "pdf" => exports::export_path(app, path, "etiquetas", "pdf").and_then(|path| {
+            let layout = labels::sheet_layout_or_default(options.layout.as_deref());
+            let skip = options.start_position.unwrap_or(1) as usize - 1;
+            labels::write_pdf(&path, &labels, layout, skip, symbology)?;
+            Ok((Some(path), None))
+        }),
+        "zpl" => exports::export_path(app, path, "etiquetas", "zpl").and_then(|path| {
+            let content = labels::render_zpl(&labels, symbology);
+            std::fs::write(&path, &content)?;
+            Ok((Some(path), Some(content)))
+        }),
+        _ => Ok((None, None)),
+    };
+
+    match written {
+        Ok((path, content)) => ApiResponse::success(LabelOutput {
+            format,
+            labels,
+            path: path.map(|path| path.display().to_string()),
+            content,
+        }),
+        Err(e) => ApiResponse::error(format!("Erro ao gravar etiquetas: {}", e)),
+    }
+}
@@ -1,7 +1,10 @@
 use crate::db::ArchiveDatabase;
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, LoanPayload, LoanRecord, LoanReturnPayload, LoanWithEmployee, TokenPayload,
+    ApiResponse, IdPayload, LoanCancelPayload, LoanFilter, LoanListPayload, LoanLostPayload,
+    LoanPartialReturnPayload, LoanPartialReturnRecord, LoanPayload, LoanRecord, LoanRenewPayload,
+    LoanRenewalRecord, LoanReservationPayload, LoanReservationRecord, LoanReturnPayload,
+    LoanReturnResult, LoanWithEmployee, Page, PageRequest, TokenPayload,
 };
 use tauri::State;
 use validator::Validate;
@@ -16,12 +19,12 @@ pub async fn create_loan(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "create_loan") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
-    match db.create_loan(&payload, &session.profile.login).await {
+    match db.create_loan(&payload, &session.profile).await {
         Ok(loan) => Ok(ApiResponse::success(loan)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao criar empréstimo: {}",
@@ -35,14 +38,14 @@ pub async fn return_loan(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
     payload: LoanReturnPayload,
-) -> Result<ApiResponse<LoanRecord>, String> {
+) -> Result<ApiResponse<LoanReturnResult>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "return_loan") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
 
     match db
@@ -50,11 +53,11 @@ pub async fn return_loan(
             payload.loan_id,
             payload.actual_return_date.as_deref(),
             payload.return_notes.as_deref(),
-            &session.profile.login,
+            &session.profile,
         )
         .await
     {
-        Ok(loan) => Ok(ApiResponse::success(loan)),
+        Ok(result) => Ok(ApiResponse::success(result)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao devolver empréstimo: {}",
             e
@@ -62,21 +65,246 @@ pub async fn return_loan(
     }
 }
 
+#[tauri::command]
+pub async fn partial_return_loan(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LoanPartialReturnPayload,
+) -> Result<ApiResponse<LoanRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "partial_return_loan") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.partial_return_loan(&payload, &session.profile).await {
+        Ok(loan) => Ok(ApiResponse::success(loan)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao registrar devolução parcial: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn renew_loan(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LoanRenewPayload,
+) -> Result<ApiResponse<LoanRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "renew_loan") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .renew_loan(
+            payload.loan_id,
+            &payload.new_expected_return_date,
+            &payload.reason,
+            &session.profile,
+        )
+        .await
+    {
+        Ok(loan) => Ok(ApiResponse::success(loan)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao renovar empréstimo: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn report_lost_loan(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LoanLostPayload,
+) -> Result<ApiResponse<LoanRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "report_lost_loan") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .report_lost_loan(payload.loan_id, &payload.incident_notes, &session.profile)
+        .await
+    {
+        Ok(loan) => Ok(ApiResponse::success(loan)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao registrar extravio: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_loan(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LoanCancelPayload,
+) -> Result<ApiResponse<LoanReturnResult>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "cancel_loan") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .cancel_loan(payload.loan_id, &payload.reason, &session.profile)
+        .await
+    {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao cancelar empréstimo: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn list_loan_renewals(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<Vec<LoanRenewalRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_loan_renewals") {
+        return Ok(denied.into());
+    }
+
+    match db.list_loan_renewals(payload.id).await {
+        Ok(renewals) => Ok(ApiResponse::success(renewals)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar renovações: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn list_loan_partial_returns(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<Vec<LoanPartialReturnRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_loan_partial_returns") {
+        return Ok(denied.into());
+    }
+
+    match db.list_loan_partial_returns(payload.id).await {
+        Ok(partial_returns) => Ok(ApiResponse::success(partial_returns)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar devoluções parciais: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn reserve_folder(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: LoanReservationPayload,
+) -> Result<ApiResponse<LoanReservationRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "reserve_folder") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.reserve_folder(&payload, &session.profile).await {
+        Ok(reservation) => Ok(ApiResponse::success(reservation)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao reservar pasta: {}", e))),
+    }
+}
+
+/// Fila de espera da pasta; `id` é o funcionário.
+#[tauri::command]
+pub async fn list_folder_reservations(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<Vec<LoanReservationRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_folder_reservations") {
+        return Ok(denied.into());
+    }
+
+    match db.list_folder_reservations(payload.id).await {
+        Ok(reservations) => Ok(ApiResponse::success(reservations)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar reservas: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_reservation(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<LoanReservationRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "cancel_reservation") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.cancel_reservation(payload.id, &session.profile).await {
+        Ok(reservation) => Ok(ApiResponse::success(reservation)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao cancelar reserva: {}",
+            e
+        ))),
+    }
+}
+
 #[tauri::command]
 pub async fn list_loans(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TokenPayload,
-) -> Result<ApiResponse<Vec<LoanRecord>>, String> {
+    payload: LoanListPayload,
+) -> Result<ApiResponse<Page<LoanRecord>>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "list_loans") {
+        return Ok(denied.into());
     }
 
-    match db.list_loans(None).await {
+    match db.list_loans(&payload.filter, &payload.paging).await {
         Ok(loans) => Ok(ApiResponse::success(loans)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao listar empréstimos: {}",
@@ -95,12 +323,16 @@ pub async fn get_pending_loans(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_pending_loans") {
+        return Ok(denied.into());
     }
 
-    match db.list_loans(Some("BORROWED")).await {
-        Ok(loans) => Ok(ApiResponse::success(loans)),
+    let filter = LoanFilter {
+        status: Some("BORROWED".to_string()),
+        ..LoanFilter::default()
+    };
+    match db.list_loans(&filter, &PageRequest::all()).await {
+        Ok(loans) => Ok(ApiResponse::success(loans.items)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao listar empréstimos pendentes: {}",
             e
@@ -118,8 +350,8 @@ pub async fn get_overdue_loans(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_overdue_loans") {
+        return Ok(denied.into());
     }
 
     match db.get_overdue_loans().await {
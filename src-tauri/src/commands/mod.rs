@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod auth;
 pub mod dead_archive;
 pub mod departments;
@@ -8,4 +9,6 @@ pub mod labels;
 pub mod loans;
 pub mod movements;
 pub mod reports;
+pub mod search;
 pub mod storage;
+pub mod users;
@@ -1,6 +1,8 @@
 use crate::db::ArchiveDatabase;
 use crate::sessions::SessionStore;
-use crate::types::{ApiResponse, MovementPayload, MovementRecord, SnapshotSummary, TokenPayload};
+use crate::types::{
+    ApiResponse, MovementListPayload, MovementPayload, MovementRecord, Page, SnapshotSummary,
+};
 use tauri::State;
 use validator::Validate;
 
@@ -14,17 +16,17 @@ pub struct MovementRecordResponse {
 pub async fn movements_list(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TokenPayload,
-) -> Result<ApiResponse<Vec<MovementRecord>>, String> {
+    payload: MovementListPayload,
+) -> Result<ApiResponse<Page<MovementRecord>>, String> {
     // Validate input
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "movements_list") {
+        return Ok(denied.into());
     }
-    match db.list_movements(25).await {
+    match db.list_movements(&payload.filter, &payload.paging).await {
         Ok(records) => Ok(ApiResponse::success(records)),
         Err(error) => Ok(ApiResponse::error(error.to_string())),
     }
@@ -41,14 +43,11 @@ pub async fn movements_record(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "movements_record") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
-    match db
-        .record_movement(&session.profile.name, &payload.data)
-        .await
-    {
+    match db.record_movement(&session.profile, &payload.data).await {
         Ok(movement) => match db.snapshot().await {
             Ok(snapshot) => Ok(ApiResponse::success(MovementRecordResponse {
                 movement,
@@ -1,9 +1,13 @@
 use crate::db::ArchiveDatabase;
+use crate::exports::{self, ExcelReport};
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, DashboardStats, FileExportResult, LoansReport, MovementsReport, TokenPayload,
+    ApiResponse, ArchiveBoxFilter, DashboardStats, EmployeeFilter, ExcelExportPayload,
+    FileExportResult, LoanFilter, LoansReport, MovementFilter, MovementListPayload,
+    MovementsReport, PageRequest, TokenPayload,
 };
-use tauri::State;
+use chrono::Utc;
+use tauri::{AppHandle, State};
 use validator::Validate;
 
 #[tauri::command]
@@ -16,8 +20,8 @@ pub async fn get_dashboard_stats(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_dashboard_stats") {
+        return Ok(denied.into());
     }
 
     match db.get_dashboard_stats().await {
@@ -33,17 +37,20 @@ pub async fn get_dashboard_stats(
 pub async fn get_movements_report(
     db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TokenPayload,
+    payload: MovementListPayload,
 ) -> Result<ApiResponse<MovementsReport>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_movements_report") {
+        return Ok(denied.into());
     }
 
-    match db.get_movements_report(100).await {
+    match db
+        .get_movements_report(&payload.filter, &payload.paging)
+        .await
+    {
         Ok(report) => Ok(ApiResponse::success(report)),
         Err(e) => Ok(ApiResponse::error(format!(
             "Erro ao gerar relatório: {}",
@@ -62,8 +69,8 @@ pub async fn get_loans_report(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "get_loans_report") {
+        return Ok(denied.into());
     }
 
     match db.get_loans_report().await {
@@ -77,20 +84,68 @@ pub async fn get_loans_report(
 
 #[tauri::command]
 pub async fn export_to_excel(
-    _db: State<'_, ArchiveDatabase>,
+    app: AppHandle,
+    db: State<'_, ArchiveDatabase>,
     sessions: State<'_, SessionStore>,
-    payload: TokenPayload,
+    payload: ExcelExportPayload,
 ) -> Result<ApiResponse<FileExportResult>, String> {
     if let Err(e) = payload.validate() {
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "export_to_excel") {
+        return Ok(denied.into());
+    }
+
+    let report = match collect_excel_report(&db).await {
+        Ok(report) => report,
+        Err(e) => {
+            return Ok(ApiResponse::error(format!(
+                "Erro ao reunir dados para exportação: {}",
+                e
+            )))
+        }
+    };
+
+    let written = exports::export_path(&app, payload.path.as_deref(), "relatorio-arquivo", "xlsx")
+        .and_then(|path| {
+            exports::write_excel_report(&path, &report)?;
+            Ok(path)
+        });
+
+    match written {
+        Ok(path) => Ok(ApiResponse::success(FileExportResult {
+            path: path.display().to_string(),
+            generated_at: Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao gerar planilha: {}", e))),
     }
+}
 
-    // TODO: Implement Excel export using a library like rust_xlsxwriter
-    Ok(ApiResponse::error(
-        "Exportação para Excel ainda não implementada",
-    ))
+async fn collect_excel_report(db: &ArchiveDatabase) -> anyhow::Result<ExcelReport> {
+    Ok(ExcelReport {
+        employees: db
+            .list_employees(&EmployeeFilter::default(), &PageRequest::all())
+            .await?
+            .items,
+        open_loans: db
+            .list_loans(
+                &LoanFilter {
+                    status: Some("BORROWED".to_string()),
+                    ..LoanFilter::default()
+                },
+                &PageRequest::all(),
+            )
+            .await?
+            .items,
+        occupation: db.get_occupation_map().await?,
+        boxes: db
+            .list_archive_boxes(&ArchiveBoxFilter::default(), &PageRequest::all())
+            .await?
+            .items,
+        movements: db
+            .list_movements(&MovementFilter::default(), &PageRequest::all())
+            .await?
+            .items,
+    })
 }
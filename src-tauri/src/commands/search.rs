@@ -0,0 +1,27 @@
+use crate::db::ArchiveDatabase;
+use crate::sessions::SessionStore;
+use crate::types::{ApiResponse, SearchHit, SearchPayload};
+use tauri::State;
+use validator::Validate;
+
+#[tauri::command]
+pub async fn search(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: SearchPayload,
+) -> Result<ApiResponse<Vec<SearchHit>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "search") {
+        return Ok(denied.into());
+    }
+
+    let limit = payload.limit.unwrap_or(20);
+
+    match db.search(&payload.query, limit).await {
+        Ok(hits) => Ok(ApiResponse::success(hits)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao buscar: {}", e))),
+    }
+}
@@ -1,8 +1,7 @@
 use crate::db::ArchiveDatabase;
 use crate::sessions::SessionStore;
 use crate::types::{
-    ApiResponse, MovementData, SnapshotSummary, StorageCreatePayload, StorageUnitRecord,
-    TokenPayload,
+    ApiResponse, SnapshotSummary, StorageCreatePayload, StorageUnitRecord, TokenPayload,
 };
 use html_escape::encode_text;
 use tauri::State;
@@ -25,8 +24,8 @@ pub async fn storage_list(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    if let Err(message) = sessions.require(&payload.token) {
-        return Ok(ApiResponse::error(message));
+    if let Err(denied) = sessions.authorize(&payload.token, "storage_list") {
+        return Ok(denied.into());
     }
     match db.list_storage_units().await {
         Ok(units) => Ok(ApiResponse::success(units)),
@@ -45,42 +44,34 @@ pub async fn storage_create(
         return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
     }
 
-    let session = match sessions.require(&payload.token) {
+    let session = match sessions.authorize(&payload.token, "storage_create") {
         Ok(session) => session,
-        Err(message) => return Ok(ApiResponse::error(message)),
+        Err(denied) => return Ok(denied.into()),
     };
     match db
-        .create_storage_unit(&crate::types::StoragePayload {
-            label: encode_text(&payload.data.label).into_owned(),
-            r#type: payload.data.r#type.clone(),
-            section: payload
-                .data
-                .section
-                .as_ref()
-                .map(|s| encode_text(s).into_owned()),
-            capacity: payload.data.capacity,
-            metadata: payload.data.metadata.clone(),
-        })
+        .create_storage_unit(
+            &crate::types::StoragePayload {
+                label: encode_text(&payload.data.label).into_owned(),
+                r#type: payload.data.r#type.clone(),
+                section: payload
+                    .data
+                    .section
+                    .as_ref()
+                    .map(|s| encode_text(s).into_owned()),
+                capacity: payload.data.capacity,
+                metadata: payload.data.metadata.clone(),
+            },
+            &session.profile,
+        )
         .await
     {
-        Ok(unit) => {
-            let movement = MovementData {
-                action: "Cadastro de unidade".into(),
-                reference: payload.data.section.clone(),
-                item_label: Some(unit.label.clone()),
-                from_unit: None,
-                to_unit: payload.data.section.clone(),
-                note: Some(format!("Unidade {} criada", unit.label)),
-            };
-            let _ = db.record_movement(&session.profile.name, &movement).await;
-            match db.snapshot().await {
-                Ok(snapshot) => Ok(ApiResponse::success(StorageCreateResponse {
-                    unit,
-                    snapshot,
-                })),
-                Err(error) => Ok(ApiResponse::error(error.to_string())),
-            }
-        }
+        Ok(unit) => match db.snapshot().await {
+            Ok(snapshot) => Ok(ApiResponse::success(StorageCreateResponse {
+                unit,
+                snapshot,
+            })),
+            Err(error) => Ok(ApiResponse::error(error.to_string())),
+        },
         Err(error) => Ok(ApiResponse::error(error.to_string())),
     }
 }
@@ -0,0 +1,208 @@
+use crate::db::ArchiveDatabase;
+use crate::sessions::SessionStore;
+use crate::types::{
+    ApiResponse, IdPayload, PasswordChangePayload, TokenPayload, UserCreatePayload,
+    UserPasswordResetPayload, UserProfile, UserRecord, UserRolePayload,
+};
+use tauri::State;
+use validator::Validate;
+
+#[tauri::command]
+pub async fn list_users(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: TokenPayload,
+) -> Result<ApiResponse<Vec<UserRecord>>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    if let Err(denied) = sessions.authorize(&payload.token, "list_users") {
+        return Ok(denied.into());
+    }
+
+    match db.list_users().await {
+        Ok(users) => Ok(ApiResponse::success(users)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao listar usuários: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn create_user(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: UserCreatePayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "create_user") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.create_user(&payload.data, &session.profile).await {
+        Ok(user) => Ok(ApiResponse::success(user)),
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao criar usuário: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn update_user_role(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: UserRolePayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "update_user_role") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .update_user_role(payload.user_id, &payload.role, &session.profile)
+        .await
+    {
+        Ok(user) => {
+            // O novo perfil só vale a partir do próximo login
+            sessions.revoke_user(user.id);
+            Ok(ApiResponse::success(user))
+        }
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao alterar perfil: {}", e))),
+    }
+}
+
+#[tauri::command]
+pub async fn deactivate_user(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "deactivate_user") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    if session.profile.id == payload.id {
+        return Ok(ApiResponse::error(
+            "Você não pode desativar o próprio usuário",
+        ));
+    }
+
+    match db
+        .set_user_active(payload.id, false, &session.profile)
+        .await
+    {
+        Ok(user) => {
+            sessions.revoke_user(user.id);
+            Ok(ApiResponse::success(user))
+        }
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao desativar usuário: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn activate_user(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: IdPayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "activate_user") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db.set_user_active(payload.id, true, &session.profile).await {
+        Ok(user) => Ok(ApiResponse::success(user)),
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao reativar usuário: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn reset_user_password(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: UserPasswordResetPayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    let session = match sessions.authorize(&payload.token, "reset_user_password") {
+        Ok(session) => session,
+        Err(denied) => return Ok(denied.into()),
+    };
+
+    match db
+        .reset_user_password(payload.user_id, &payload.new_password, &session.profile)
+        .await
+    {
+        Ok(user) => {
+            sessions.revoke_user(user.id);
+            Ok(ApiResponse::success(user))
+        }
+        Err(e) => Ok(ApiResponse::error(format!(
+            "Erro ao redefinir senha: {}",
+            e
+        ))),
+    }
+}
+
+#[tauri::command]
+pub async fn change_own_password(
+    db: State<'_, ArchiveDatabase>,
+    sessions: State<'_, SessionStore>,
+    payload: PasswordChangePayload,
+) -> Result<ApiResponse<UserRecord>, String> {
+    if let Err(e) = payload.validate() {
+        return Ok(ApiResponse::error(format!("Dados inválidos: {}", e)));
+    }
+
+    // Qualquer usuário autenticado pode trocar a própria senha
+    let session = match sessions.require(&payload.token) {
+        Ok(session) => session,
+        Err(message) => return Ok(ApiResponse::error(message)),
+    };
+
+    match db
+        .change_password(
+            &session.profile,
+            &payload.current_password,
+            &payload.new_password,
+        )
+        .await
+    {
+        Ok(user) => {
+            sessions.update_profile(
+                &session.token,
+                UserProfile {
+                    must_change_password: user.must_change_password,
+                    ..session.profile
+                },
+            );
+            Ok(ApiResponse::success(user))
+        }
+        Err(e) => Ok(ApiResponse::error(format!("Erro ao alterar senha: {}", e))),
+    }
+}
@@ -0,0 +1,76 @@
+/// Apenas os dígitos do CPF. É a forma gravada no banco, para que valores com e
+/// sem máscara sejam o mesmo registro.
+pub fn normalize(cpf: &str) -> String {
+    cpf.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Confere os dois dígitos verificadores. Sequências repetidas (000.000.000-00 etc.)
+/// passam no cálculo, mas não são CPFs válidos.
+pub fn is_valid(cpf: &str) -> bool {
+    let digits: Vec<u32> = normalize(cpf)
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    if digits.len() != 11 || digits.iter().all(|d| *d == digits[0]) {
+        return false;
+    }
+
+    let check_digit = |len: usize| {
+        let sum: u32 = digits[..len]
+            .iter()
+            .enumerate()
+            .map(|(i, d)| d * (len as u32 + 1 - i as u32))
+            .sum();
+        match sum % 11 {
+            0 | 1 => 0,
+            rest => 11 - rest,
+        }
+    };
+    check_digit(9) == digits[9] && check_digit(10) == digits[10]
+}
+
+/// Máscara de exibição (000.000.000-00). Valores fora do padrão, como os que
+/// ficaram sem normalizar por conflito, são devolvidos como estão.
+pub fn format(cpf: &str) -> String {
+    let digits = normalize(cpf);
+    if digits.len() != 11 || digits.len() != cpf.len() {
+        return cpf.to_string();
+    }
+    format!(
+        "{}.{}.{}-{}",
+        &digits[..3],
+        &digits[3..6],
+        &digits[6..9],
+        &digits[9..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_cpf_with_or_without_mask() {
+        assert!(is_valid("52998224725"));
+        assert!(is_valid("529.982.247-25"));
+        assert_eq!(normalize("529.982.247-25"), "52998224725");
+        assert_eq!(normalize(" 529_982 247;25 "), "52998224725");
+    }
+
+    #[test]
+    fn rejects_wrong_check_digit_and_repeated_digits() {
+        assert!(!is_valid("529.982.247-24"));
+        assert!(!is_valid("529.982.247-15"));
+        assert!(!is_valid("111.111.111-11"));
+        assert!(!is_valid("00000000000"));
+        assert!(!is_valid("5299822472"));
+    }
+
+    #[test]
+    fn format_masks_only_normalized_values() {
+        assert_eq!(format("52998224725"), "529.982.247-25");
+        // CPF que ficou sem normalizar por conflito é exibido como foi gravado
+        assert_eq!(format("529_982_247_25"), "529_982_247_25");
+        assert_eq!(format("123"), "123");
+    }
+}
@@ -1,227 +1,51 @@
 use std::collections::HashMap;
+use std::path::Path;
 
-use anyhow::Result;
-use bcrypt::verify;
+use anyhow::bail;
+use anyhow::{Context, Result};
+use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::Utc;
-use serde_json::Value;
+use serde::Serialize;
+use serde_json::{Map, Value};
 use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
-    Row, SqlitePool,
+    Executor, Row, Sqlite, SqliteConnection, SqlitePool,
 };
 use std::str::FromStr;
+use validator::Validate;
 
+use crate::attachments::{AttachmentStore, BlobStatus};
+use crate::labels::ScanCode;
+use crate::migrations;
+use crate::permissions::Role;
 use crate::types::{
-    MovementData, MovementRecord, SnapshotSummary, StoragePayload, StorageUnitRecord, UserProfile,
+    LabelField, MovementData, MovementRecord, SnapshotSummary, StoragePayload, StorageUnitRecord,
+    UserPayload, UserProfile, UserRecord,
 };
 
-const MIGRATIONS: [&str; 40] = [
-    "CREATE TABLE IF NOT EXISTS users (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        name TEXT NOT NULL,
-        login TEXT NOT NULL UNIQUE,
-        password_hash TEXT NOT NULL,
-        role TEXT NOT NULL DEFAULT 'admin',
-        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS storage_units (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        label TEXT NOT NULL,
-        type TEXT NOT NULL,
-        section TEXT,
-        capacity INTEGER DEFAULT 0,
-        occupancy INTEGER DEFAULT 0,
-        metadata TEXT,
-        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS movements (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        reference TEXT,
-        item_label TEXT,
-        from_unit TEXT,
-        to_unit TEXT,
-        action TEXT NOT NULL,
-        note TEXT,
-        actor TEXT NOT NULL,
-        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS departments (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        name TEXT NOT NULL UNIQUE,
-        code TEXT,
-        description TEXT,
-        is_active INTEGER DEFAULT 1,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        updated_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS employees (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        full_name TEXT NOT NULL,
-        registration TEXT NOT NULL UNIQUE,
-        cpf TEXT UNIQUE,
-        department_id INTEGER REFERENCES departments(id),
-        admission_date TEXT NOT NULL,
-        termination_date TEXT,
-        status TEXT DEFAULT 'ACTIVE',
-        drawer_position_id INTEGER REFERENCES drawer_positions(id),
-        notes TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        updated_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS file_cabinets (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        number TEXT NOT NULL UNIQUE,
-        location TEXT,
-        num_drawers INTEGER NOT NULL DEFAULT 4,
-        description TEXT,
-        is_active INTEGER DEFAULT 1,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        updated_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS drawers (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        file_cabinet_id INTEGER NOT NULL REFERENCES file_cabinets(id),
-        number INTEGER NOT NULL,
-        capacity INTEGER NOT NULL DEFAULT 30,
-        label TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        UNIQUE(file_cabinet_id, number)
-    )",
-    "CREATE TABLE IF NOT EXISTS drawer_positions (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        drawer_id INTEGER NOT NULL REFERENCES drawers(id),
-        position INTEGER NOT NULL,
-        employee_id INTEGER REFERENCES employees(id),
-        is_occupied INTEGER DEFAULT 0,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        UNIQUE(drawer_id, position)
-    )",
-    "CREATE TABLE IF NOT EXISTS document_categories (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        name TEXT NOT NULL UNIQUE,
-        code TEXT NOT NULL UNIQUE,
-        description TEXT,
-        icon TEXT,
-        color TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "INSERT OR IGNORE INTO document_categories (name, code, description)
-        VALUES
-        ('Pessoal', 'PESSOAL', 'Documentos pessoais, contratos, admissão'),
-        ('Medicina do Trabalho', 'MEDICINA', 'Exames, ASOs, atestados'),
-        ('Segurança do Trabalho', 'SEGURANCA', 'EPIs, treinamentos de segurança'),
-        ('Treinamento', 'TREINAMENTO', 'Certificados, capacitações')",
-    "CREATE TABLE IF NOT EXISTS document_types (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        category_id INTEGER NOT NULL REFERENCES document_categories(id),
-        name TEXT NOT NULL,
-        retention_years INTEGER DEFAULT 5,
-        is_required INTEGER DEFAULT 0,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        UNIQUE(category_id, name)
-    )",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Contrato de Trabalho', 10 FROM document_categories WHERE code = 'PESSOAL'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'RG', 5 FROM document_categories WHERE code = 'PESSOAL'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'CPF', 5 FROM document_categories WHERE code = 'PESSOAL'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Comprovante de Residência', 2 FROM document_categories WHERE code = 'PESSOAL'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Certidão de Nascimento/Casamento', 5 FROM document_categories WHERE code = 'PESSOAL'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'ASO Admissional', 20 FROM document_categories WHERE code = 'MEDICINA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'ASO Periódico', 20 FROM document_categories WHERE code = 'MEDICINA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'ASO Demissional', 20 FROM document_categories WHERE code = 'MEDICINA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Atestado Médico', 5 FROM document_categories WHERE code = 'MEDICINA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Ficha de EPI', 5 FROM document_categories WHERE code = 'SEGURANCA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Treinamento NR', 5 FROM document_categories WHERE code = 'SEGURANCA'",
-    "INSERT OR IGNORE INTO document_types (category_id, name, retention_years)
-        SELECT id, 'Certificado de Curso', 5 FROM document_categories WHERE code = 'TREINAMENTO'",
-    "CREATE TABLE IF NOT EXISTS documents (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        employee_id INTEGER NOT NULL REFERENCES employees(id),
-        category_id INTEGER NOT NULL REFERENCES document_categories(id),
-        type_id INTEGER NOT NULL REFERENCES document_types(id),
-        description TEXT,
-        document_date TEXT,
-        filing_date TEXT DEFAULT CURRENT_TIMESTAMP,
-        expiration_date TEXT,
-        notes TEXT,
-        filed_by TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS loans (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        employee_id INTEGER NOT NULL REFERENCES employees(id),
-        requester_name TEXT NOT NULL,
-        requester_department_id INTEGER REFERENCES departments(id),
-        reason TEXT NOT NULL,
-        loan_date TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-        expected_return_date TEXT NOT NULL,
-        actual_return_date TEXT,
-        status TEXT DEFAULT 'BORROWED',
-        return_notes TEXT,
-        loaned_by TEXT NOT NULL,
-        returned_by TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-        updated_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS dead_archive_boxes (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        box_number TEXT NOT NULL UNIQUE,
-        year INTEGER NOT NULL,
-        period TEXT,
-        letter_range TEXT,
-        location TEXT,
-        capacity INTEGER DEFAULT 50,
-        current_count INTEGER DEFAULT 0,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS dead_archive_items (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        employee_id INTEGER NOT NULL REFERENCES employees(id),
-        box_id INTEGER NOT NULL REFERENCES dead_archive_boxes(id),
-        transfer_date TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-        disposal_eligible_date TEXT,
-        disposed INTEGER DEFAULT 0,
-        disposal_date TEXT,
-        disposal_term_number TEXT,
-        transferred_by TEXT NOT NULL,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE TABLE IF NOT EXISTS audit_logs (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        user_id INTEGER REFERENCES users(id),
-        action TEXT NOT NULL,
-        entity_type TEXT NOT NULL,
-        entity_id INTEGER,
-        old_values TEXT,
-        new_values TEXT,
-        ip_address TEXT,
-        created_at TEXT DEFAULT CURRENT_TIMESTAMP
-    )",
-    "CREATE INDEX IF NOT EXISTS idx_storage_updated_at ON storage_units(updated_at)",
-    "CREATE INDEX IF NOT EXISTS idx_movements_created_at ON movements(created_at)",
-    "CREATE INDEX IF NOT EXISTS idx_users_login ON users(login)",
-    "CREATE INDEX IF NOT EXISTS idx_employees_registration ON employees(registration)",
-    "CREATE INDEX IF NOT EXISTS idx_employees_status ON employees(status)",
-    "CREATE INDEX IF NOT EXISTS idx_employees_name ON employees(full_name)",
-    "CREATE INDEX IF NOT EXISTS idx_documents_employee ON documents(employee_id)",
-    "CREATE INDEX IF NOT EXISTS idx_loans_status ON loans(status)",
-    "CREATE INDEX IF NOT EXISTS idx_loans_employee ON loans(employee_id)",
-    "CREATE INDEX IF NOT EXISTS idx_dead_archive_employee ON dead_archive_items(employee_id)",
-    "CREATE INDEX IF NOT EXISTS idx_audit_created ON audit_logs(created_at)",
-    // Insert default admin user with password 'admin123'
-    "INSERT OR IGNORE INTO users (name, login, password_hash, role) VALUES ('Administrador', 'admin', '$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/X4.rMGnDIPZEmMHXO', 'admin')",
+/// Hash de `admin123` semeado por versões anteriores nas migrações.
+const LEGACY_DEFAULT_ADMIN_HASH: &str =
+    "$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/X4.rMGnDIPZEmMHXO";
+
+/// Senhas triviais recusadas na criação do primeiro administrador.
+const KNOWN_DEFAULT_PASSWORDS: [&str; 8] = [
+    "admin",
+    "admin123",
+    "administrador",
+    "123456",
+    "12345678",
+    "password",
+    "senha",
+    "senha123",
 ];
 
+fn normalize_role(role: &str) -> Result<&'static str> {
+    match Role::from_code(role) {
+        Some(role) => Ok(role.code()),
+        None => bail!("Perfil inválido: {}", role),
+    }
+}
+
 pub struct ArchiveDatabase {
     pool: SqlitePool,
 }
@@ -245,16 +69,69 @@ impl ArchiveDatabase {
             .connect_with(options)
             .await?;
 
-        let db = Self { pool };
-        db.apply_migrations().await?;
-        Ok(db)
+        migrations::run(&pool).await?;
+        Ok(Self { pool })
     }
 
-    async fn apply_migrations(&self) -> Result<()> {
-        for ddl in MIGRATIONS {
-            sqlx::query(ddl).execute(&self.pool).await?;
+    /// Cria o primeiro administrador a partir de `ARCHIVE_DEFAULT_ADMIN_LOGIN` e
+    /// `ARCHIVE_DEFAULT_ADMIN_PASSWORD` quando o banco ainda não tem usuários.
+    pub async fn bootstrap_admin(&self, login: Option<&str>, password: Option<&str>) -> Result<()> {
+        // Bancos antigos ainda podem ter o administrador semeado com a senha padrão
+        sqlx::query("UPDATE users SET must_change_password = 1 WHERE password_hash = ?")
+            .bind(LEGACY_DEFAULT_ADMIN_HASH)
+            .execute(&self.pool)
+            .await?;
+
+        let row = sqlx::query("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await?;
+        let users: i64 = row.get(0);
+        if users > 0 {
+            return Ok(());
+        }
+
+        let login = login.map(str::trim).unwrap_or_default().to_lowercase();
+        let password = password.unwrap_or_default();
+        if login.len() < 3 || password.is_empty() {
+            bail!(
+                "Defina ARCHIVE_DEFAULT_ADMIN_LOGIN e ARCHIVE_DEFAULT_ADMIN_PASSWORD no arquivo .env para criar o primeiro administrador"
+            );
         }
-        self.ensure_login_column().await?;
+        if password.len() < 8
+            || KNOWN_DEFAULT_PASSWORDS.contains(&password.to_lowercase().as_str())
+            || password.eq_ignore_ascii_case(&login)
+        {
+            bail!(
+                "ARCHIVE_DEFAULT_ADMIN_PASSWORD é uma senha padrão ou fraca; defina uma senha com pelo menos 8 caracteres"
+            );
+        }
+
+        let password_hash = hash(password, DEFAULT_COST)?;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT INTO users (name, login, password_hash, role, is_active, must_change_password, created_at)
+             VALUES ('Administrador', ?, ?, ?, 1, 1, ?)",
+        )
+        .bind(&login)
+        .bind(&password_hash)
+        .bind(Role::Admin.code())
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let admin = fetch_user(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            None,
+            "BOOTSTRAP",
+            "user",
+            admin.id,
+            None,
+            Some(&admin),
+        )
+        .await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -276,9 +153,9 @@ impl ArchiveDatabase {
 
         for term in search_terms {
             let query_str = if term.contains('%') {
-                "SELECT id, name, login, password_hash, role FROM users WHERE LOWER(login) LIKE ? LIMIT 1"
+                "SELECT id, name, login, password_hash, role, must_change_password FROM users WHERE LOWER(login) LIKE ? AND is_active = 1 LIMIT 1"
             } else {
-                "SELECT id, name, login, password_hash, role FROM users WHERE LOWER(login) = ? LIMIT 1"
+                "SELECT id, name, login, password_hash, role, must_change_password FROM users WHERE LOWER(login) = ? AND is_active = 1 LIMIT 1"
             };
 
             let record = sqlx::query(query_str)
@@ -294,6 +171,7 @@ impl ArchiveDatabase {
                         name: row.get(1),
                         login: row.get(2),
                         role: row.get(4),
+                        must_change_password: row.get::<i64, _>(5) == 1,
                     }));
                 }
             }
@@ -329,12 +207,16 @@ impl ArchiveDatabase {
         Ok(result)
     }
 
-    pub async fn create_storage_unit(&self, payload: &StoragePayload) -> Result<StorageUnitRecord> {
+    pub async fn create_storage_unit(
+        &self,
+        payload: &StoragePayload,
+        actor: &UserProfile,
+    ) -> Result<StorageUnitRecord> {
         let now = Utc::now().to_rfc3339();
         let metadata = payload
             .metadata
             .as_ref()
-            .map(|value| serde_json::to_string(value))
+            .map(serde_json::to_string)
             .transpose()?;
         let section = payload
             .section
@@ -343,6 +225,7 @@ impl ArchiveDatabase {
             .filter(|value| !value.is_empty())
             .map(|value| value.to_string());
 
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query(
             "INSERT INTO storage_units (label, type, section, capacity, occupancy, metadata, created_at, updated_at)
              VALUES (?, ?, ?, ?, 0, ?, ?, ?)",
@@ -354,43 +237,74 @@ impl ArchiveDatabase {
         .bind(metadata)
         .bind(&now)
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_storage_unit(id).await
-    }
-
-    async fn get_storage_unit(&self, id: i64) -> Result<StorageUnitRecord> {
-        let row = sqlx::query(
-            "SELECT id, label, type, section, capacity, occupancy, metadata, created_at, updated_at FROM storage_units WHERE id = ?",
+        let unit = fetch_storage_unit(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "storage_unit",
+            unit.id,
+            None,
+            Some(&unit),
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
-
-        let metadata_str: Option<String> = row.get(6);
-        let parsed = metadata_str.and_then(|json| serde_json::from_str::<Value>(&json).ok());
-
-        Ok(StorageUnitRecord {
-            id: row.get(0),
-            label: row.get(1),
-            r#type: row.get(2),
-            section: row.get(3),
-            capacity: row.get(4),
-            occupancy: row.get(5),
-            metadata: parsed,
-            created_at: row.get(7),
-            updated_at: row.get(8),
-        })
+        insert_movement(
+            &mut tx,
+            actor,
+            &MovementData {
+                action: "Cadastro de unidade".into(),
+                reference: unit.section.clone(),
+                item_label: Some(unit.label.clone()),
+                from_unit: None,
+                to_unit: unit.section.clone(),
+                note: Some(format!("Unidade {} criada", unit.label)),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(unit)
     }
 
-    pub async fn list_movements(&self, limit: i64) -> Result<Vec<MovementRecord>> {
-        let rows = sqlx::query(
-            "SELECT id, reference, item_label, from_unit, to_unit, action, note, actor, created_at
-             FROM movements ORDER BY datetime(created_at) DESC LIMIT ?",
+    pub async fn list_movements(
+        &self,
+        filter: &crate::types::MovementFilter,
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::Page<MovementRecord>> {
+        let order = order_by(
+            paging,
+            &[
+                ("created_at", "datetime(created_at)"),
+                ("action", "action"),
+                ("actor", "actor"),
+                ("reference", "reference"),
+                ("item_label", "item_label"),
+            ],
+            "datetime(created_at) DESC",
+        )?;
+
+        let total: i64 = bind_movement_filter(
+            sqlx::query(&format!(
+                "SELECT COUNT(*) FROM movements {}",
+                MOVEMENT_FILTER
+            )),
+            filter,
         )
-        .bind(limit)
+        .fetch_one(&self.pool)
+        .await?
+        .get(0);
+        let rows = bind_movement_filter(
+            sqlx::query(&format!(
+                "SELECT id, reference, item_label, from_unit, to_unit, action, note, actor, created_at
+                 FROM movements {} ORDER BY {}, id DESC LIMIT ?5 OFFSET ?6",
+                MOVEMENT_FILTER, order
+            )),
+            filter,
+        )
+        .bind(paging.page_size())
+        .bind(paging.offset())
         .fetch_all(&self.pool)
         .await?;
 
@@ -408,54 +322,18 @@ impl ArchiveDatabase {
                 created_at: row.get(8),
             });
         }
-        Ok(result)
+        Ok(crate::types::Page::new(result, total, paging))
     }
 
     pub async fn record_movement(
         &self,
-        actor: &str,
+        actor: &UserProfile,
         payload: &MovementData,
     ) -> Result<MovementRecord> {
-        let now = Utc::now().to_rfc3339();
-
-        let result = sqlx::query(
-            "INSERT INTO movements (reference, item_label, from_unit, to_unit, action, note, actor, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind(payload.reference.as_deref())
-        .bind(payload.item_label.as_deref())
-        .bind(payload.from_unit.as_deref())
-        .bind(payload.to_unit.as_deref())
-        .bind(payload.action.trim())
-        .bind(payload.note.as_deref())
-        .bind(actor)
-        .bind(&now)
-        .execute(&self.pool)
-        .await?;
-
-        let id = result.last_insert_rowid();
-        self.get_movement(id).await
-    }
-
-    async fn get_movement(&self, id: i64) -> Result<MovementRecord> {
-        let row = sqlx::query(
-            "SELECT id, reference, item_label, from_unit, to_unit, action, note, actor, created_at FROM movements WHERE id = ?",
-        )
-        .bind(id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(MovementRecord {
-            id: row.get(0),
-            reference: row.get(1),
-            item_label: row.get(2),
-            from_unit: row.get(3),
-            to_unit: row.get(4),
-            action: row.get(5),
-            note: row.get(6),
-            actor: row.get(7),
-            created_at: row.get(8),
-        })
+        let mut tx = self.pool.begin().await?;
+        let movement = insert_movement(&mut tx, actor, payload).await?;
+        tx.commit().await?;
+        Ok(movement)
     }
 
     pub async fn snapshot(&self) -> Result<SnapshotSummary> {
@@ -512,30 +390,221 @@ impl ArchiveDatabase {
         })
     }
 
-    async fn ensure_login_column(&self) -> Result<()> {
-        let rows = sqlx::query("PRAGMA table_info(users)")
-            .fetch_all(&self.pool)
-            .await?;
+    // ========================== USERS ==========================
 
-        let mut has_login = false;
-        let mut has_email = false;
+    pub async fn list_users(&self) -> Result<Vec<UserRecord>> {
+        let rows = sqlx::query(
+            "SELECT id, name, login, role, is_active, must_change_password, created_at
+             FROM users ORDER BY name ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
+        let mut result = Vec::new();
         for row in rows {
-            let name: String = row.get(1);
-            if name == "login" {
-                has_login = true;
-            }
-            if name == "email" {
-                has_email = true;
-            }
+            result.push(UserRecord {
+                id: row.get(0),
+                name: row.get(1),
+                login: row.get(2),
+                role: row.get(3),
+                is_active: row.get::<i64, _>(4) == 1,
+                must_change_password: row.get::<i64, _>(5) == 1,
+                created_at: row.get(6),
+            });
         }
+        Ok(result)
+    }
 
-        if !has_login && has_email {
-            sqlx::query("ALTER TABLE users RENAME COLUMN email TO login")
-                .execute(&self.pool)
-                .await?;
+    pub async fn create_user(
+        &self,
+        payload: &UserPayload,
+        actor: &UserProfile,
+    ) -> Result<UserRecord> {
+        let login = payload.login.trim().to_lowercase();
+        let role = normalize_role(&payload.role)?;
+        let password_hash = hash(&payload.password, DEFAULT_COST)?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+        let existing = sqlx::query("SELECT id FROM users WHERE LOWER(login) = ?")
+            .bind(&login)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if existing.is_some() {
+            bail!("Já existe um usuário com o login {}", login);
         }
-        Ok(())
+
+        let result = sqlx::query(
+            "INSERT INTO users (name, login, password_hash, role, is_active, must_change_password, created_at)
+             VALUES (?, ?, ?, ?, 1, 1, ?)",
+        )
+        .bind(payload.name.trim())
+        .bind(&login)
+        .bind(&password_hash)
+        .bind(role)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let user = fetch_user(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "user",
+            user.id,
+            None,
+            Some(&user),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    pub async fn update_user_role(
+        &self,
+        id: i64,
+        role: &str,
+        actor: &UserProfile,
+    ) -> Result<UserRecord> {
+        let role = normalize_role(role)?;
+
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_user(&mut *tx, id)
+            .await
+            .context("Usuário não encontrado")?;
+        if role != Role::Admin.code() {
+            ensure_not_last_admin(&mut tx, &before).await?;
+        }
+
+        sqlx::query("UPDATE users SET role = ? WHERE id = ?")
+            .bind(role)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        let after = fetch_user(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "ROLE_CHANGE",
+            "user",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    pub async fn set_user_active(
+        &self,
+        id: i64,
+        is_active: bool,
+        actor: &UserProfile,
+    ) -> Result<UserRecord> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_user(&mut *tx, id)
+            .await
+            .context("Usuário não encontrado")?;
+        if !is_active {
+            ensure_not_last_admin(&mut tx, &before).await?;
+        }
+
+        sqlx::query("UPDATE users SET is_active = ? WHERE id = ?")
+            .bind(if is_active { 1 } else { 0 })
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        let after = fetch_user(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            if is_active { "ACTIVATE" } else { "DEACTIVATE" },
+            "user",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    /// Senhas definidas por um administrador são provisórias e exigem troca no próximo login.
+    pub async fn reset_user_password(
+        &self,
+        id: i64,
+        new_password: &str,
+        actor: &UserProfile,
+    ) -> Result<UserRecord> {
+        self.set_password(id, new_password, true, actor, "PASSWORD_RESET")
+            .await
+    }
+
+    pub async fn change_password(
+        &self,
+        actor: &UserProfile,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<UserRecord> {
+        let row = sqlx::query("SELECT password_hash FROM users WHERE id = ? AND is_active = 1")
+            .bind(actor.id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        let Some(row) = row else {
+            bail!("Usuário não encontrado");
+        };
+        let password_hash: String = row.get(0);
+        if !verify(current_password, &password_hash)? {
+            bail!("Senha atual incorreta");
+        }
+        if current_password == new_password {
+            bail!("A nova senha deve ser diferente da atual");
+        }
+
+        self.set_password(actor.id, new_password, false, actor, "PASSWORD_CHANGE")
+            .await
+    }
+
+    async fn set_password(
+        &self,
+        id: i64,
+        new_password: &str,
+        must_change_password: bool,
+        actor: &UserProfile,
+        action: &str,
+    ) -> Result<UserRecord> {
+        let password_hash = hash(new_password, DEFAULT_COST)?;
+
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_user(&mut *tx, id)
+            .await
+            .context("Usuário não encontrado")?;
+        sqlx::query("UPDATE users SET password_hash = ?, must_change_password = ? WHERE id = ?")
+            .bind(&password_hash)
+            .bind(if must_change_password { 1 } else { 0 })
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        // O hash nunca entra na auditoria; apenas o registro público do usuário
+        let after = fetch_user(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            action,
+            "user",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(after)
     }
 
     // ========================== DEPARTMENTS ==========================
@@ -566,10 +635,12 @@ impl ArchiveDatabase {
     pub async fn create_department(
         &self,
         payload: &crate::types::DepartmentPayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::DepartmentRecord> {
         let now = Utc::now().to_rfc3339();
         let is_active = payload.is_active.unwrap_or(true);
 
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query(
             "INSERT INTO departments (name, code, description, is_active, created_at, updated_at)
              VALUES (?, ?, ?, ?, ?, ?)",
@@ -580,21 +651,35 @@ impl ArchiveDatabase {
         .bind(if is_active { 1 } else { 0 })
         .bind(&now)
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_department(id).await
+        let department = fetch_department(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "department",
+            department.id,
+            None,
+            Some(&department),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(department)
     }
 
     pub async fn update_department(
         &self,
         id: i64,
         payload: &crate::types::DepartmentPayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::DepartmentRecord> {
         let now = Utc::now().to_rfc3339();
         let is_active = payload.is_active.unwrap_or(true);
 
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_department(&mut *tx, id).await?;
         sqlx::query(
             "UPDATE departments SET name = ?, code = ?, description = ?, is_active = ?, updated_at = ?
              WHERE id = ?",
@@ -605,71 +690,84 @@ impl ArchiveDatabase {
         .bind(if is_active { 1 } else { 0 })
         .bind(&now)
         .bind(id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        self.get_department(id).await
-    }
-
-    pub async fn get_department(&self, id: i64) -> Result<crate::types::DepartmentRecord> {
-        let row = sqlx::query(
-            "SELECT id, name, code, description, is_active, created_at, updated_at
-             FROM departments WHERE id = ?",
+        let after = fetch_department(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "department",
+            id,
+            Some(&before),
+            Some(&after),
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
-
-        Ok(crate::types::DepartmentRecord {
-            id: row.get(0),
-            name: row.get(1),
-            code: row.get(2),
-            description: row.get(3),
-            is_active: row.get::<i64, _>(4) == 1,
-            created_at: row.get(5),
-            updated_at: row.get(6),
-        })
+        tx.commit().await?;
+        Ok(after)
     }
 
     // ========================== EMPLOYEES ==========================
 
     pub async fn list_employees(
         &self,
-        status: Option<&str>,
-        department_id: Option<i64>,
-        page: i64,
-        page_size: i64,
-    ) -> Result<Vec<crate::types::EmployeeRecord>> {
-        let offset = (page - 1) * page_size;
-
-        let mut query = String::from(
+        filter: &crate::types::EmployeeFilter,
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::Page<crate::types::EmployeeRecord>> {
+        let order = order_by(
+            paging,
+            &[
+                ("full_name", "e.full_name"),
+                ("registration", "e.registration"),
+                ("cpf", "e.cpf"),
+                ("department_name", "d.name"),
+                ("admission_date", "e.admission_date"),
+                ("termination_date", "e.termination_date"),
+                ("status", "e.status"),
+                ("created_at", "e.created_at"),
+                ("updated_at", "e.updated_at"),
+            ],
+            "e.full_name ASC",
+        )?;
+
+        let from = "FROM employees e
+             LEFT JOIN departments d ON e.department_id = d.id
+             LEFT JOIN drawer_positions dp ON dp.id = e.drawer_position_id
+             LEFT JOIN drawers dr ON dr.id = dp.drawer_id
+             WHERE (?1 IS NULL OR e.status = ?1)
+               AND (?2 IS NULL OR e.department_id = ?2)
+               AND (?3 IS NULL OR e.admission_date >= ?3)
+               AND (?4 IS NULL OR e.admission_date <= ?4)
+               AND (?5 IS NULL OR e.termination_date >= ?5)
+               AND (?6 IS NULL OR e.termination_date <= ?6)
+               AND (?7 IS NULL OR dr.file_cabinet_id = ?7)
+               AND (?8 IS NULL OR dp.drawer_id = ?8)
+               AND (?9 IS NULL OR e.drawer_position_id = ?9)
+               AND (?10 IS NULL OR (e.drawer_position_id IS NOT NULL) = ?10)
+               AND (?11 IS NULL OR EXISTS (
+                   SELECT 1 FROM loans l WHERE l.employee_id = e.id AND l.status = 'BORROWED'
+               ) = ?11)";
+
+        let count_sql = format!("SELECT COUNT(*) {}", from);
+        let list_sql = format!(
             "SELECT e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name as department_name,
              e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
-             e.created_at, e.updated_at
-             FROM employees e
-             LEFT JOIN departments d ON e.department_id = d.id
-             WHERE 1=1"
+             e.created_at, e.updated_at, e.termination_reason
+             {}
+             ORDER BY {}, e.id ASC LIMIT ?12 OFFSET ?13",
+            from, order
         );
 
-        if status.is_some() {
-            query.push_str(" AND e.status = ?");
-        }
-        if department_id.is_some() {
-            query.push_str(" AND e.department_id = ?");
-        }
-        query.push_str(" ORDER BY e.full_name ASC LIMIT ? OFFSET ?");
-
-        let mut q = sqlx::query(&query);
-
-        if let Some(s) = status {
-            q = q.bind(s);
-        }
-        if let Some(did) = department_id {
-            q = q.bind(did);
-        }
-        q = q.bind(page_size).bind(offset);
-
-        let rows = q.fetch_all(&self.pool).await?;
+        let total: i64 = bind_employee_filter(sqlx::query(&count_sql), filter)
+            .fetch_one(&self.pool)
+            .await?
+            .get(0);
+        let rows = bind_employee_filter(sqlx::query(&list_sql), filter)
+            .bind(paging.page_size())
+            .bind(paging.offset())
+            .fetch_all(&self.pool)
+            .await?;
 
         let mut result = Vec::new();
         for row in rows {
@@ -677,11 +775,14 @@ impl ArchiveDatabase {
                 id: row.get(0),
                 full_name: row.get(1),
                 registration: row.get(2),
-                cpf: row.get(3),
+                cpf: row
+                    .get::<Option<String>, _>(3)
+                    .map(|cpf| crate::cpf::format(&cpf)),
                 department_id: row.get(4),
                 department_name: row.get(5),
                 admission_date: row.get(6),
                 termination_date: row.get(7),
+                termination_reason: row.get(13),
                 status: row.get(8),
                 drawer_position_id: row.get(9),
                 notes: row.get(10),
@@ -689,7 +790,7 @@ impl ArchiveDatabase {
                 updated_at: row.get(12),
             });
         }
-        Ok(result)
+        Ok(crate::types::Page::new(result, total, paging))
     }
 
     pub async fn search_employees(
@@ -697,20 +798,21 @@ impl ArchiveDatabase {
         query: &str,
         limit: i64,
     ) -> Result<Vec<crate::types::EmployeeRecord>> {
-        let search_pattern = format!("%{}%", query.trim());
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
 
         let rows = sqlx::query(
             "SELECT e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name as department_name,
              e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
-             e.created_at, e.updated_at
-             FROM employees e
+             e.created_at, e.updated_at, e.termination_reason
+             FROM search_index s
+             JOIN employees e ON e.id = s.entity_id
              LEFT JOIN departments d ON e.department_id = d.id
-             WHERE e.full_name LIKE ? OR e.registration LIKE ? OR e.cpf LIKE ?
-             ORDER BY e.full_name ASC LIMIT ?"
+             WHERE search_index MATCH ? AND s.entity_type = 'employee'
+             ORDER BY bm25(search_index, 0.0, 0.0, 10.0, 1.0), e.full_name ASC LIMIT ?"
         )
-        .bind(&search_pattern)
-        .bind(&search_pattern)
-        .bind(&search_pattern)
+        .bind(&fts_query)
         .bind(limit)
         .fetch_all(&self.pool)
         .await?;
@@ -721,11 +823,14 @@ impl ArchiveDatabase {
                 id: row.get(0),
                 full_name: row.get(1),
                 registration: row.get(2),
-                cpf: row.get(3),
+                cpf: row
+                    .get::<Option<String>, _>(3)
+                    .map(|cpf| crate::cpf::format(&cpf)),
                 department_id: row.get(4),
                 department_name: row.get(5),
                 admission_date: row.get(6),
                 termination_date: row.get(7),
+                termination_reason: row.get(13),
                 status: row.get(8),
                 drawer_position_id: row.get(9),
                 notes: row.get(10),
@@ -739,41 +844,25 @@ impl ArchiveDatabase {
     pub async fn create_employee(
         &self,
         payload: &crate::types::EmployeePayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::EmployeeRecord> {
-        let now = Utc::now().to_rfc3339();
-        let status = payload.status.as_deref().unwrap_or("ACTIVE");
-
-        let result = sqlx::query(
-            "INSERT INTO employees (full_name, registration, cpf, department_id, admission_date,
-             termination_date, status, drawer_position_id, notes, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind(payload.full_name.trim())
-        .bind(payload.registration.trim())
-        .bind(payload.cpf.as_deref())
-        .bind(payload.department_id)
-        .bind(&payload.admission_date)
-        .bind(payload.termination_date.as_deref())
-        .bind(status)
-        .bind(payload.drawer_position_id)
-        .bind(payload.notes.as_deref())
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
-        .await?;
-
-        let id = result.last_insert_rowid();
-        self.get_employee_by_id(id).await
+        let mut tx = self.pool.begin().await?;
+        let employee = insert_employee(&mut tx, actor, payload).await?;
+        tx.commit().await?;
+        Ok(employee)
     }
 
     pub async fn update_employee(
         &self,
         id: i64,
         payload: &crate::types::EmployeePayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::EmployeeRecord> {
         let now = Utc::now().to_rfc3339();
         let status = payload.status.as_deref().unwrap_or("ACTIVE");
 
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_employee(&mut *tx, id).await?;
         sqlx::query(
             "UPDATE employees SET full_name = ?, registration = ?, cpf = ?, department_id = ?,
              admission_date = ?, termination_date = ?, status = ?, drawer_position_id = ?,
@@ -781,7 +870,7 @@ impl ArchiveDatabase {
         )
         .bind(payload.full_name.trim())
         .bind(payload.registration.trim())
-        .bind(payload.cpf.as_deref())
+        .bind(payload.cpf.as_deref().map(crate::cpf::normalize))
         .bind(payload.department_id)
         .bind(&payload.admission_date)
         .bind(payload.termination_date.as_deref())
@@ -790,139 +879,228 @@ impl ArchiveDatabase {
         .bind(payload.notes.as_deref())
         .bind(&now)
         .bind(id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        self.get_employee_by_id(id).await
+        let after = fetch_employee(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "employee",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        refresh_disposal_date(&mut tx, actor, id).await?;
+        tx.commit().await?;
+        Ok(after)
     }
 
+    /// Desliga o funcionário: libera a gaveta, registra a movimentação e, se
+    /// informada a caixa, transfere a pasta para o arquivo morto.
     pub async fn terminate_employee(
         &self,
-        id: i64,
-        termination_date: &str,
-    ) -> Result<crate::types::EmployeeRecord> {
+        payload: &crate::types::TerminationPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::TerminationResult> {
         let now = Utc::now().to_rfc3339();
+        let id = payload.employee_id;
+        let reason = payload
+            .reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_employee(&mut *tx, id).await?;
+        if before.status == "TERMINATED" {
+            bail!("{} já está desligado", before.full_name);
+        }
+        let location = match before.drawer_position_id {
+            Some(position_id) => folder_location(&mut tx, position_id).await?,
+            None => None,
+        };
 
         // Update employee status
         sqlx::query(
-            "UPDATE employees SET status = 'TERMINATED', termination_date = ?,
+            "UPDATE employees SET status = 'TERMINATED', termination_date = ?, termination_reason = ?,
              drawer_position_id = NULL, updated_at = ? WHERE id = ?",
         )
-        .bind(termination_date)
+        .bind(&payload.termination_date)
+        .bind(reason)
         .bind(&now)
         .bind(id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
         // Free the drawer position if assigned
-        sqlx::query(
-            "UPDATE drawer_positions SET employee_id = NULL, is_occupied = 0
-             WHERE employee_id = ?",
+        let released: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM drawer_positions WHERE employee_id = ?")
+                .bind(id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        for position_id in released {
+            let position_before = fetch_drawer_position(&mut *tx, position_id).await?;
+            sqlx::query(
+                "UPDATE drawer_positions SET employee_id = NULL, is_occupied = 0 WHERE id = ?",
+            )
+            .bind(position_id)
+            .execute(&mut *tx)
+            .await?;
+            let position_after = fetch_drawer_position(&mut *tx, position_id).await?;
+            write_audit(
+                &mut tx,
+                Some(actor.id),
+                "RELEASE_POSITION",
+                "drawer_position",
+                position_id,
+                Some(&position_before),
+                Some(&position_after),
+            )
+            .await?;
+        }
+
+        let employee = fetch_employee(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "TERMINATE",
+            "employee",
+            id,
+            Some(&before),
+            Some(&employee),
         )
-        .bind(id)
-        .execute(&self.pool)
         .await?;
 
-        self.get_employee_by_id(id).await
-    }
+        let (archive_item, to_unit) = match payload.transfer_to_box_id {
+            Some(box_id) => {
+                let retention =
+                    disposal_eligible_date(&mut tx, id, &payload.termination_date).await?;
+                let item = insert_archive_item(&mut tx, actor, id, box_id, &retention).await?;
+                let archive_box = fetch_archive_box(&mut *tx, box_id).await?;
+                (
+                    Some(item),
+                    Some(format!("Caixa {}", archive_box.box_number)),
+                )
+            }
+            None => (None, None),
+        };
 
-    pub async fn get_employee_by_id(&self, id: i64) -> Result<crate::types::EmployeeRecord> {
-        let row = sqlx::query(
-            "SELECT e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name as department_name,
-             e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
-             e.created_at, e.updated_at
-             FROM employees e
-             LEFT JOIN departments d ON e.department_id = d.id
-             WHERE e.id = ?"
+        insert_movement(
+            &mut tx,
+            actor,
+            &MovementData {
+                action: if archive_item.is_some() {
+                    "Transferência para arquivo morto".into()
+                } else {
+                    "Desligamento".into()
+                },
+                reference: Some(employee.registration.clone()),
+                item_label: Some(employee.full_name.clone()),
+                from_unit: location,
+                to_unit,
+                note: reason.map(|value| value.to_string()),
+            },
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
+        tx.commit().await?;
+
+        // O desligamento já está gravado; etiqueta que falhar pode ser reimpressa
+        let (label, box_label) = match &archive_item {
+            Some(item) => (
+                self.generate_folder_label(id).await.ok(),
+                self.generate_box_label(item.box_id).await.ok(),
+            ),
+            None => (None, None),
+        };
 
-        Ok(crate::types::EmployeeRecord {
-            id: row.get(0),
-            full_name: row.get(1),
-            registration: row.get(2),
-            cpf: row.get(3),
-            department_id: row.get(4),
-            department_name: row.get(5),
-            admission_date: row.get(6),
-            termination_date: row.get(7),
-            status: row.get(8),
-            drawer_position_id: row.get(9),
-            notes: row.get(10),
-            created_at: row.get(11),
-            updated_at: row.get(12),
+        Ok(crate::types::TerminationResult {
+            employee,
+            archive_item,
+            label,
+            box_label,
+        })
+    }
+
+    pub async fn get_employee_by_id(&self, id: i64) -> Result<crate::types::EmployeeRecord> {
+        fetch_employee(&self.pool, id).await
+    }
+
+    /// Ficha completa. Falhas nos dados relacionados não impedem a exibição do cadastro.
+    pub async fn get_employee_detail(&self, id: i64) -> Result<crate::types::EmployeeDetail> {
+        let basic = self.get_employee_by_id(id).await?;
+        let documents = self
+            .get_employee_documents(id, &crate::types::PageRequest::all())
+            .await
+            .map(|page| page.items)
+            .unwrap_or_default();
+        let active_loans = self.get_employee_active_loans(id).await.unwrap_or_default();
+        let drawer_position = self.get_employee_drawer_position(id).await.ok().flatten();
+
+        Ok(crate::types::EmployeeDetail {
+            basic,
+            documents,
+            active_loans,
+            drawer_position,
         })
     }
 
     pub async fn get_employee_documents(
         &self,
         employee_id: i64,
-    ) -> Result<Vec<crate::types::DocumentRecord>> {
-        let rows = sqlx::query(
-            "SELECT id, employee_id, category_id, type_id, description, document_date,
-             filing_date, expiration_date, notes, filed_by, created_at
-             FROM documents WHERE employee_id = ? ORDER BY filing_date DESC",
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::Page<crate::types::DocumentRecord>> {
+        let order = order_by(
+            paging,
+            &[
+                ("filing_date", "d.filing_date"),
+                ("document_date", "d.document_date"),
+                ("expiration_date", "d.expiration_date"),
+                ("created_at", "d.created_at"),
+            ],
+            "d.filing_date DESC",
+        )?;
+
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM documents WHERE employee_id = ? AND deleted_at IS NULL",
         )
         .bind(employee_id)
+        .fetch_one(&self.pool)
+        .await?;
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM documents d
+             WHERE d.employee_id = ? AND d.deleted_at IS NULL
+             ORDER BY {}, d.id DESC LIMIT ? OFFSET ?",
+            DOCUMENT_COLUMNS, order
+        ))
+        .bind(employee_id)
+        .bind(paging.page_size())
+        .bind(paging.offset())
         .fetch_all(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::DocumentRecord {
-                id: row.get(0),
-                employee_id: row.get(1),
-                category_id: row.get(2),
-                type_id: row.get(3),
-                description: row.get(4),
-                document_date: row.get(5),
-                filing_date: row.get(6),
-                expiration_date: row.get(7),
-                notes: row.get(8),
-                filed_by: row.get(9),
-                created_at: row.get(10),
-            });
-        }
-        Ok(result)
+        let documents = rows.iter().map(document_from_row).collect();
+        Ok(crate::types::Page::new(documents, total, paging))
     }
 
     pub async fn get_employee_active_loans(
         &self,
         employee_id: i64,
     ) -> Result<Vec<crate::types::LoanRecord>> {
-        let rows = sqlx::query(
-            "SELECT id, employee_id, requester_name, requester_department_id, reason,
-             loan_date, expected_return_date, actual_return_date, status, return_notes,
-             loaned_by, returned_by, created_at, updated_at
-             FROM loans WHERE employee_id = ? AND status = 'BORROWED'
-             ORDER BY loan_date DESC",
-        )
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM loans l
+             WHERE l.employee_id = ? AND l.status = 'BORROWED'
+             ORDER BY l.loan_date DESC",
+            LOAN_COLUMNS
+        ))
         .bind(employee_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::LoanRecord {
-                id: row.get(0),
-                employee_id: row.get(1),
-                requester_name: row.get(2),
-                requester_department_id: row.get(3),
-                reason: row.get(4),
-                loan_date: row.get(5),
-                expected_return_date: row.get(6),
-                actual_return_date: row.get(7),
-                status: row.get(8),
-                return_notes: row.get(9),
-                loaned_by: row.get(10),
-                returned_by: row.get(11),
-                created_at: row.get(12),
-                updated_at: row.get(13),
-            });
-        }
-        Ok(result)
+        Ok(rows.iter().map(loan_from_row).collect())
     }
 
     pub async fn get_employee_drawer_position(
@@ -947,16 +1125,162 @@ impl ArchiveDatabase {
         }))
     }
 
-    // ========================== FILE CABINETS ==========================
+    /// CPFs que a migração de normalização deixou intactos por colisão e que
+    /// ainda não foram corrigidos no cadastro.
+    pub async fn list_cpf_conflicts(&self) -> Result<Vec<crate::types::CpfConflictRecord>> {
+        let rows = sqlx::query(
+            "SELECT c.employee_id, e.full_name, e.registration, c.original_cpf, c.normalized_cpf, c.detected_at
+             FROM cpf_conflicts c
+             JOIN employees e ON e.id = c.employee_id
+             WHERE e.cpf = c.original_cpf
+             ORDER BY c.normalized_cpf, c.employee_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-    pub async fn create_file_cabinet(
-        &self,
-        payload: &crate::types::FileCabinetPayload,
+        Ok(rows
+            .iter()
+            .map(|row| crate::types::CpfConflictRecord {
+                employee_id: row.get(0),
+                full_name: row.get(1),
+                registration: row.get(2),
+                original_cpf: row.get(3),
+                normalized_cpf: row.get(4),
+                detected_at: row.get(5),
+            })
+            .collect())
+    }
+
+    /// Valida as linhas do CSV da folha e, fora do modo de simulação, grava todas
+    /// numa única transação. Qualquer linha com erro impede a gravação do lote.
+    pub async fn import_employees(
+        &self,
+        mut rows: Vec<crate::imports::EmployeeImportRow>,
+        dry_run: bool,
+        actor: &UserProfile,
+    ) -> Result<crate::types::EmployeeImportReport> {
+        let departments: HashMap<String, i64> =
+            sqlx::query("SELECT id, name, code FROM departments")
+                .fetch_all(&self.pool)
+                .await?
+                .iter()
+                .flat_map(|row| {
+                    let id: i64 = row.get(0);
+                    let name: String = row.get(1);
+                    let code: Option<String> = row.get(2);
+                    std::iter::once(name)
+                        .chain(code)
+                        .map(move |key| (key.trim().to_lowercase(), id))
+                })
+                .collect();
+
+        let existing = sqlx::query("SELECT registration, cpf FROM employees")
+            .fetch_all(&self.pool)
+            .await?;
+        // Valor `None` indica registro já gravado; `Some(linha)`, repetição no próprio arquivo
+        let mut registrations: HashMap<String, Option<usize>> = existing
+            .iter()
+            .map(|row| (row.get::<String, _>(0).trim().to_lowercase(), None))
+            .collect();
+        let mut cpfs: HashMap<String, Option<usize>> = existing
+            .iter()
+            .filter_map(|row| row.get::<Option<String>, _>(1))
+            .map(|cpf| (crate::cpf::normalize(&cpf), None))
+            .collect();
+
+        let mut lines = Vec::new();
+        for row in rows.iter_mut() {
+            let mut errors = std::mem::take(&mut row.errors);
+
+            if let Err(e) = row.payload.validate() {
+                for field_errors in e.field_errors().values() {
+                    errors.extend(field_errors.iter().map(|error| {
+                        error
+                            .message
+                            .as_ref()
+                            .map(|message| message.to_string())
+                            .unwrap_or_else(|| error.code.to_string())
+                    }));
+                }
+            }
+
+            if let Some(name) = &row.department {
+                match departments.get(&name.trim().to_lowercase()) {
+                    Some(id) => row.payload.department_id = Some(*id),
+                    None => errors.push(format!("Departamento '{}' não encontrado", name)),
+                }
+            }
+
+            let registration = row.payload.registration.trim().to_lowercase();
+            if !registration.is_empty() {
+                match registrations.get(&registration) {
+                    Some(None) => errors.push(format!(
+                        "Matrícula {} já cadastrada",
+                        row.payload.registration
+                    )),
+                    Some(Some(line)) => errors.push(format!(
+                        "Matrícula {} repetida (linha {})",
+                        row.payload.registration, line
+                    )),
+                    None => {
+                        registrations.insert(registration, Some(row.line));
+                    }
+                }
+            }
+
+            if let Some(cpf) = row.payload.cpf.as_deref().map(crate::cpf::normalize) {
+                match cpfs.get(&cpf) {
+                    Some(None) => errors.push("CPF já cadastrado".to_string()),
+                    Some(Some(line)) => errors.push(format!("CPF repetido (linha {})", line)),
+                    None => {
+                        cpfs.insert(cpf, Some(row.line));
+                    }
+                }
+            }
+
+            lines.push(crate::types::EmployeeImportLine {
+                line: row.line,
+                registration: row.payload.registration.clone(),
+                full_name: row.payload.full_name.clone(),
+                errors,
+            });
+        }
+
+        let valid_rows = lines.iter().filter(|line| line.errors.is_empty()).count();
+        let mut report = crate::types::EmployeeImportReport {
+            dry_run,
+            total_rows: lines.len(),
+            valid_rows,
+            imported_rows: 0,
+            lines,
+        };
+        if dry_run || valid_rows < report.total_rows {
+            return Ok(report);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for row in &rows {
+            insert_employee(&mut tx, actor, &row.payload)
+                .await
+                .with_context(|| format!("Linha {}", row.line))?;
+        }
+        tx.commit().await?;
+        report.imported_rows = rows.len();
+        Ok(report)
+    }
+
+    // ========================== FILE CABINETS ==========================
+
+    pub async fn create_file_cabinet(
+        &self,
+        payload: &crate::types::FileCabinetPayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::FileCabinetRecord> {
         let now = Utc::now().to_rfc3339();
         let num_drawers = payload.num_drawers.unwrap_or(4);
         let is_active = payload.is_active.unwrap_or(true);
 
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query(
             "INSERT INTO file_cabinets (number, location, num_drawers, description, is_active, created_at, updated_at)
              VALUES (?, ?, ?, ?, ?, ?, ?)"
@@ -968,54 +1292,58 @@ impl ArchiveDatabase {
         .bind(if is_active { 1 } else { 0 })
         .bind(&now)
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let cabinet_id = result.last_insert_rowid();
+        let cabinet = fetch_file_cabinet(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "file_cabinet",
+            cabinet.id,
+            None,
+            Some(&cabinet),
+        )
+        .await?;
 
         // Automatically create drawers
         for drawer_num in 1..=num_drawers {
-            sqlx::query(
+            let result = sqlx::query(
                 "INSERT INTO drawers (file_cabinet_id, number, capacity, created_at)
                  VALUES (?, ?, 30, ?)",
             )
-            .bind(cabinet_id)
+            .bind(cabinet.id)
             .bind(drawer_num)
             .bind(&now)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
-        }
-
-        self.get_file_cabinet(cabinet_id).await
-    }
 
-    pub async fn get_file_cabinet(&self, id: i64) -> Result<crate::types::FileCabinetRecord> {
-        let row = sqlx::query(
-            "SELECT id, number, location, num_drawers, description, is_active, created_at, updated_at
-             FROM file_cabinets WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&self.pool)
-        .await?;
+            let drawer = fetch_drawer(&mut *tx, result.last_insert_rowid()).await?;
+            write_audit(
+                &mut tx,
+                Some(actor.id),
+                "CREATE",
+                "drawer",
+                drawer.id,
+                None,
+                Some(&drawer),
+            )
+            .await?;
+        }
 
-        Ok(crate::types::FileCabinetRecord {
-            id: row.get(0),
-            number: row.get(1),
-            location: row.get(2),
-            num_drawers: row.get(3),
-            description: row.get(4),
-            is_active: row.get::<i64, _>(5) == 1,
-            created_at: row.get(6),
-            updated_at: row.get(7),
-        })
+        tx.commit().await?;
+        Ok(cabinet)
     }
 
     pub async fn create_drawer(
         &self,
         payload: &crate::types::DrawerPayload,
+        actor: &UserProfile,
     ) -> Result<crate::types::DrawerRecord> {
         let now = Utc::now().to_rfc3339();
 
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query(
             "INSERT INTO drawers (file_cabinet_id, number, capacity, label, created_at)
              VALUES (?, ?, ?, ?, ?)",
@@ -1025,30 +1353,22 @@ impl ArchiveDatabase {
         .bind(payload.capacity)
         .bind(payload.label.as_deref())
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_drawer(id).await
-    }
-
-    pub async fn get_drawer(&self, id: i64) -> Result<crate::types::DrawerRecord> {
-        let row = sqlx::query(
-            "SELECT id, file_cabinet_id, number, capacity, label, created_at
-             FROM drawers WHERE id = ?",
+        let drawer = fetch_drawer(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "drawer",
+            drawer.id,
+            None,
+            Some(&drawer),
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
-
-        Ok(crate::types::DrawerRecord {
-            id: row.get(0),
-            file_cabinet_id: row.get(1),
-            number: row.get(2),
-            capacity: row.get(3),
-            label: row.get(4),
-            created_at: row.get(5),
-        })
+        tx.commit().await?;
+        Ok(drawer)
     }
 
     pub async fn list_file_cabinets(&self) -> Result<Vec<crate::types::FileCabinetWithOccupancy>> {
@@ -1184,28 +1504,35 @@ impl ArchiveDatabase {
         employee_id: i64,
         drawer_id: i64,
         position: i64,
+        actor: &UserProfile,
     ) -> Result<crate::types::DrawerPositionRecord> {
         let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        let employee_before = fetch_employee(&mut *tx, employee_id).await?;
 
         // Check if position exists, if not create it
         let existing =
             sqlx::query("SELECT id FROM drawer_positions WHERE drawer_id = ? AND position = ?")
                 .bind(drawer_id)
                 .bind(position)
-                .fetch_optional(&self.pool)
+                .fetch_optional(&mut *tx)
                 .await?;
 
-        let position_id = if let Some(row) = existing {
+        let (position_id, position_before) = if let Some(row) = existing {
             let id: i64 = row.get(0);
+            let before = fetch_drawer_position(&mut *tx, id).await?;
+            if before.is_occupied && before.employee_id != Some(employee_id) {
+                bail!("Posição {} da gaveta já está ocupada", position);
+            }
             // Update existing position
             sqlx::query(
                 "UPDATE drawer_positions SET employee_id = ?, is_occupied = 1 WHERE id = ?",
             )
             .bind(employee_id)
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
-            id
+            (id, Some(before))
         } else {
             // Create new position
             let result = sqlx::query(
@@ -1216,38 +1543,77 @@ impl ArchiveDatabase {
             .bind(position)
             .bind(employee_id)
             .bind(&now)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
-            result.last_insert_rowid()
+            (result.last_insert_rowid(), None)
         };
 
-        // Update employee's drawer_position_id
-        sqlx::query("UPDATE employees SET drawer_position_id = ? WHERE id = ?")
-            .bind(position_id)
-            .bind(employee_id)
-            .execute(&self.pool)
+        // Free the slot the employee held before, if any
+        let previous: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM drawer_positions WHERE employee_id = ? AND id <> ?")
+                .bind(employee_id)
+                .bind(position_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        for previous_id in previous {
+            let released_before = fetch_drawer_position(&mut *tx, previous_id).await?;
+            sqlx::query(
+                "UPDATE drawer_positions SET employee_id = NULL, is_occupied = 0 WHERE id = ?",
+            )
+            .bind(previous_id)
+            .execute(&mut *tx)
+            .await?;
+            let released_after = fetch_drawer_position(&mut *tx, previous_id).await?;
+            write_audit(
+                &mut tx,
+                Some(actor.id),
+                "RELEASE_POSITION",
+                "drawer_position",
+                previous_id,
+                Some(&released_before),
+                Some(&released_after),
+            )
             .await?;
+        }
 
-        self.get_drawer_position(position_id).await
-    }
+        // Update employee's drawer_position_id
+        let updated =
+            sqlx::query("UPDATE employees SET drawer_position_id = ?, updated_at = ? WHERE id = ?")
+                .bind(position_id)
+                .bind(&now)
+                .bind(employee_id)
+                .execute(&mut *tx)
+                .await?;
+        if updated.rows_affected() != 1 {
+            bail!("Funcionário {} não encontrado", employee_id);
+        }
 
-    async fn get_drawer_position(&self, id: i64) -> Result<crate::types::DrawerPositionRecord> {
-        let row = sqlx::query(
-            "SELECT id, drawer_id, position, employee_id, is_occupied, created_at
-             FROM drawer_positions WHERE id = ?",
+        let position_after = fetch_drawer_position(&mut *tx, position_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "ASSIGN_POSITION",
+            "drawer_position",
+            position_id,
+            position_before.as_ref(),
+            Some(&position_after),
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
 
-        Ok(crate::types::DrawerPositionRecord {
-            id: row.get(0),
-            drawer_id: row.get(1),
-            position: row.get(2),
-            employee_id: row.get(3),
-            is_occupied: row.get::<i64, _>(4) == 1,
-            created_at: row.get(5),
-        })
+        let employee_after = fetch_employee(&mut *tx, employee_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "ASSIGN_POSITION",
+            "employee",
+            employee_id,
+            Some(&employee_before),
+            Some(&employee_after),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(position_after)
     }
 
     pub async fn suggest_reorganization(
@@ -1334,700 +1700,5120 @@ impl ArchiveDatabase {
 
     pub async fn list_document_categories(
         &self,
+        include_inactive: bool,
     ) -> Result<Vec<crate::types::DocumentCategoryRecord>> {
-        let rows = sqlx::query(
-            "SELECT id, name, code, description, icon, color, created_at
-             FROM document_categories ORDER BY name ASC",
-        )
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM document_categories
+             WHERE ? OR is_active = 1 ORDER BY name ASC",
+            DOCUMENT_CATEGORY_COLUMNS
+        ))
+        .bind(include_inactive)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::DocumentCategoryRecord {
-                id: row.get(0),
-                name: row.get(1),
-                code: row.get(2),
-                description: row.get(3),
-                icon: row.get(4),
-                color: row.get(5),
-                created_at: row.get(6),
-            });
-        }
-        Ok(result)
+        Ok(rows.iter().map(document_category_from_row).collect())
     }
 
     pub async fn list_document_types(
         &self,
         category_id: Option<i64>,
+        include_inactive: bool,
     ) -> Result<Vec<crate::types::DocumentTypeRecord>> {
-        let query = if category_id.is_some() {
-            "SELECT id, category_id, name, retention_years, is_required, created_at
-             FROM document_types WHERE category_id = ? ORDER BY name ASC"
-        } else {
-            "SELECT id, category_id, name, retention_years, is_required, created_at
-             FROM document_types ORDER BY name ASC"
-        };
-
-        let mut q = sqlx::query(query);
-        if let Some(cid) = category_id {
-            q = q.bind(cid);
-        }
-
-        let rows = q.fetch_all(&self.pool).await?;
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM document_types
+             WHERE (?1 IS NULL OR category_id = ?1) AND (?2 OR is_active = 1)
+             ORDER BY name ASC",
+            DOCUMENT_TYPE_COLUMNS
+        ))
+        .bind(category_id)
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::DocumentTypeRecord {
-                id: row.get(0),
-                category_id: row.get(1),
-                name: row.get(2),
-                retention_years: row.get(3),
-                is_required: row.get::<i64, _>(4) == 1,
-                created_at: row.get(5),
-            });
-        }
-        Ok(result)
+        Ok(rows.iter().map(document_type_from_row).collect())
     }
 
-    pub async fn create_document(
+    pub async fn create_document_category(
         &self,
-        payload: &crate::types::DocumentPayload,
-        actor: &str,
-    ) -> Result<crate::types::DocumentRecord> {
+        payload: &crate::types::DocumentCategoryPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentCategoryRecord> {
         let now = Utc::now().to_rfc3339();
 
+        let mut tx = self.pool.begin().await?;
+        ensure_unique_category(&mut tx, None, payload).await?;
         let result = sqlx::query(
-            "INSERT INTO documents (employee_id, category_id, type_id, description, document_date,
-             filing_date, expiration_date, notes, filed_by, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO document_categories (name, code, description, icon, color, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(payload.employee_id)
-        .bind(payload.category_id)
-        .bind(payload.type_id)
+        .bind(payload.name.trim())
+        .bind(&payload.code)
         .bind(payload.description.as_deref())
-        .bind(payload.document_date.as_deref())
-        .bind(&now)
-        .bind(payload.expiration_date.as_deref())
-        .bind(payload.notes.as_deref())
-        .bind(payload.filed_by.as_deref().unwrap_or(actor))
+        .bind(payload.icon.as_deref())
+        .bind(payload.color.as_deref())
+        .bind(if payload.is_active.unwrap_or(true) { 1 } else { 0 })
         .bind(&now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_document(id).await
+        let category = fetch_document_category(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "document_category",
+            category.id,
+            None,
+            Some(&category),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(category)
     }
 
-    async fn get_document(&self, id: i64) -> Result<crate::types::DocumentRecord> {
-        let row = sqlx::query(
-            "SELECT id, employee_id, category_id, type_id, description, document_date,
-             filing_date, expiration_date, notes, filed_by, created_at
-             FROM documents WHERE id = ?",
+    pub async fn update_document_category(
+        &self,
+        id: i64,
+        payload: &crate::types::DocumentCategoryPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentCategoryRecord> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document_category(&mut *tx, id)
+            .await
+            .context("Categoria não encontrada")?;
+        ensure_unique_category(&mut tx, Some(id), payload).await?;
+        let is_active = payload.is_active.unwrap_or(before.is_active);
+        sqlx::query(
+            "UPDATE document_categories SET name = ?, code = ?, description = ?, icon = ?,
+             color = ?, is_active = ? WHERE id = ?",
         )
+        .bind(payload.name.trim())
+        .bind(&payload.code)
+        .bind(payload.description.as_deref())
+        .bind(payload.icon.as_deref())
+        .bind(payload.color.as_deref())
+        .bind(if is_active { 1 } else { 0 })
         .bind(id)
-        .fetch_one(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(crate::types::DocumentRecord {
-            id: row.get(0),
-            employee_id: row.get(1),
-            category_id: row.get(2),
-            type_id: row.get(3),
-            description: row.get(4),
-            document_date: row.get(5),
-            filing_date: row.get(6),
-            expiration_date: row.get(7),
-            notes: row.get(8),
-            filed_by: row.get(9),
-            created_at: row.get(10),
-        })
+        let after = fetch_document_category(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "document_category",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        if before.is_active && !is_active {
+            deactivate_category_types(&mut tx, actor, id).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
     }
 
-    // ========================== LOANS ==========================
-
-    pub async fn create_loan(
+    /// Desativa a categoria e todos os seus tipos. Os documentos já arquivados
+    /// não mudam; a categoria só deixa de ser oferecida em novos cadastros.
+    pub async fn deactivate_document_category(
         &self,
-        payload: &crate::types::LoanPayload,
-        actor: &str,
-    ) -> Result<crate::types::LoanRecord> {
-        let now = Utc::now().to_rfc3339();
+        id: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentCategoryRecord> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document_category(&mut *tx, id)
+            .await
+            .context("Categoria não encontrada")?;
+        if !before.is_active {
+            return Ok(before);
+        }
 
-        let result = sqlx::query(
-            "INSERT INTO loans (employee_id, requester_name, requester_department_id, reason,
-             loan_date, expected_return_date, status, return_notes, loaned_by, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, 'BORROWED', ?, ?, ?, ?)"
+        sqlx::query("UPDATE document_categories SET is_active = 0 WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let after = fetch_document_category(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "document_category",
+            id,
+            Some(&before),
+            Some(&after),
         )
-        .bind(payload.employee_id)
-        .bind(&payload.requester_name)
-        .bind(payload.requester_department_id)
-        .bind(&payload.reason)
-        .bind(&now)
-        .bind(&payload.expected_return_date)
-        .bind(payload.return_notes.as_deref())
-        .bind(actor)
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
         .await?;
+        deactivate_category_types(&mut tx, actor, id).await?;
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    /// Exclui uma categoria criada por engano. Categorias com tipos ou
+    /// documentos só podem ser desativadas.
+    pub async fn delete_document_category(&self, id: i64, actor: &UserProfile) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let category = fetch_document_category(&mut *tx, id)
+            .await
+            .context("Categoria não encontrada")?;
+        let (types, documents): (i64, i64) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM document_types WHERE category_id = ?1),
+                    (SELECT COUNT(*) FROM documents WHERE category_id = ?1)",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if documents > 0 {
+            bail!(
+                "Categoria usada em {} documento(s); desative-a em vez de excluir",
+                documents
+            );
+        }
+        if types > 0 {
+            bail!(
+                "Categoria possui {} tipo(s) cadastrado(s); exclua os tipos ou desative a categoria",
+                types
+            );
+        }
 
-        let id = result.last_insert_rowid();
-        self.get_loan(id).await
+        sqlx::query("DELETE FROM document_categories WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "DELETE",
+            "document_category",
+            id,
+            Some(&category),
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
     }
 
-    pub async fn return_loan(
+    pub async fn create_document_type(
         &self,
-        loan_id: i64,
-        return_date: Option<&str>,
-        return_notes: Option<&str>,
-        actor: &str,
-    ) -> Result<crate::types::LoanRecord> {
+        payload: &crate::types::DocumentTypePayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentTypeRecord> {
         let now = Utc::now().to_rfc3339();
-        let actual_date = return_date.unwrap_or(&now);
+        let is_active = payload.is_active.unwrap_or(true);
 
-        sqlx::query(
-            "UPDATE loans SET status = 'RETURNED', actual_return_date = ?, return_notes = ?,
-             returned_by = ?, updated_at = ? WHERE id = ?",
+        let mut tx = self.pool.begin().await?;
+        ensure_type_category(&mut tx, payload.category_id, is_active).await?;
+        ensure_unique_type(&mut tx, None, payload).await?;
+        let result = sqlx::query(
+            "INSERT INTO document_types (category_id, name, retention_years, is_required, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
         )
-        .bind(actual_date)
-        .bind(return_notes)
-        .bind(actor)
+        .bind(payload.category_id)
+        .bind(payload.name.trim())
+        .bind(payload.retention_years)
+        .bind(if payload.is_required { 1 } else { 0 })
+        .bind(if is_active { 1 } else { 0 })
         .bind(&now)
-        .bind(loan_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        self.get_loan(loan_id).await
+        let document_type = fetch_document_type(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "document_type",
+            document_type.id,
+            None,
+            Some(&document_type),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(document_type)
     }
 
-    async fn get_loan(&self, id: i64) -> Result<crate::types::LoanRecord> {
-        let row = sqlx::query(
-            "SELECT id, employee_id, requester_name, requester_department_id, reason,
-             loan_date, expected_return_date, actual_return_date, status, return_notes,
-             loaned_by, returned_by, created_at, updated_at
-             FROM loans WHERE id = ?",
+    /// Altera o tipo. Mudança no prazo de guarda recalcula a data de descarte
+    /// das pastas no arquivo morto que têm documentos desse tipo.
+    pub async fn update_document_type(
+        &self,
+        id: i64,
+        payload: &crate::types::DocumentTypePayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentTypeRecord> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document_type(&mut *tx, id)
+            .await
+            .context("Tipo de documento não encontrado")?;
+        let is_active = payload.is_active.unwrap_or(before.is_active);
+        ensure_type_category(&mut tx, payload.category_id, is_active).await?;
+        ensure_unique_type(&mut tx, Some(id), payload).await?;
+
+        let employees: Vec<i64> =
+            sqlx::query_scalar("SELECT DISTINCT employee_id FROM documents WHERE type_id = ?")
+                .bind(id)
+                .fetch_all(&mut *tx)
+                .await?;
+        if payload.category_id != before.category_id && !employees.is_empty() {
+            bail!("Tipo já usado em documentos não pode mudar de categoria");
+        }
+
+        sqlx::query(
+            "UPDATE document_types SET category_id = ?, name = ?, retention_years = ?,
+             is_required = ?, is_active = ? WHERE id = ?",
         )
+        .bind(payload.category_id)
+        .bind(payload.name.trim())
+        .bind(payload.retention_years)
+        .bind(if payload.is_required { 1 } else { 0 })
+        .bind(if is_active { 1 } else { 0 })
         .bind(id)
-        .fetch_one(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(crate::types::LoanRecord {
-            id: row.get(0),
-            employee_id: row.get(1),
-            requester_name: row.get(2),
-            requester_department_id: row.get(3),
-            reason: row.get(4),
-            loan_date: row.get(5),
-            expected_return_date: row.get(6),
-            actual_return_date: row.get(7),
-            status: row.get(8),
-            return_notes: row.get(9),
-            loaned_by: row.get(10),
-            returned_by: row.get(11),
-            created_at: row.get(12),
-            updated_at: row.get(13),
-        })
+        let after = fetch_document_type(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "document_type",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        // O nome do tipo também aparece na regra de descarte registrada
+        if before.retention_years != after.retention_years || before.name != after.name {
+            for employee_id in employees {
+                refresh_disposal_date(&mut tx, actor, employee_id).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(after)
     }
 
-    pub async fn list_loans(&self, status: Option<&str>) -> Result<Vec<crate::types::LoanRecord>> {
-        let query = if status.is_some() {
-            "SELECT id, employee_id, requester_name, requester_department_id, reason,
-             loan_date, expected_return_date, actual_return_date, status, return_notes,
-             loaned_by, returned_by, created_at, updated_at
-             FROM loans WHERE status = ? ORDER BY loan_date DESC"
-        } else {
-            "SELECT id, employee_id, requester_name, requester_department_id, reason,
-             loan_date, expected_return_date, actual_return_date, status, return_notes,
-             loaned_by, returned_by, created_at, updated_at
-             FROM loans ORDER BY loan_date DESC"
-        };
-
-        let mut q = sqlx::query(query);
-        if let Some(s) = status {
-            q = q.bind(s);
+    /// Desativa o tipo: deixa de ser oferecido em novos documentos e de contar
+    /// na checagem de conformidade.
+    pub async fn deactivate_document_type(
+        &self,
+        id: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentTypeRecord> {
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document_type(&mut *tx, id)
+            .await
+            .context("Tipo de documento não encontrado")?;
+        if !before.is_active {
+            return Ok(before);
         }
+        let after = set_document_type_inactive(&mut tx, actor, before).await?;
+        tx.commit().await?;
+        Ok(after)
+    }
 
-        let rows = q.fetch_all(&self.pool).await?;
-
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::LoanRecord {
-                id: row.get(0),
-                employee_id: row.get(1),
-                requester_name: row.get(2),
-                requester_department_id: row.get(3),
-                reason: row.get(4),
-                loan_date: row.get(5),
-                expected_return_date: row.get(6),
-                actual_return_date: row.get(7),
-                status: row.get(8),
-                return_notes: row.get(9),
-                loaned_by: row.get(10),
-                returned_by: row.get(11),
-                created_at: row.get(12),
-                updated_at: row.get(13),
-            });
+    /// Exclui um tipo criado por engano. Tipos já usados em documentos só
+    /// podem ser desativados.
+    pub async fn delete_document_type(&self, id: i64, actor: &UserProfile) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let document_type = fetch_document_type(&mut *tx, id)
+            .await
+            .context("Tipo de documento não encontrado")?;
+        let documents: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM documents WHERE type_id = ?")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if documents > 0 {
+            bail!(
+                "Tipo usado em {} documento(s); desative-o em vez de excluir",
+                documents
+            );
         }
-        Ok(result)
-    }
 
-    pub async fn get_overdue_loans(&self) -> Result<Vec<crate::types::LoanWithEmployee>> {
-        let rows = sqlx::query(
-            "SELECT l.id, l.employee_id, l.requester_name, l.requester_department_id, l.reason,
-             l.loan_date, l.expected_return_date, l.actual_return_date, l.status, l.return_notes,
-             l.loaned_by, l.returned_by, l.created_at, l.updated_at,
-             e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name,
-             e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
-             e.created_at, e.updated_at
-             FROM loans l
-             JOIN employees e ON l.employee_id = e.id
-             LEFT JOIN departments d ON e.department_id = d.id
-             WHERE l.status = 'BORROWED' AND l.expected_return_date < DATE('now')
-             ORDER BY l.expected_return_date ASC",
+        sqlx::query("DELETE FROM document_types WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "DELETE",
+            "document_type",
+            id,
+            Some(&document_type),
+            None,
         )
-        .fetch_all(&self.pool)
         .await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::LoanWithEmployee {
-                loan: crate::types::LoanRecord {
-                    id: row.get(0),
-                    employee_id: row.get(1),
-                    requester_name: row.get(2),
-                    requester_department_id: row.get(3),
-                    reason: row.get(4),
-                    loan_date: row.get(5),
-                    expected_return_date: row.get(6),
-                    actual_return_date: row.get(7),
-                    status: row.get(8),
-                    return_notes: row.get(9),
-                    loaned_by: row.get(10),
-                    returned_by: row.get(11),
-                    created_at: row.get(12),
-                    updated_at: row.get(13),
-                },
-                employee: crate::types::EmployeeRecord {
-                    id: row.get(14),
-                    full_name: row.get(15),
-                    registration: row.get(16),
-                    cpf: row.get(17),
-                    department_id: row.get(18),
-                    department_name: row.get(19),
-                    admission_date: row.get(20),
-                    termination_date: row.get(21),
-                    status: row.get(22),
-                    drawer_position_id: row.get(23),
-                    notes: row.get(24),
-                    created_at: row.get(25),
-                    updated_at: row.get(26),
-                },
-            });
-        }
-        Ok(result)
-    }
-
-    // ========================== DEAD ARCHIVE ==========================
-
-    pub async fn create_archive_box(
+    pub async fn create_document(
         &self,
-        payload: &crate::types::ArchiveBoxPayload,
-    ) -> Result<crate::types::ArchiveBoxRecord> {
+        payload: &crate::types::DocumentPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentRecord> {
         let now = Utc::now().to_rfc3339();
-        let capacity = payload.capacity.unwrap_or(50);
 
+        let mut tx = self.pool.begin().await?;
+        ensure_document_type(&mut tx, payload.category_id, payload.type_id).await?;
         let result = sqlx::query(
-            "INSERT INTO dead_archive_boxes (box_number, year, period, letter_range, location, capacity, current_count, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, 0, ?)"
+            "INSERT INTO documents (employee_id, category_id, type_id, description, document_date,
+             filing_date, expiration_date, notes, filed_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
-        .bind(payload.box_number.trim())
-        .bind(payload.year)
-        .bind(payload.period.as_deref())
-        .bind(payload.letter_range.as_deref())
-        .bind(payload.location.as_deref())
-        .bind(capacity)
+        .bind(payload.employee_id)
+        .bind(payload.category_id)
+        .bind(payload.type_id)
+        .bind(payload.description.as_deref())
+        .bind(payload.document_date.as_deref())
         .bind(&now)
-        .execute(&self.pool)
+        .bind(payload.expiration_date.as_deref())
+        .bind(payload.notes.as_deref())
+        .bind(payload.filed_by.as_deref().unwrap_or(&actor.login))
+        .bind(&now)
+        .execute(&mut *tx)
         .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_archive_box(id).await
-    }
-
-    async fn get_archive_box(&self, id: i64) -> Result<crate::types::ArchiveBoxRecord> {
-        let row = sqlx::query(
-            "SELECT id, box_number, year, period, letter_range, location, capacity, current_count, created_at
-             FROM dead_archive_boxes WHERE id = ?"
+        let document = fetch_document(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "document",
+            document.id,
+            None,
+            Some(&document),
         )
-        .bind(id)
-        .fetch_one(&self.pool)
         .await?;
-
-        Ok(crate::types::ArchiveBoxRecord {
-            id: row.get(0),
-            box_number: row.get(1),
-            year: row.get(2),
-            period: row.get(3),
-            letter_range: row.get(4),
-            location: row.get(5),
-            capacity: row.get(6),
-            current_count: row.get(7),
-            created_at: row.get(8),
-        })
+        refresh_disposal_date(&mut tx, actor, document.employee_id).await?;
+        tx.commit().await?;
+        Ok(document)
     }
 
-    pub async fn list_archive_boxes(&self) -> Result<Vec<crate::types::ArchiveBoxRecord>> {
-        let rows = sqlx::query(
-            "SELECT id, box_number, year, period, letter_range, location, capacity, current_count, created_at
-             FROM dead_archive_boxes ORDER BY year DESC, box_number ASC"
+    /// Corrige um documento arquivado. Troca de tipo recalcula a data de
+    /// descarte, que depende do prazo de guarda do tipo.
+    pub async fn update_document(
+        &self,
+        payload: &crate::types::DocumentUpdatePayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentRecord> {
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document(&mut *tx, payload.id)
+            .await
+            .context("Documento não encontrado")?;
+        if before.deleted_at.is_some() {
+            bail!("Documento excluído não pode ser alterado");
+        }
+        ensure_document_type(&mut tx, payload.category_id, payload.type_id).await?;
+
+        sqlx::query(
+            "UPDATE documents SET category_id = ?, type_id = ?, description = ?, document_date = ?,
+             expiration_date = ?, notes = ?, updated_at = ? WHERE id = ?",
         )
-        .fetch_all(&self.pool)
+        .bind(payload.category_id)
+        .bind(payload.type_id)
+        .bind(payload.description.as_deref())
+        .bind(payload.document_date.as_deref())
+        .bind(payload.expiration_date.as_deref())
+        .bind(payload.notes.as_deref())
+        .bind(&now)
+        .bind(payload.id)
+        .execute(&mut *tx)
         .await?;
 
-        let mut result = Vec::new();
-        for row in rows {
-            result.push(crate::types::ArchiveBoxRecord {
-                id: row.get(0),
-                box_number: row.get(1),
-                year: row.get(2),
-                period: row.get(3),
-                letter_range: row.get(4),
-                location: row.get(5),
-                capacity: row.get(6),
-                current_count: row.get(7),
-                created_at: row.get(8),
-            });
+        let after = fetch_document(&mut *tx, payload.id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "UPDATE",
+            "document",
+            after.id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        if before.type_id != after.type_id {
+            refresh_disposal_date(&mut tx, actor, after.employee_id).await?;
         }
-        Ok(result)
+        tx.commit().await?;
+        Ok(after)
     }
 
-    pub async fn transfer_to_archive(
+    /// Exclusão lógica: o documento sai das listas, da conformidade e do
+    /// cálculo de descarte, mas a linha e os anexos continuam guardados.
+    pub async fn delete_document(
         &self,
-        employee_id: i64,
-        box_id: i64,
-        disposal_eligible_date: Option<&str>,
-        actor: &str,
-    ) -> Result<crate::types::ArchiveItemRecord> {
+        id: i64,
+        reason: &str,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentRecord> {
         let now = Utc::now().to_rfc3339();
 
-        let result = sqlx::query(
-            "INSERT INTO dead_archive_items (employee_id, box_id, transfer_date, disposal_eligible_date, transferred_by, created_at)
-             VALUES (?, ?, ?, ?, ?, ?)"
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_document(&mut *tx, id)
+            .await
+            .context("Documento não encontrado")?;
+        if before.deleted_at.is_some() {
+            bail!("Documento já foi excluído");
+        }
+
+        sqlx::query(
+            "UPDATE documents SET deleted_at = ?, deleted_by = ?, deletion_reason = ?, updated_at = ?
+             WHERE id = ?",
         )
-        .bind(employee_id)
-        .bind(box_id)
         .bind(&now)
-        .bind(disposal_eligible_date)
-        .bind(actor)
+        .bind(&actor.login)
+        .bind(reason.trim())
         .bind(&now)
-        .execute(&self.pool)
+        .bind(id)
+        .execute(&mut *tx)
         .await?;
 
-        // Update box count
-        sqlx::query("UPDATE dead_archive_boxes SET current_count = current_count + 1 WHERE id = ?")
-            .bind(box_id)
-            .execute(&self.pool)
+        let after = fetch_document(&mut *tx, id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "DELETE",
+            "document",
+            id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        refresh_disposal_date(&mut tx, actor, after.employee_id).await?;
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    /// Documentos vencidos ou que vencem em até `days` dias, agrupados por
+    /// departamento e funcionário. Funcionários desligados ficam de fora.
+    pub async fn list_expiring_documents(
+        &self,
+        days: i64,
+        include_expired: bool,
+        department_id: Option<i64>,
+    ) -> Result<crate::types::ExpiringDocumentsReport> {
+        let sql = format!(
+            "SELECT {},
+             c.name, t.name, e.full_name, e.registration, e.department_id, dep.name,
+             CAST(julianday(DATE(d.expiration_date)) - julianday(DATE('now')) AS INTEGER)
+             FROM documents d
+             JOIN employees e ON e.id = d.employee_id
+             JOIN document_categories c ON c.id = d.category_id
+             JOIN document_types t ON t.id = d.type_id
+             LEFT JOIN departments dep ON dep.id = e.department_id
+             WHERE {}
+               AND DATE(d.expiration_date) <= DATE('now', '+' || ?1 || ' days')
+               AND (?2 OR DATE(d.expiration_date) >= DATE('now'))
+               AND (?3 IS NULL OR e.department_id = ?3)
+             ORDER BY dep.name IS NULL, dep.name COLLATE NOCASE, e.department_id,
+               e.full_name COLLATE NOCASE, e.id, d.expiration_date",
+            DOCUMENT_COLUMNS, PENDING_EXPIRATION
+        );
+        let rows = sqlx::query(&sql)
+            .bind(days)
+            .bind(include_expired)
+            .bind(department_id)
+            .fetch_all(&self.pool)
             .await?;
 
-        let id = result.last_insert_rowid();
-        self.get_archive_item(id).await
+        let mut report = crate::types::ExpiringDocumentsReport {
+            days,
+            expired_count: 0,
+            expiring_count: 0,
+            departments: Vec::new(),
+        };
+        for row in rows {
+            let days_remaining: i64 = row.get(21);
+            let expired = days_remaining < 0;
+            let department_id: Option<i64> = row.get(19);
+            let employee_id: i64 = row.get(1);
+
+            if report
+                .departments
+                .last()
+                .is_none_or(|group| group.department_id != department_id)
+            {
+                report
+                    .departments
+                    .push(crate::types::ExpiringDepartmentGroup {
+                        department_id,
+                        department_name: row.get(20),
+                        expired_count: 0,
+                        expiring_count: 0,
+                        employees: Vec::new(),
+                    });
+            }
+            let last = report.departments.len() - 1;
+            let department = &mut report.departments[last];
+            if department
+                .employees
+                .last()
+                .is_none_or(|group| group.employee_id != employee_id)
+            {
+                department
+                    .employees
+                    .push(crate::types::ExpiringEmployeeGroup {
+                        employee_id,
+                        full_name: row.get(17),
+                        registration: row.get(18),
+                        documents: Vec::new(),
+                    });
+            }
+
+            if expired {
+                department.expired_count += 1;
+                report.expired_count += 1;
+            } else {
+                department.expiring_count += 1;
+                report.expiring_count += 1;
+            }
+            let last = department.employees.len() - 1;
+            let employee = &mut department.employees[last];
+            employee.documents.push(crate::types::ExpiringDocument {
+                document: document_from_row(&row),
+                category_name: row.get(15),
+                type_name: row.get(16),
+                days_remaining,
+                expired,
+            });
+        }
+        Ok(report)
     }
 
-    async fn get_archive_item(&self, id: i64) -> Result<crate::types::ArchiveItemRecord> {
-        let row = sqlx::query(
-            "SELECT id, employee_id, box_id, transfer_date, disposal_eligible_date, disposed, disposal_date, disposal_term_number, transferred_by, created_at
-             FROM dead_archive_items WHERE id = ?"
+    // ========================== COMPLIANCE ==========================
+
+    pub async fn get_employee_compliance(
+        &self,
+        employee_id: i64,
+    ) -> Result<crate::types::EmployeeCompliance> {
+        self.employee_compliance(None, Some(employee_id))
+            .await?
+            .pop()
+            .context("Funcionário não encontrado")
+    }
+
+    /// Conformidade documental dos funcionários não desligados, consolidada por
+    /// departamento. Pedido trimestralmente pela auditoria.
+    pub async fn get_compliance_report(
+        &self,
+        department_id: Option<i64>,
+        include_compliant: bool,
+    ) -> Result<crate::types::ComplianceReport> {
+        let employees = self.employee_compliance(department_id, None).await?;
+        let required_types: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM document_types WHERE is_required = 1 AND is_active = 1",
         )
-        .bind(id)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(crate::types::ArchiveItemRecord {
-            id: row.get(0),
-            employee_id: row.get(1),
-            box_id: row.get(2),
-            transfer_date: row.get(3),
-            disposal_eligible_date: row.get(4),
-            disposed: row.get::<i64, _>(5) == 1,
-            disposal_date: row.get(6),
-            disposal_term_number: row.get(7),
-            transferred_by: row.get(8),
-            created_at: row.get(9),
+        let mut departments: Vec<crate::types::DepartmentCompliance> = Vec::new();
+        let mut department_required: Vec<i64> = Vec::new();
+        let mut department_compliant: Vec<i64> = Vec::new();
+        for employee in &employees {
+            if departments
+                .last()
+                .is_none_or(|group| group.department_id != employee.department_id)
+            {
+                departments.push(crate::types::DepartmentCompliance {
+                    department_id: employee.department_id,
+                    department_name: employee.department_name.clone(),
+                    employees: 0,
+                    compliant_employees: 0,
+                    missing_documents: 0,
+                    expired_documents: 0,
+                    compliance_rate: 100.0,
+                });
+                department_required.push(0);
+                department_compliant.push(0);
+            }
+            let last = departments.len() - 1;
+            let department = &mut departments[last];
+            department.employees += 1;
+            if employee.compliant_count == employee.required_count {
+                department.compliant_employees += 1;
+            }
+            department.missing_documents += employee.missing.len() as i64;
+            department.expired_documents += employee.expired.len() as i64;
+            department_required[last] += employee.required_count;
+            department_compliant[last] += employee.compliant_count;
+            department.compliance_rate =
+                compliance_rate(department_compliant[last], department_required[last]);
+        }
+
+        let compliant_employees = employees
+            .iter()
+            .filter(|employee| employee.compliant_count == employee.required_count)
+            .count() as i64;
+        let compliance_rate = compliance_rate(
+            department_compliant.iter().sum(),
+            department_required.iter().sum(),
+        );
+        let total = employees.len() as i64;
+        let details = employees
+            .into_iter()
+            .filter(|employee| {
+                include_compliant || employee.compliant_count < employee.required_count
+            })
+            .collect();
+
+        Ok(crate::types::ComplianceReport {
+            generated_at: Utc::now().to_rfc3339(),
+            required_types,
+            employees: total,
+            compliant_employees,
+            compliance_rate,
+            departments,
+            details,
         })
     }
 
-    pub async fn get_disposal_candidates(&self) -> Result<Vec<crate::types::DisposalCandidate>> {
+    /// Para cada funcionário e tipo obrigatório, considera o documento mais recente
+    /// pela data do documento (ou, sem ela, a de arquivamento): sem vencimento ou
+    /// com vencimento futuro está em dia; vencido, pendente.
+    /// Com `employee_id`, inclui o funcionário mesmo que desligado.
+    async fn employee_compliance(
+        &self,
+        department_id: Option<i64>,
+        employee_id: Option<i64>,
+    ) -> Result<Vec<crate::types::EmployeeCompliance>> {
         let rows = sqlx::query(
-            "SELECT dai.id, dai.employee_id, dai.box_id, dai.transfer_date, dai.disposal_eligible_date,
-             dai.disposed, dai.disposal_date, dai.disposal_term_number, dai.transferred_by, dai.created_at,
-             e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name,
-             e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
-             e.created_at, e.updated_at
-             FROM dead_archive_items dai
-             JOIN employees e ON dai.employee_id = e.id
-             LEFT JOIN departments d ON e.department_id = d.id
-             WHERE dai.disposed = 0 AND dai.disposal_eligible_date <= DATE('now')
-             ORDER BY dai.disposal_eligible_date ASC"
+            "SELECT e.id, e.full_name, e.registration, e.department_id, dep.name,
+             t.id, t.name, c.name, latest.id, latest.expiration_date,
+             latest.expiration_date IS NOT NULL AND DATE(latest.expiration_date) < DATE('now')
+             FROM employees e
+             LEFT JOIN departments dep ON dep.id = e.department_id
+             LEFT JOIN document_types t ON t.is_required = 1 AND t.is_active = 1
+             LEFT JOIN document_categories c ON c.id = t.category_id
+             LEFT JOIN (
+               SELECT id, employee_id, type_id, expiration_date,
+                 ROW_NUMBER() OVER (
+                   PARTITION BY employee_id, type_id
+                   ORDER BY DATE(COALESCE(document_date, filing_date)) DESC, id DESC
+                 ) AS rank
+               FROM documents WHERE deleted_at IS NULL
+             ) latest ON latest.employee_id = e.id AND latest.type_id = t.id AND latest.rank = 1
+             WHERE (?1 IS NULL OR e.department_id = ?1)
+               AND (?2 IS NULL OR e.id = ?2)
+               AND (?2 IS NOT NULL OR e.status != 'TERMINATED')
+             ORDER BY dep.name IS NULL, dep.name COLLATE NOCASE, e.department_id,
+               e.full_name COLLATE NOCASE, e.id, c.name, t.name",
         )
+        .bind(department_id)
+        .bind(employee_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut result = Vec::new();
+        let mut employees: Vec<crate::types::EmployeeCompliance> = Vec::new();
         for row in rows {
-            result.push(crate::types::DisposalCandidate {
-                archive_item: crate::types::ArchiveItemRecord {
-                    id: row.get(0),
-                    employee_id: row.get(1),
-                    box_id: row.get(2),
-                    transfer_date: row.get(3),
-                    disposal_eligible_date: row.get(4),
-                    disposed: row.get::<i64, _>(5) == 1,
-                    disposal_date: row.get(6),
-                    disposal_term_number: row.get(7),
-                    transferred_by: row.get(8),
-                    created_at: row.get(9),
-                },
-                employee: crate::types::EmployeeRecord {
-                    id: row.get(10),
-                    full_name: row.get(11),
-                    registration: row.get(12),
-                    cpf: row.get(13),
-                    department_id: row.get(14),
-                    department_name: row.get(15),
-                    admission_date: row.get(16),
-                    termination_date: row.get(17),
-                    status: row.get(18),
-                    drawer_position_id: row.get(19),
-                    notes: row.get(20),
-                    created_at: row.get(21),
-                    updated_at: row.get(22),
-                },
-            });
+            let id: i64 = row.get(0);
+            if employees
+                .last()
+                .is_none_or(|employee| employee.employee_id != id)
+            {
+                employees.push(crate::types::EmployeeCompliance {
+                    employee_id: id,
+                    full_name: row.get(1),
+                    registration: row.get(2),
+                    department_id: row.get(3),
+                    department_name: row.get(4),
+                    required_count: 0,
+                    compliant_count: 0,
+                    missing: Vec::new(),
+                    expired: Vec::new(),
+                    compliance_rate: 100.0,
+                });
+            }
+            let last = employees.len() - 1;
+            let employee = &mut employees[last];
+
+            let Some(type_id) = row.get::<Option<i64>, _>(5) else {
+                continue;
+            };
+            let gap = crate::types::ComplianceGap {
+                type_id,
+                type_name: row.get(6),
+                category_name: row.get(7),
+                document_id: row.get(8),
+                expiration_date: row.get(9),
+            };
+            employee.required_count += 1;
+            if gap.document_id.is_none() {
+                employee.missing.push(gap);
+            } else if row.get::<bool, _>(10) {
+                employee.expired.push(gap);
+            } else {
+                employee.compliant_count += 1;
+            }
+            employee.compliance_rate =
+                compliance_rate(employee.compliant_count, employee.required_count);
         }
-        Ok(result)
+        Ok(employees)
     }
 
-    pub async fn register_disposal(
+    // ========================== DOCUMENT FILES ==========================
+
+    pub async fn list_document_files(
         &self,
-        item_ids: &[i64],
-        term_number: Option<&str>,
-    ) -> Result<crate::types::DisposalTerm> {
-        let now = Utc::now().to_rfc3339();
-        let term = term_number
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| format!("TERMO-{}", now.replace(":", "-")));
+        document_id: i64,
+    ) -> Result<Vec<crate::types::DocumentFileRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM document_files WHERE document_id = ? ORDER BY created_at, id",
+            DOCUMENT_FILE_COLUMNS
+        ))
+        .bind(document_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-        let mut items = Vec::new();
-        for id in item_ids {
-            sqlx::query(
-                "UPDATE dead_archive_items SET disposed = 1, disposal_date = ?, disposal_term_number = ?
-                 WHERE id = ?"
-            )
-            .bind(&now)
-            .bind(&term)
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
+        Ok(rows.iter().map(document_file_from_row).collect())
+    }
 
-            items.push(self.get_archive_item(*id).await?);
-        }
+    pub async fn get_document_file(&self, id: i64) -> Result<crate::types::DocumentFileRecord> {
+        fetch_document_file(&self.pool, id)
+            .await
+            .context("Anexo não encontrado")
+    }
 
-        Ok(crate::types::DisposalTerm {
-            term_number: term,
-            generated_at: now,
-            items,
-            generated_by: "Sistema".to_string(),
-        })
+    /// Copia o arquivo para o armazenamento e o vincula ao documento. O mesmo
+    /// conteúdo não pode ser anexado duas vezes ao mesmo documento.
+    pub async fn attach_document_file(
+        &self,
+        store: &AttachmentStore,
+        document_id: i64,
+        source: &Path,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentFileAttachResult> {
+        let document = fetch_document(&self.pool, document_id)
+            .await
+            .context("Documento não encontrado")?;
+        if document.deleted_at.is_some() {
+            bail!("Documento excluído não recebe anexos");
+        }
+        let file_name = source
+            .file_name()
+            .and_then(|name| name.to_str())
+            .context("Nome de arquivo inválido")?
+            .to_string();
+        let mime_type = crate::attachments::mime_type(&file_name)?;
+
+        // Uma remoção do mesmo conteúdo não pode apagá-lo entre o `put` e o registro
+        let _mutations = store.lock().await;
+        let (sha256, size_bytes) = store.put(source)?;
+        let result = self
+            .insert_document_file(
+                document_id,
+                &sha256,
+                &file_name,
+                mime_type,
+                size_bytes,
+                actor,
+            )
+            .await;
+        match result {
+            Ok(attached) => Ok(attached),
+            Err(e) => {
+                // Não deixa no armazenamento um conteúdo que nenhum anexo referencia
+                self.remove_unreferenced_blob(store, &sha256).await?;
+                Err(e)
+            }
+        }
     }
 
-    // ========================== REPORTS ==========================
+    /// Apaga o conteúdo guardado se nenhum anexo o referencia. Quem chama
+    /// segura a trava do armazenamento (`AttachmentStore::lock`).
+    async fn remove_unreferenced_blob(&self, store: &AttachmentStore, sha256: &str) -> Result<()> {
+        let referenced: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM document_files WHERE sha256 = ?")
+                .bind(sha256)
+                .fetch_one(&self.pool)
+                .await?;
+        if referenced == 0 {
+            store.remove(sha256)?;
+        }
+        Ok(())
+    }
 
-    pub async fn get_dashboard_stats(&self) -> Result<crate::types::DashboardStats> {
+    async fn insert_document_file(
+        &self,
+        document_id: i64,
+        sha256: &str,
+        file_name: &str,
+        mime_type: &str,
+        size_bytes: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentFileAttachResult> {
         let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM document_files WHERE sha256 = ? ORDER BY id",
+            DOCUMENT_FILE_COLUMNS
+        ))
+        .bind(sha256)
+        .fetch_all(&mut *tx)
+        .await?;
+        let duplicates: Vec<crate::types::DocumentFileRecord> =
+            rows.iter().map(document_file_from_row).collect();
+        if let Some(existing) = duplicates
+            .iter()
+            .find(|file| file.document_id == document_id)
+        {
+            bail!(
+                "Este arquivo já está anexado ao documento como {}",
+                existing.file_name
+            );
+        }
 
-        // Active employees
-        let active_row = sqlx::query("SELECT COUNT(*) FROM employees WHERE status = 'ACTIVE'")
-            .fetch_one(&self.pool)
-            .await?;
-        let active_employees: i64 = active_row.get(0);
+        let result = sqlx::query(
+            "INSERT INTO document_files (document_id, sha256, file_name, mime_type, size_bytes,
+             attached_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(document_id)
+        .bind(sha256)
+        .bind(file_name)
+        .bind(mime_type)
+        .bind(size_bytes)
+        .bind(&actor.login)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
 
-        // Terminated employees
-        let terminated_row =
-            sqlx::query("SELECT COUNT(*) FROM employees WHERE status = 'TERMINATED'")
-                .fetch_one(&self.pool)
-                .await?;
-        let terminated_employees: i64 = terminated_row.get(0);
+        let file = fetch_document_file(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "document_file",
+            file.id,
+            None,
+            Some(&file),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(crate::types::DocumentFileAttachResult { file, duplicates })
+    }
 
-        // Open loans
-        let open_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED'")
-            .fetch_one(&self.pool)
+    /// Desvincula o anexo. O conteúdo só sai do armazenamento quando nenhum outro
+    /// documento o referencia.
+    pub async fn remove_document_file(
+        &self,
+        store: &AttachmentStore,
+        id: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DocumentFileRecord> {
+        let _mutations = store.lock().await;
+        let mut tx = self.pool.begin().await?;
+        let file = fetch_document_file(&mut *tx, id)
+            .await
+            .context("Anexo não encontrado")?;
+
+        sqlx::query("DELETE FROM document_files WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
             .await?;
-        let open_loans: i64 = open_row.get(0);
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "DELETE",
+            "document_file",
+            file.id,
+            Some(&file),
+            None,
+        )
+        .await?;
+        tx.commit().await?;
 
-        // Overdue loans
-        let overdue_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED' AND expected_return_date < DATE('now')")
-            .fetch_one(&self.pool)
-            .await?;
-        let overdue_loans: i64 = overdue_row.get(0);
-
-        // Archive boxes
-        let boxes_row = sqlx::query("SELECT COUNT(*) FROM dead_archive_boxes")
-            .fetch_one(&self.pool)
-            .await?;
-        let archive_boxes: i64 = boxes_row.get(0);
+        self.remove_unreferenced_blob(store, &file.sha256).await?;
+        Ok(file)
+    }
 
-        // Critical cabinets
-        let occupation_map = self.get_occupation_map().await?;
-        let critical_cabinets: Vec<crate::types::CabinetOccupationNode> = occupation_map
-            .cabinets
-            .into_iter()
-            .filter(|c| c.status == "CRITICAL")
-            .collect();
+    /// Recalcula o hash dos arquivos guardados e aponta os ausentes ou alterados.
+    pub async fn verify_document_files(
+        &self,
+        store: &AttachmentStore,
+        document_id: Option<i64>,
+    ) -> Result<crate::types::DocumentFileIntegrityReport> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM document_files WHERE (?1 IS NULL OR document_id = ?1) ORDER BY id",
+            DOCUMENT_FILE_COLUMNS
+        ))
+        .bind(document_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-        Ok(crate::types::DashboardStats {
-            active_employees,
-            terminated_employees,
-            open_loans,
-            overdue_loans,
-            critical_cabinets,
-            archive_boxes,
-            last_sync: now,
-        })
+        let mut report = crate::types::DocumentFileIntegrityReport {
+            checked: 0,
+            intact: 0,
+            missing: Vec::new(),
+            corrupted: Vec::new(),
+        };
+        // Conteúdo compartilhado entre documentos é conferido uma vez só
+        let mut verified: HashMap<String, BlobStatus> = HashMap::new();
+        for file in rows.iter().map(document_file_from_row) {
+            let status = match verified.get(&file.sha256) {
+                Some(status) => *status,
+                None => {
+                    let status = store.verify(&file.sha256)?;
+                    verified.insert(file.sha256.clone(), status);
+                    status
+                }
+            };
+            report.checked += 1;
+            match status {
+                BlobStatus::Intact => report.intact += 1,
+                BlobStatus::Missing => report.missing.push(file),
+                BlobStatus::Corrupted => report.corrupted.push(file),
+            }
+        }
+        Ok(report)
     }
 
-    pub async fn get_movements_report(&self, limit: i64) -> Result<crate::types::MovementsReport> {
-        let total_row = sqlx::query("SELECT COUNT(*) FROM movements")
-            .fetch_one(&self.pool)
-            .await?;
-        let total_movements: i64 = total_row.get(0);
+    // ========================== LOANS ==========================
 
-        let by_action_rows =
-            sqlx::query("SELECT action, COUNT(*) as count FROM movements GROUP BY action")
-                .fetch_all(&self.pool)
-                .await?;
+    pub async fn create_loan(
+        &self,
+        payload: &crate::types::LoanPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanRecord> {
+        let now = Utc::now().to_rfc3339();
 
-        let mut by_action = std::collections::HashMap::new();
-        for row in by_action_rows {
-            let action: String = row.get(0);
-            let count: i64 = row.get(1);
-            by_action.insert(action, count);
+        let mut tx = self.pool.begin().await?;
+        let reservation =
+            ensure_folder_available(&mut tx, payload.employee_id, &payload.requester_name).await?;
+        let result = sqlx::query(
+            "INSERT INTO loans (employee_id, requester_name, requester_department_id, reason,
+             loan_date, expected_return_date, status, return_notes, loaned_by, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, 'BORROWED', ?, ?, ?, ?)"
+        )
+        .bind(payload.employee_id)
+        .bind(&payload.requester_name)
+        .bind(payload.requester_department_id)
+        .bind(&payload.reason)
+        .bind(&now)
+        .bind(&payload.expected_return_date)
+        .bind(payload.return_notes.as_deref())
+        .bind(&actor.login)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let loan = fetch_loan(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "loan",
+            loan.id,
+            None,
+            Some(&loan),
+        )
+        .await?;
+        if let Some(reservation) = reservation {
+            set_reservation_status(&mut tx, actor, reservation, "FULFILLED", Some(loan.id)).await?;
         }
+        tx.commit().await?;
+        Ok(loan)
+    }
 
-        let latest = self.list_movements(limit).await?;
+    /// Devolve a pasta. Vale para empréstimos em aberto e para pastas dadas
+    /// como extraviadas que foram encontradas. A primeira reserva da fila é
+    /// marcada como avisada e volta no resultado.
+    pub async fn return_loan(
+        &self,
+        loan_id: i64,
+        return_date: Option<&str>,
+        return_notes: Option<&str>,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanReturnResult> {
+        let now = Utc::now().to_rfc3339();
+        let actual_date = return_date.unwrap_or(&now);
 
-        Ok(crate::types::MovementsReport {
-            total_movements,
-            by_action,
-            latest,
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_loan(&mut *tx, loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let status = LoanStatus::parse(&before.status)?.apply(LoanAction::Return)?;
+        sqlx::query(
+            "UPDATE loans SET status = ?, actual_return_date = ?, return_notes = ?,
+             returned_by = ?, outstanding_items = NULL, updated_at = ? WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(actual_date)
+        .bind(return_notes)
+        .bind(&actor.login)
+        .bind(&now)
+        .bind(loan_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let after = fetch_loan(&mut *tx, loan_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "RETURN",
+            "loan",
+            loan_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        let next_reservation = notify_next_reservation(&mut tx, actor, after.employee_id).await?;
+        tx.commit().await?;
+        Ok(crate::types::LoanReturnResult {
+            loan: after,
+            next_reservation,
         })
     }
 
-    pub async fn get_loans_report(&self) -> Result<crate::types::LoansReport> {
-        let total_row = sqlx::query("SELECT COUNT(*) FROM loans")
-            .fetch_one(&self.pool)
-            .await?;
-        let total_loans: i64 = total_row.get(0);
-
-        let open_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED'")
-            .fetch_one(&self.pool)
-            .await?;
-        let open_loans: i64 = open_row.get(0);
+    /// Registra a volta de parte da pasta. O empréstimo continua em aberto com o
+    /// que falta em `outstanding_items`: a pasta segue indisponível e a fila de
+    /// espera só é avisada na devolução completa (`return_loan`).
+    pub async fn partial_return_loan(
+        &self,
+        payload: &crate::types::LoanPartialReturnPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanRecord> {
+        let now = Utc::now().to_rfc3339();
+        let return_date = payload.actual_return_date.as_deref().unwrap_or(&now);
 
-        let returned_today_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'RETURNED' AND DATE(actual_return_date) = DATE('now')")
-            .fetch_one(&self.pool)
-            .await?;
-        let returned_today: i64 = returned_today_row.get(0);
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_loan(&mut *tx, payload.loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let status = LoanStatus::parse(&before.status)?.apply(LoanAction::PartialReturn)?;
 
-        let overdue_loans = self.get_overdue_loans().await?;
+        sqlx::query(
+            "INSERT INTO loan_partial_returns (loan_id, returned_items, outstanding_items,
+             return_date, notes, received_by, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(payload.loan_id)
+        .bind(payload.returned_items.trim())
+        .bind(payload.outstanding_items.trim())
+        .bind(return_date)
+        .bind(payload.notes.as_deref())
+        .bind(&actor.login)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE loans SET status = ?, outstanding_items = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(payload.outstanding_items.trim())
+        .bind(&now)
+        .bind(payload.loan_id)
+        .execute(&mut *tx)
+        .await?;
 
-        Ok(crate::types::LoansReport {
-            total_loans,
-            open_loans,
-            overdue_loans,
-            returned_today,
-        })
+        let after = fetch_loan(&mut *tx, payload.loan_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "PARTIAL_RETURN",
+            "loan",
+            payload.loan_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(after)
     }
 
-    // ========================== LABELS ==========================
+    pub async fn list_loan_partial_returns(
+        &self,
+        loan_id: i64,
+    ) -> Result<Vec<crate::types::LoanPartialReturnRecord>> {
+        fetch_loan(&self.pool, loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let rows = sqlx::query(
+            "SELECT id, loan_id, returned_items, outstanding_items, return_date, notes,
+             received_by, created_at FROM loan_partial_returns WHERE loan_id = ? ORDER BY id",
+        )
+        .bind(loan_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-    pub async fn generate_folder_label(&self, employee_id: i64) -> Result<crate::types::LabelData> {
-        let emp = self.get_employee_by_id(employee_id).await?;
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(crate::types::LoanPartialReturnRecord {
+                id: row.get(0),
+                loan_id: row.get(1),
+                returned_items: row.get(2),
+                outstanding_items: row.get(3),
+                return_date: row.get(4),
+                notes: row.get(5),
+                received_by: row.get(6),
+                created_at: row.get(7),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Prorroga a devolução. Cada renovação fica em `loan_renewals` com o
+    /// prazo anterior e o motivo.
+    pub async fn renew_loan(
+        &self,
+        loan_id: i64,
+        new_return_date: &str,
+        reason: &str,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanRecord> {
         let now = Utc::now().to_rfc3339();
 
-        let mut details = std::collections::HashMap::new();
-        details.insert("Matrícula".to_string(), emp.registration.clone());
-        if let Some(dept) = &emp.department_name {
-            details.insert("Departamento".to_string(), dept.clone());
-        }
-        details.insert("Admissão".to_string(), emp.admission_date.clone());
-
-        // Get drawer position info
-        if let Some(pos_id) = emp.drawer_position_id {
-            if let Ok(pos) = self.get_drawer_position(pos_id).await {
-                if let Ok(drawer) = self.get_drawer(pos.drawer_id).await {
-                    if let Ok(cab) = self.get_file_cabinet(drawer.file_cabinet_id).await {
-                        details.insert(
-                            "Localização".to_string(),
-                            format!("{}-G{}-P{}", cab.number, drawer.number, pos.position),
-                        );
-                    }
-                }
-            }
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_loan(&mut *tx, loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let status = LoanStatus::parse(&before.status)?.apply(LoanAction::Renew)?;
+        let new_date = chrono::NaiveDate::parse_from_str(new_return_date, "%Y-%m-%d")
+            .context("Nova data de devolução deve estar no formato AAAA-MM-DD")?;
+        // Datas antigas fora do padrão não impedem a renovação
+        let previous_date = before
+            .expected_return_date
+            .get(..10)
+            .and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+        if previous_date.is_some_and(|previous| new_date <= previous) {
+            bail!(
+                "Nova data deve ser posterior à devolução prevista ({})",
+                display_date(&before.expected_return_date)
+            );
+        }
+        if new_date < Utc::now().date_naive() {
+            bail!("Nova data de devolução já passou");
         }
 
-        Ok(crate::types::LabelData {
-            title: emp.full_name,
-            subtitle: Some(emp.registration),
-            details,
-            generated_at: now,
-        })
+        sqlx::query(
+            "INSERT INTO loan_renewals (loan_id, previous_return_date, new_return_date, reason,
+             renewed_by, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(loan_id)
+        .bind(&before.expected_return_date)
+        .bind(new_return_date)
+        .bind(reason.trim())
+        .bind(&actor.login)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE loans SET status = ?, expected_return_date = ?,
+             renewal_count = renewal_count + 1, updated_at = ? WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(new_return_date)
+        .bind(&now)
+        .bind(loan_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let after = fetch_loan(&mut *tx, loan_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "RENEW",
+            "loan",
+            loan_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(after)
     }
 
-    pub async fn generate_envelope_label(
+    /// Registra o extravio da pasta emprestada.
+    pub async fn report_lost_loan(
         &self,
-        employee_id: i64,
-        category: &str,
-    ) -> Result<crate::types::LabelData> {
-        let emp = self.get_employee_by_id(employee_id).await?;
+        loan_id: i64,
+        incident_notes: &str,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanRecord> {
+        self.close_loan(loan_id, LoanAction::ReportLost, incident_notes, actor)
+            .await
+            .map(|closed| closed.loan)
+    }
+
+    /// Cancela um empréstimo lançado por engano (a pasta não chegou a sair).
+    /// Como na devolução, a primeira reserva da fila é avisada.
+    pub async fn cancel_loan(
+        &self,
+        loan_id: i64,
+        reason: &str,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanReturnResult> {
+        self.close_loan(loan_id, LoanAction::Cancel, reason, actor)
+            .await
+    }
+
+    async fn close_loan(
+        &self,
+        loan_id: i64,
+        action: LoanAction,
+        notes: &str,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanReturnResult> {
         let now = Utc::now().to_rfc3339();
+        let (column, audit_action) = match action {
+            LoanAction::ReportLost => ("incident_notes", "LOST"),
+            LoanAction::Cancel => ("cancellation_reason", "CANCEL"),
+            LoanAction::Return | LoanAction::PartialReturn | LoanAction::Renew => {
+                bail!("Ação não encerra o empréstimo")
+            }
+        };
 
-        let mut details = std::collections::HashMap::new();
-        details.insert("Matrícula".to_string(), emp.registration.clone());
-        details.insert("Categoria".to_string(), category.to_string());
+        let mut tx = self.pool.begin().await?;
+        let before = fetch_loan(&mut *tx, loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let status = LoanStatus::parse(&before.status)?.apply(action)?;
+        sqlx::query(&format!(
+            "UPDATE loans SET status = ?, {} = ?, updated_at = ? WHERE id = ?",
+            column
+        ))
+        .bind(status.as_str())
+        .bind(notes.trim())
+        .bind(&now)
+        .bind(loan_id)
+        .execute(&mut *tx)
+        .await?;
 
-        Ok(crate::types::LabelData {
-            title: emp.full_name,
-            subtitle: Some(category.to_string()),
-            details,
-            generated_at: now,
+        let after = fetch_loan(&mut *tx, loan_id).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            audit_action,
+            "loan",
+            loan_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        // Extraviada, a pasta continua fora; cancelado, ela nunca saiu
+        let next_reservation = match status {
+            LoanStatus::Cancelled => {
+                notify_next_reservation(&mut tx, actor, after.employee_id).await?
+            }
+            _ => None,
+        };
+        tx.commit().await?;
+        Ok(crate::types::LoanReturnResult {
+            loan: after,
+            next_reservation,
         })
     }
 
-    pub async fn generate_box_label(&self, box_id: i64) -> Result<crate::types::LabelData> {
-        let archive_box = self.get_archive_box(box_id).await?;
+    pub async fn list_loan_renewals(
+        &self,
+        loan_id: i64,
+    ) -> Result<Vec<crate::types::LoanRenewalRecord>> {
+        fetch_loan(&self.pool, loan_id)
+            .await
+            .context("Empréstimo não encontrado")?;
+        let rows = sqlx::query(
+            "SELECT id, loan_id, previous_return_date, new_return_date, reason, renewed_by,
+             created_at FROM loan_renewals WHERE loan_id = ? ORDER BY id",
+        )
+        .bind(loan_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(crate::types::LoanRenewalRecord {
+                id: row.get(0),
+                loan_id: row.get(1),
+                previous_return_date: row.get(2),
+                new_return_date: row.get(3),
+                reason: row.get(4),
+                renewed_by: row.get(5),
+                created_at: row.get(6),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Coloca o solicitante na fila de espera de uma pasta que está fora ou
+    /// que já tem reservas pendentes.
+    pub async fn reserve_folder(
+        &self,
+        payload: &crate::types::LoanReservationPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanReservationRecord> {
         let now = Utc::now().to_rfc3339();
 
-        let mut details = std::collections::HashMap::new();
-        details.insert("Ano".to_string(), archive_box.year.to_string());
-        if let Some(period) = &archive_box.period {
-            details.insert("Período".to_string(), period.clone());
+        let mut tx = self.pool.begin().await?;
+        let employee = fetch_employee(&mut *tx, payload.employee_id)
+            .await
+            .context("Funcionário não encontrado")?;
+        ensure_folder_in_active_archive(&mut tx, &employee).await?;
+        let queue = active_reservations(&mut tx, employee.id).await?;
+        match open_folder_loan(&mut tx, employee.id).await? {
+            Some(open_loan)
+                if open_loan
+                    .requester_name
+                    .eq_ignore_ascii_case(payload.requester_name.trim()) =>
+            {
+                bail!("A pasta já está com {}", open_loan.requester_name);
+            }
+            None if queue.is_empty() => {
+                bail!("Pasta disponível; registre o empréstimo diretamente");
+            }
+            _ => {}
         }
-        if let Some(range) = &archive_box.letter_range {
-            details.insert("Faixa".to_string(), range.clone());
+        if queue.iter().any(|reservation| {
+            reservation
+                .requester_name
+                .eq_ignore_ascii_case(payload.requester_name.trim())
+        }) {
+            bail!(
+                "{} já está na fila de espera desta pasta",
+                payload.requester_name.trim()
+            );
         }
-        if let Some(loc) = &archive_box.location {
-            details.insert("Local".to_string(), loc.clone());
+
+        let result = sqlx::query(
+            "INSERT INTO loan_reservations (employee_id, requester_name, requester_department_id,
+             reason, status, reserved_by, created_at, updated_at)
+             VALUES (?, ?, ?, ?, 'WAITING', ?, ?, ?)",
+        )
+        .bind(employee.id)
+        .bind(payload.requester_name.trim())
+        .bind(payload.requester_department_id)
+        .bind(payload.reason.trim())
+        .bind(&actor.login)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let reservation = fetch_loan_reservation(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "loan_reservation",
+            reservation.id,
+            None,
+            Some(&reservation),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(reservation)
+    }
+
+    /// Fila de espera da pasta, na ordem de atendimento.
+    pub async fn list_folder_reservations(
+        &self,
+        employee_id: i64,
+    ) -> Result<Vec<crate::types::LoanReservationRecord>> {
+        let mut conn = self.pool.acquire().await?;
+        active_reservations(&mut conn, employee_id).await
+    }
+
+    pub async fn cancel_reservation(
+        &self,
+        id: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::LoanReservationRecord> {
+        let mut tx = self.pool.begin().await?;
+        let reservation = fetch_loan_reservation(&mut *tx, id)
+            .await
+            .context("Reserva não encontrada")?;
+        if !matches!(reservation.status.as_str(), "WAITING" | "NOTIFIED") {
+            bail!("Reserva já encerrada");
         }
-        details.insert(
-            "Capacidade".to_string(),
-            format!("{}/{}", archive_box.current_count, archive_box.capacity),
-        );
+        let employee_id = reservation.employee_id;
+        let cancelled =
+            set_reservation_status(&mut tx, actor, reservation, "CANCELLED", None).await?;
+        // Com a pasta no arquivo, o próximo da fila passa a ser avisado
+        if open_folder_loan(&mut tx, employee_id).await?.is_none() {
+            notify_next_reservation(&mut tx, actor, employee_id).await?;
+        }
+        tx.commit().await?;
+        Ok(cancelled)
+    }
 
-        Ok(crate::types::LabelData {
-            title: format!("Caixa {}", archive_box.box_number),
-            subtitle: Some(format!("Arquivo Morto {}", archive_box.year)),
-            details,
-            generated_at: now,
-        })
+    pub async fn list_loans(
+        &self,
+        filter: &crate::types::LoanFilter,
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::Page<crate::types::LoanRecord>> {
+        let order = order_by(
+            paging,
+            &[
+                ("loan_date", "loan_date"),
+                ("expected_return_date", "expected_return_date"),
+                ("actual_return_date", "actual_return_date"),
+                ("requester_name", "requester_name"),
+                ("status", "status"),
+                ("created_at", "created_at"),
+            ],
+            "loan_date DESC",
+        )?;
+
+        let total: i64 = bind_loan_filter(
+            sqlx::query(&format!("SELECT COUNT(*) FROM loans {}", LOAN_FILTER)),
+            filter,
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .get(0);
+        let rows = bind_loan_filter(
+            sqlx::query(&format!(
+                "SELECT {} FROM loans l {} ORDER BY {}, id DESC LIMIT ?6 OFFSET ?7",
+                LOAN_COLUMNS, LOAN_FILTER, order
+            )),
+            filter,
+        )
+        .bind(paging.page_size())
+        .bind(paging.offset())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let result = rows.iter().map(loan_from_row).collect();
+        Ok(crate::types::Page::new(result, total, paging))
+    }
+
+    pub async fn get_overdue_loans(&self) -> Result<Vec<crate::types::LoanWithEmployee>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {},
+             e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name,
+             e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
+             e.created_at, e.updated_at, e.termination_reason
+             FROM loans l
+             JOIN employees e ON l.employee_id = e.id
+             LEFT JOIN departments d ON e.department_id = d.id
+             WHERE l.status = 'BORROWED' AND l.expected_return_date < DATE('now')
+             ORDER BY l.expected_return_date ASC",
+            LOAN_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(crate::types::LoanWithEmployee {
+                loan: loan_from_row(&row),
+                employee: crate::types::EmployeeRecord {
+                    id: row.get(18),
+                    full_name: row.get(19),
+                    registration: row.get(20),
+                    cpf: row
+                        .get::<Option<String>, _>(21)
+                        .map(|cpf| crate::cpf::format(&cpf)),
+                    department_id: row.get(22),
+                    department_name: row.get(23),
+                    admission_date: row.get(24),
+                    termination_date: row.get(25),
+                    termination_reason: row.get(31),
+                    status: row.get(26),
+                    drawer_position_id: row.get(27),
+                    notes: row.get(28),
+                    created_at: row.get(29),
+                    updated_at: row.get(30),
+                },
+            });
+        }
+        Ok(result)
+    }
+
+    // ========================== DEAD ARCHIVE ==========================
+
+    pub async fn create_archive_box(
+        &self,
+        payload: &crate::types::ArchiveBoxPayload,
+        actor: &UserProfile,
+    ) -> Result<crate::types::ArchiveBoxRecord> {
+        let now = Utc::now().to_rfc3339();
+        let capacity = payload.capacity.unwrap_or(50);
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT INTO dead_archive_boxes (box_number, year, period, letter_range, location, capacity, current_count, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, 0, ?)"
+        )
+        .bind(payload.box_number.trim())
+        .bind(payload.year)
+        .bind(payload.period.as_deref())
+        .bind(payload.letter_range.as_deref())
+        .bind(payload.location.as_deref())
+        .bind(capacity)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        let archive_box = fetch_archive_box(&mut *tx, result.last_insert_rowid()).await?;
+        write_audit(
+            &mut tx,
+            Some(actor.id),
+            "CREATE",
+            "archive_box",
+            archive_box.id,
+            None,
+            Some(&archive_box),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(archive_box)
+    }
+
+    async fn get_archive_box(&self, id: i64) -> Result<crate::types::ArchiveBoxRecord> {
+        fetch_archive_box(&self.pool, id).await
+    }
+
+    pub async fn list_archive_boxes(
+        &self,
+        filter: &crate::types::ArchiveBoxFilter,
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::Page<crate::types::ArchiveBoxRecord>> {
+        let order = order_by(
+            paging,
+            &[
+                ("box_number", "box_number"),
+                ("year", "year"),
+                ("location", "location"),
+                ("current_count", "current_count"),
+                ("capacity", "capacity"),
+                ("created_at", "created_at"),
+            ],
+            "year DESC, box_number ASC",
+        )?;
+
+        let total: i64 = bind_archive_box_filter(
+            sqlx::query(&format!(
+                "SELECT COUNT(*) FROM dead_archive_boxes {}",
+                ARCHIVE_BOX_FILTER
+            )),
+            filter,
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .get(0);
+        let rows = bind_archive_box_filter(
+            sqlx::query(&format!(
+                "SELECT id, box_number, year, period, letter_range, location, capacity, current_count, created_at
+                 FROM dead_archive_boxes {} ORDER BY {}, id ASC LIMIT ?4 OFFSET ?5",
+                ARCHIVE_BOX_FILTER, order
+            )),
+            filter,
+        )
+        .bind(paging.page_size())
+        .bind(paging.offset())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(crate::types::ArchiveBoxRecord {
+                id: row.get(0),
+                box_number: row.get(1),
+                year: row.get(2),
+                period: row.get(3),
+                letter_range: row.get(4),
+                location: row.get(5),
+                capacity: row.get(6),
+                current_count: row.get(7),
+                created_at: row.get(8),
+            });
+        }
+        Ok(crate::types::Page::new(result, total, paging))
+    }
+
+    /// Transfere a pasta de um funcionário desligado; a data de descarte sai
+    /// da tabela de temporalidade dos documentos arquivados.
+    pub async fn transfer_to_archive(
+        &self,
+        employee_id: i64,
+        box_id: i64,
+        actor: &UserProfile,
+    ) -> Result<crate::types::ArchiveItemRecord> {
+        let mut tx = self.pool.begin().await?;
+        let employee = fetch_employee(&mut *tx, employee_id).await?;
+        let Some(termination_date) = employee.termination_date.as_deref() else {
+            bail!(
+                "Registre o desligamento de {} antes de transferir a pasta",
+                employee.full_name
+            );
+        };
+
+        let retention = disposal_eligible_date(&mut tx, employee_id, termination_date).await?;
+        let item = insert_archive_item(&mut tx, actor, employee_id, box_id, &retention).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    pub async fn get_disposal_candidates(&self) -> Result<Vec<crate::types::DisposalCandidate>> {
+        let rows = sqlx::query(
+            "SELECT dai.id, dai.employee_id, dai.box_id, dai.transfer_date, dai.disposal_eligible_date,
+             dai.disposed, dai.disposal_date, dai.disposal_term_number, dai.transferred_by, dai.created_at,
+             e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name,
+             e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
+             e.created_at, e.updated_at, e.termination_reason, dai.disposal_rule
+             FROM dead_archive_items dai
+             JOIN employees e ON dai.employee_id = e.id
+             LEFT JOIN departments d ON e.department_id = d.id
+             WHERE dai.disposed = 0 AND dai.disposal_eligible_date <= DATE('now')
+             ORDER BY dai.disposal_eligible_date ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(crate::types::DisposalCandidate {
+                archive_item: crate::types::ArchiveItemRecord {
+                    id: row.get(0),
+                    employee_id: row.get(1),
+                    box_id: row.get(2),
+                    transfer_date: row.get(3),
+                    disposal_eligible_date: row.get(4),
+                    disposal_rule: row.get(24),
+                    disposed: row.get::<i64, _>(5) == 1,
+                    disposal_date: row.get(6),
+                    disposal_term_number: row.get(7),
+                    transferred_by: row.get(8),
+                    created_at: row.get(9),
+                },
+                employee: crate::types::EmployeeRecord {
+                    id: row.get(10),
+                    full_name: row.get(11),
+                    registration: row.get(12),
+                    cpf: row
+                        .get::<Option<String>, _>(13)
+                        .map(|cpf| crate::cpf::format(&cpf)),
+                    department_id: row.get(14),
+                    department_name: row.get(15),
+                    admission_date: row.get(16),
+                    termination_date: row.get(17),
+                    termination_reason: row.get(23),
+                    status: row.get(18),
+                    drawer_position_id: row.get(19),
+                    notes: row.get(20),
+                    created_at: row.get(21),
+                    updated_at: row.get(22),
+                },
+            });
+        }
+        Ok(result)
+    }
+
+    pub async fn register_disposal(
+        &self,
+        item_ids: &[i64],
+        term_number: Option<&str>,
+        actor: &UserProfile,
+    ) -> Result<crate::types::DisposalTerm> {
+        let now = Utc::now().to_rfc3339();
+        let term = term_number
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("TERMO-{}", now.replace(":", "-")));
+
+        // Qualquer item inválido desfaz o termo inteiro
+        let mut tx = self.pool.begin().await?;
+        let mut items = Vec::new();
+        for id in item_ids {
+            let before = fetch_archive_item(&mut *tx, *id)
+                .await
+                .with_context(|| format!("Item {} do arquivo morto não encontrado", id))?;
+            let updated = sqlx::query(
+                "UPDATE dead_archive_items SET disposed = 1, disposal_date = ?, disposal_term_number = ?
+                 WHERE id = ? AND disposed = 0"
+            )
+            .bind(&now)
+            .bind(&term)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() != 1 {
+                bail!("Item {} do arquivo morto já foi descartado", id);
+            }
+
+            let after = fetch_archive_item(&mut *tx, *id).await?;
+            write_audit(
+                &mut tx,
+                Some(actor.id),
+                "DISPOSE",
+                "archive_item",
+                *id,
+                Some(&before),
+                Some(&after),
+            )
+            .await?;
+            items.push(after);
+        }
+        tx.commit().await?;
+
+        Ok(crate::types::DisposalTerm {
+            term_number: term,
+            generated_at: now,
+            items,
+            generated_by: actor.name.clone(),
+        })
+    }
+
+    // ========================== REPORTS ==========================
+
+    pub async fn get_dashboard_stats(&self) -> Result<crate::types::DashboardStats> {
+        let now = Utc::now().to_rfc3339();
+
+        // Active employees
+        let active_row = sqlx::query("SELECT COUNT(*) FROM employees WHERE status = 'ACTIVE'")
+            .fetch_one(&self.pool)
+            .await?;
+        let active_employees: i64 = active_row.get(0);
+
+        // Terminated employees
+        let terminated_row =
+            sqlx::query("SELECT COUNT(*) FROM employees WHERE status = 'TERMINATED'")
+                .fetch_one(&self.pool)
+                .await?;
+        let terminated_employees: i64 = terminated_row.get(0);
+
+        // Open loans
+        let open_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED'")
+            .fetch_one(&self.pool)
+            .await?;
+        let open_loans: i64 = open_row.get(0);
+
+        // Overdue loans
+        let overdue_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED' AND expected_return_date < DATE('now')")
+            .fetch_one(&self.pool)
+            .await?;
+        let overdue_loans: i64 = overdue_row.get(0);
+
+        // Archive boxes
+        let boxes_row = sqlx::query("SELECT COUNT(*) FROM dead_archive_boxes")
+            .fetch_one(&self.pool)
+            .await?;
+        let archive_boxes: i64 = boxes_row.get(0);
+
+        // Documents to renew
+        let expiration_row = sqlx::query(&format!(
+            "SELECT
+               COALESCE(SUM(DATE(d.expiration_date) < DATE('now')), 0),
+               COALESCE(SUM(DATE(d.expiration_date) >= DATE('now')
+                 AND DATE(d.expiration_date) <= DATE('now', '+' || ? || ' days')), 0)
+             FROM documents d JOIN employees e ON e.id = d.employee_id
+             WHERE {}",
+            PENDING_EXPIRATION
+        ))
+        .bind(EXPIRATION_ALERT_DAYS)
+        .fetch_one(&self.pool)
+        .await?;
+        let expired_documents: i64 = expiration_row.get(0);
+        let expiring_documents: i64 = expiration_row.get(1);
+
+        // Critical cabinets
+        let occupation_map = self.get_occupation_map().await?;
+        let critical_cabinets: Vec<crate::types::CabinetOccupationNode> = occupation_map
+            .cabinets
+            .into_iter()
+            .filter(|c| c.status == "CRITICAL")
+            .collect();
+
+        Ok(crate::types::DashboardStats {
+            active_employees,
+            terminated_employees,
+            open_loans,
+            overdue_loans,
+            critical_cabinets,
+            archive_boxes,
+            expiring_documents,
+            expired_documents,
+            last_sync: now,
+        })
+    }
+
+    pub async fn get_movements_report(
+        &self,
+        filter: &crate::types::MovementFilter,
+        paging: &crate::types::PageRequest,
+    ) -> Result<crate::types::MovementsReport> {
+        let by_action_rows = bind_movement_filter(
+            sqlx::query(&format!(
+                "SELECT action, COUNT(*) as count FROM movements {} GROUP BY action",
+                MOVEMENT_FILTER
+            )),
+            filter,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut by_action = std::collections::HashMap::new();
+        for row in by_action_rows {
+            let action: String = row.get(0);
+            let count: i64 = row.get(1);
+            by_action.insert(action, count);
+        }
+
+        let latest = self.list_movements(filter, paging).await?;
+
+        Ok(crate::types::MovementsReport {
+            total_movements: latest.total,
+            by_action,
+            latest,
+        })
+    }
+
+    pub async fn get_loans_report(&self) -> Result<crate::types::LoansReport> {
+        let total_row = sqlx::query("SELECT COUNT(*) FROM loans")
+            .fetch_one(&self.pool)
+            .await?;
+        let total_loans: i64 = total_row.get(0);
+
+        let open_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'BORROWED'")
+            .fetch_one(&self.pool)
+            .await?;
+        let open_loans: i64 = open_row.get(0);
+
+        let returned_today_row = sqlx::query("SELECT COUNT(*) FROM loans WHERE status = 'RETURNED' AND DATE(actual_return_date) = DATE('now')")
+            .fetch_one(&self.pool)
+            .await?;
+        let returned_today: i64 = returned_today_row.get(0);
+
+        let overdue_loans = self.get_overdue_loans().await?;
+
+        Ok(crate::types::LoansReport {
+            total_loans,
+            open_loans,
+            overdue_loans,
+            returned_today,
+        })
+    }
+
+    // ========================== LABELS ==========================
+
+    pub async fn generate_folder_label(&self, employee_id: i64) -> Result<crate::types::LabelData> {
+        let emp = self.get_employee_by_id(employee_id).await?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut details = vec![LabelField::new("Matrícula", &emp.registration)];
+        if let Some(dept) = &emp.department_name {
+            details.push(LabelField::new("Departamento", dept));
+        }
+        details.push(LabelField::new("Admissão", &emp.admission_date));
+        if let Some(date) = &emp.termination_date {
+            details.push(LabelField::new("Desligamento", date));
+        }
+
+        let (location, disposal_date) = self.folder_whereabouts(&emp).await?;
+        if let Some(location) = &location {
+            details.push(LabelField::new("Localização", location));
+        }
+        if let Some(date) = &disposal_date {
+            details.push(LabelField::new("Descarte a partir de", date));
+        }
+
+        Ok(crate::types::LabelData {
+            code: crate::labels::employee_code(emp.id),
+            title: emp.full_name,
+            subtitle: Some(emp.registration),
+            details,
+            generated_at: now,
+        })
+    }
+
+    /// Etiquetas de pasta em lote, na ordem física: gaveta e posição para `drawer` e
+    /// `cabinet`; nome do funcionário para as pastas de uma caixa (`box`).
+    pub async fn generate_folder_labels(
+        &self,
+        scope: &str,
+        scope_id: i64,
+    ) -> Result<Vec<crate::types::LabelData>> {
+        let employee_ids: Vec<i64> = match scope {
+            "drawer" => {
+                fetch_drawer(&self.pool, scope_id)
+                    .await
+                    .context("Gaveta não encontrada")?;
+                sqlx::query_scalar(
+                    "SELECT employee_id FROM drawer_positions
+                     WHERE drawer_id = ? AND employee_id IS NOT NULL
+                     ORDER BY position",
+                )
+                .bind(scope_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            "cabinet" => {
+                fetch_file_cabinet(&self.pool, scope_id)
+                    .await
+                    .context("Arquivo não encontrado")?;
+                sqlx::query_scalar(
+                    "SELECT dp.employee_id FROM drawer_positions dp
+                     JOIN drawers d ON d.id = dp.drawer_id
+                     WHERE d.file_cabinet_id = ? AND dp.employee_id IS NOT NULL
+                     ORDER BY d.number, dp.position",
+                )
+                .bind(scope_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            "box" => {
+                fetch_archive_box(&self.pool, scope_id)
+                    .await
+                    .context("Caixa não encontrada")?;
+                sqlx::query_scalar(
+                    "SELECT i.employee_id FROM dead_archive_items i
+                     JOIN employees e ON e.id = i.employee_id
+                     WHERE i.box_id = ? AND i.disposed = 0
+                     ORDER BY e.full_name COLLATE NOCASE",
+                )
+                .bind(scope_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            other => bail!("Escopo de etiquetas inválido: {}", other),
+        };
+
+        if employee_ids.is_empty() {
+            bail!("Nenhuma pasta guardada para etiquetar");
+        }
+
+        let mut labels = Vec::with_capacity(employee_ids.len());
+        for employee_id in employee_ids {
+            labels.push(self.generate_folder_label(employee_id).await?);
+        }
+        Ok(labels)
+    }
+
+    /// Onde a pasta está e, se já foi para o arquivo morto, a data de descarte.
+    /// A gaveta tem prioridade; pastas transferidas ficam na caixa do arquivo morto.
+    async fn folder_whereabouts(
+        &self,
+        emp: &crate::types::EmployeeRecord,
+    ) -> Result<(Option<String>, Option<String>)> {
+        let mut conn = self.pool.acquire().await?;
+        let mut location = match emp.drawer_position_id {
+            Some(position_id) => folder_location(&mut conn, position_id).await?,
+            None => None,
+        };
+        let mut disposal_date = None;
+        if let Some((box_number, eligible_date)) = archived_folder(&mut conn, emp.id).await? {
+            location.get_or_insert(format!("Arquivo morto - Caixa {}", box_number));
+            disposal_date = eligible_date;
+        }
+        Ok((location, disposal_date))
+    }
+
+    pub async fn generate_envelope_label(
+        &self,
+        employee_id: i64,
+        category: &str,
+    ) -> Result<crate::types::LabelData> {
+        let emp = self.get_employee_by_id(employee_id).await?;
+        let now = Utc::now().to_rfc3339();
+
+        let details = vec![
+            LabelField::new("Matrícula", &emp.registration),
+            LabelField::new("Categoria", category),
+        ];
+
+        Ok(crate::types::LabelData {
+            code: crate::labels::employee_code(emp.id),
+            title: emp.full_name,
+            subtitle: Some(category.to_string()),
+            details,
+            generated_at: now,
+        })
+    }
+
+    pub async fn generate_box_label(&self, box_id: i64) -> Result<crate::types::LabelData> {
+        let archive_box = self.get_archive_box(box_id).await?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut details = vec![LabelField::new("Ano", &archive_box.year.to_string())];
+        if let Some(period) = &archive_box.period {
+            details.push(LabelField::new("Período", period));
+        }
+        if let Some(range) = &archive_box.letter_range {
+            details.push(LabelField::new("Faixa", range));
+        }
+        if let Some(loc) = &archive_box.location {
+            details.push(LabelField::new("Local", loc));
+        }
+        details.push(LabelField::new(
+            "Capacidade",
+            &format!("{}/{}", archive_box.current_count, archive_box.capacity),
+        ));
+
+        Ok(crate::types::LabelData {
+            code: crate::labels::box_code(&archive_box.box_number),
+            title: format!("Caixa {}", archive_box.box_number),
+            subtitle: Some(format!("Arquivo Morto {}", archive_box.year)),
+            details,
+            generated_at: now,
+        })
+    }
+
+    // ========================== SCAN ==========================
+
+    /// Resolve o código lido das etiquetas (`EMP-<id>`, `BOX-<caixa>`) ou uma matrícula.
+    pub async fn resolve_scan(&self, raw: &str) -> Result<crate::types::ScanResult> {
+        let code = raw.trim().to_string();
+        if code.is_empty() {
+            bail!("Código vazio");
+        }
+        let employee_id = match crate::labels::parse_scan(&code) {
+            None => bail!("Código inválido: {}", code),
+            Some(ScanCode::Employee(id)) => id,
+            Some(ScanCode::Registration(registration)) => {
+                sqlx::query_scalar("SELECT id FROM employees WHERE registration = ?")
+                    .bind(&registration)
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .with_context(|| format!("Nenhuma pasta ou caixa com o código {}", code))?
+            }
+            Some(ScanCode::Box(box_number)) => {
+                let box_id: i64 =
+                    sqlx::query_scalar("SELECT id FROM dead_archive_boxes WHERE box_number = ?")
+                        .bind(&box_number)
+                        .fetch_optional(&self.pool)
+                        .await?
+                        .with_context(|| format!("Caixa {} não encontrada", box_number))?;
+                return Ok(crate::types::ScanResult {
+                    kind: "box".to_string(),
+                    code,
+                    employee: None,
+                    location: None,
+                    archive_box: Some(self.get_box_contents(box_id).await?),
+                });
+            }
+        };
+
+        let employee = self
+            .get_employee_detail(employee_id)
+            .await
+            .with_context(|| format!("Nenhuma pasta ou caixa com o código {}", code))?;
+        let (location, _) = self.folder_whereabouts(&employee.basic).await?;
+        Ok(crate::types::ScanResult {
+            kind: "employee".to_string(),
+            code,
+            employee: Some(employee),
+            location,
+            archive_box: None,
+        })
+    }
+
+    /// Caixa com as pastas ainda guardadas nela (as descartadas ficam de fora).
+    pub async fn get_box_contents(&self, box_id: i64) -> Result<crate::types::BoxWithCount> {
+        let archive_box = self.get_archive_box(box_id).await?;
+        let item_ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM dead_archive_items WHERE box_id = ? AND disposed = 0 ORDER BY id",
+        )
+        .bind(box_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut occupants = Vec::new();
+        for item_id in item_ids {
+            occupants.push(fetch_archive_item(&self.pool, item_id).await?);
+        }
+        Ok(crate::types::BoxWithCount {
+            r#box: archive_box,
+            occupants,
+        })
+    }
+
+    // ========================== SEARCH ==========================
+
+    /// Busca global no índice FTS, com os resultados mais relevantes primeiro.
+    pub async fn search(&self, query: &str, limit: i64) -> Result<Vec<crate::types::SearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let rows = sqlx::query(
+            "SELECT s.entity_type, s.entity_id,
+             CASE s.entity_type
+                 WHEN 'employee' THEN e.full_name || ' (' || e.registration || ')'
+                 WHEN 'document' THEN COALESCE(dt.name, 'Documento') || ' - ' || COALESCE(de.full_name, '')
+                 WHEN 'loan' THEN 'Empréstimo para ' || l.requester_name || ' - ' || COALESCE(le.full_name, '')
+                 ELSE 'Caixa ' || b.box_number
+             END,
+             CASE s.entity_type
+                 WHEN 'employee' THEN e.id
+                 WHEN 'document' THEN doc.employee_id
+                 WHEN 'loan' THEN l.employee_id
+             END,
+             snippet(search_index, -1, '[', ']', '…', 12),
+             bm25(search_index, 0.0, 0.0, 10.0, 1.0) AS score
+             FROM search_index s
+             LEFT JOIN employees e ON s.entity_type = 'employee' AND e.id = s.entity_id
+             LEFT JOIN documents doc ON s.entity_type = 'document' AND doc.id = s.entity_id
+             LEFT JOIN document_types dt ON dt.id = doc.type_id
+             LEFT JOIN employees de ON de.id = doc.employee_id
+             LEFT JOIN loans l ON s.entity_type = 'loan' AND l.id = s.entity_id
+             LEFT JOIN employees le ON le.id = l.employee_id
+             LEFT JOIN dead_archive_boxes b ON s.entity_type = 'box' AND b.id = s.entity_id
+             WHERE search_index MATCH ?
+             ORDER BY score LIMIT ?",
+        )
+        .bind(&fts_query)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| crate::types::SearchHit {
+                entity_type: row.get(0),
+                entity_id: row.get(1),
+                title: row.get(2),
+                employee_id: row.get(3),
+                snippet: row.get(4),
+                // bm25 é negativo e menor para os melhores resultados
+                score: -row.get::<f64, _>(5),
+            })
+            .collect())
+    }
+
+    // ========================== AUDIT ==========================
+
+    pub async fn list_audit_logs(
+        &self,
+        filter: &crate::types::AuditLogFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<crate::types::AuditLogPage> {
+        // Busca um registro a mais para saber se existe próxima página
+        let mut items = self
+            .query_audit_logs(filter, cursor, Some(limit + 1))
+            .await?;
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|log| log.id)
+        } else {
+            None
+        };
+
+        Ok(crate::types::AuditLogPage { items, next_cursor })
+    }
+
+    pub async fn export_audit_logs(
+        &self,
+        filter: &crate::types::AuditLogFilter,
+    ) -> Result<Vec<crate::types::AuditLogRecord>> {
+        self.query_audit_logs(filter, None, None).await
+    }
+
+    async fn query_audit_logs(
+        &self,
+        filter: &crate::types::AuditLogFilter,
+        cursor: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<Vec<crate::types::AuditLogRecord>> {
+        let entity_type = filter
+            .entity_type
+            .as_deref()
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty());
+        let action = filter
+            .action
+            .as_deref()
+            .map(|value| value.trim().to_uppercase())
+            .filter(|value| !value.is_empty());
+
+        let rows = sqlx::query(
+            "SELECT a.id, a.user_id, u.name, u.login, a.action, a.entity_type, a.entity_id,
+             a.old_values, a.new_values, a.ip_address, a.created_at
+             FROM audit_logs a LEFT JOIN users u ON u.id = a.user_id
+             WHERE (?1 IS NULL OR a.user_id = ?1)
+               AND (?2 IS NULL OR a.entity_type = ?2)
+               AND (?3 IS NULL OR a.entity_id = ?3)
+               AND (?4 IS NULL OR a.action = ?4)
+               AND (?5 IS NULL OR substr(a.created_at, 1, 10) >= ?5)
+               AND (?6 IS NULL OR substr(a.created_at, 1, 10) <= ?6)
+               AND (?7 IS NULL OR a.id < ?7)
+             ORDER BY a.id DESC LIMIT ?8",
+        )
+        .bind(filter.user_id)
+        .bind(entity_type)
+        .bind(filter.entity_id)
+        .bind(action)
+        .bind(filter.date_from.as_deref())
+        .bind(filter.date_to.as_deref())
+        .bind(cursor)
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(audit_log_from_row).collect()
+    }
+
+    /// Reconstrói a linha do tempo de um registro aplicando `new_values` em ordem.
+    pub async fn get_entity_history(
+        &self,
+        entity_type: &str,
+        entity_id: i64,
+    ) -> Result<crate::types::EntityHistory> {
+        let entity_type = entity_type.trim().to_lowercase();
+        let rows = sqlx::query(
+            "SELECT a.id, a.user_id, u.name, u.login, a.action, a.entity_type, a.entity_id,
+             a.old_values, a.new_values, a.ip_address, a.created_at
+             FROM audit_logs a LEFT JOIN users u ON u.id = a.user_id
+             WHERE a.entity_type = ? AND a.entity_id = ?
+             ORDER BY a.id ASC",
+        )
+        .bind(&entity_type)
+        .bind(entity_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut state = Map::new();
+        let mut entries = Vec::new();
+        for row in &rows {
+            let log = audit_log_from_row(row)?;
+            match (&log.old_values, &log.new_values) {
+                // Registro completo (criação): substitui o estado acumulado
+                (None, Some(Value::Object(values))) => state = values.clone(),
+                (_, Some(Value::Object(values))) => {
+                    for (key, value) in values {
+                        state.insert(key.clone(), value.clone());
+                    }
+                }
+                _ => {}
+            }
+            entries.push(crate::types::EntityHistoryEntry {
+                log,
+                state: Value::Object(state.clone()),
+            });
+        }
+
+        Ok(crate::types::EntityHistory {
+            entity_type,
+            entity_id,
+            current_state: entries.last().map(|entry| entry.state.clone()),
+            entries,
+        })
+    }
+}
+
+// ========================== SHARED WRITES ==========================
+
+async fn insert_employee(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    payload: &crate::types::EmployeePayload,
+) -> Result<crate::types::EmployeeRecord> {
+    let now = Utc::now().to_rfc3339();
+    let status = payload.status.as_deref().unwrap_or("ACTIVE");
+
+    let result = sqlx::query(
+        "INSERT INTO employees (full_name, registration, cpf, department_id, admission_date,
+         termination_date, status, drawer_position_id, notes, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(payload.full_name.trim())
+    .bind(payload.registration.trim())
+    .bind(payload.cpf.as_deref().map(crate::cpf::normalize))
+    .bind(payload.department_id)
+    .bind(&payload.admission_date)
+    .bind(payload.termination_date.as_deref())
+    .bind(status)
+    .bind(payload.drawer_position_id)
+    .bind(payload.notes.as_deref())
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+
+    let employee = fetch_employee(&mut *conn, result.last_insert_rowid()).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "CREATE",
+        "employee",
+        employee.id,
+        None,
+        Some(&employee),
+    )
+    .await?;
+    Ok(employee)
+}
+
+/// Impede que o último administrador ativo seja rebaixado ou desativado.
+async fn ensure_not_last_admin(conn: &mut SqliteConnection, user: &UserRecord) -> Result<()> {
+    if user.role != Role::Admin.code() || !user.is_active {
+        return Ok(());
+    }
+
+    let row = sqlx::query("SELECT COUNT(*) FROM users WHERE role = ? AND is_active = 1")
+        .bind(Role::Admin.code())
+        .fetch_one(conn)
+        .await?;
+    let active_admins: i64 = row.get(0);
+    if active_admins <= 1 {
+        bail!("Não é possível remover o último administrador ativo");
+    }
+    Ok(())
+}
+
+/// Grava uma movimentação dentro da transação de quem a originou.
+async fn insert_movement(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    payload: &MovementData,
+) -> Result<MovementRecord> {
+    let now = Utc::now().to_rfc3339();
+
+    let result = sqlx::query(
+        "INSERT INTO movements (reference, item_label, from_unit, to_unit, action, note, actor, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(payload.reference.as_deref())
+    .bind(payload.item_label.as_deref())
+    .bind(payload.from_unit.as_deref())
+    .bind(payload.to_unit.as_deref())
+    .bind(payload.action.trim())
+    .bind(payload.note.as_deref())
+    .bind(&actor.name)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+
+    let movement = fetch_movement(&mut *conn, result.last_insert_rowid()).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "CREATE",
+        "movement",
+        movement.id,
+        None,
+        Some(&movement),
+    )
+    .await?;
+    Ok(movement)
+}
+
+/// Transfere a pasta para uma caixa do arquivo morto, respeitando a capacidade.
+/// Pastas emprestadas ou extraviadas não são transferidas; reservas pendentes
+/// são canceladas.
+async fn insert_archive_item(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    employee_id: i64,
+    box_id: i64,
+    retention: &Retention,
+) -> Result<crate::types::ArchiveItemRecord> {
+    let now = Utc::now().to_rfc3339();
+    let box_before = fetch_archive_box(&mut *conn, box_id).await?;
+
+    let archived: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM dead_archive_items WHERE employee_id = ? AND disposed = 0",
+    )
+    .bind(employee_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if archived > 0 {
+        bail!("A pasta deste funcionário já está no arquivo morto");
+    }
+    if let Some(loan) = open_folder_loan(conn, employee_id).await? {
+        if loan.status == "LOST" {
+            bail!(
+                "A pasta consta como extraviada no empréstimo para {}; registre a devolução quando for encontrada",
+                loan.requester_name
+            );
+        }
+        bail!(
+            "A pasta está emprestada para {}; registre a devolução antes de transferir",
+            loan.requester_name
+        );
+    }
+    // A pasta não volta a sair do arquivo ativo: a fila de espera é encerrada
+    for reservation in active_reservations(conn, employee_id).await? {
+        set_reservation_status(conn, actor, reservation, "CANCELLED", None).await?;
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO dead_archive_items (employee_id, box_id, transfer_date, disposal_eligible_date, disposal_rule, transferred_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(employee_id)
+    .bind(box_id)
+    .bind(&now)
+    .bind(&retention.eligible_date)
+    .bind(&retention.rule)
+    .bind(&actor.login)
+    .bind(&now)
+    .execute(&mut *conn)
+    .await?;
+
+    // Update box count
+    let updated = sqlx::query(
+        "UPDATE dead_archive_boxes SET current_count = current_count + 1
+         WHERE id = ? AND current_count < capacity",
+    )
+    .bind(box_id)
+    .execute(&mut *conn)
+    .await?;
+    if updated.rows_affected() != 1 {
+        bail!("Caixa {} está cheia", box_before.box_number);
+    }
+
+    let item = fetch_archive_item(&mut *conn, result.last_insert_rowid()).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "TRANSFER",
+        "archive_item",
+        item.id,
+        None,
+        Some(&item),
+    )
+    .await?;
+
+    let box_after = fetch_archive_box(&mut *conn, box_id).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "TRANSFER",
+        "archive_box",
+        box_id,
+        Some(&box_before),
+        Some(&box_after),
+    )
+    .await?;
+    Ok(item)
+}
+
+/// Prazo de guarda usado quando o funcionário não tem documentos arquivados.
+const DEFAULT_RETENTION_YEARS: i64 = 5;
+
+/// Data de descarte e a regra de temporalidade que a produziu.
+struct Retention {
+    eligible_date: String,
+    rule: String,
+}
+
+/// Data do desligamento somada ao maior `retention_years` entre os documentos do funcionário.
+async fn disposal_eligible_date(
+    conn: &mut SqliteConnection,
+    employee_id: i64,
+    termination_date: &str,
+) -> Result<Retention> {
+    let termination = chrono::NaiveDate::parse_from_str(termination_date, "%Y-%m-%d")
+        .with_context(|| format!("Data de desligamento inválida: {}", termination_date))?;
+
+    let longest = sqlx::query(
+        "SELECT dt.name, dt.retention_years FROM documents d
+         JOIN document_types dt ON dt.id = d.type_id
+         WHERE d.employee_id = ? AND d.deleted_at IS NULL AND dt.retention_years IS NOT NULL
+         ORDER BY dt.retention_years DESC, dt.name
+         LIMIT 1",
+    )
+    .bind(employee_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (source, years) = match longest {
+        Some(row) => (row.get::<String, _>(0), row.get::<i64, _>(1).max(0)),
+        None => ("prazo padrão".to_string(), DEFAULT_RETENTION_YEARS),
+    };
+
+    let eligible = termination
+        .checked_add_months(chrono::Months::new(years as u32 * 12))
+        .context("Prazo de guarda fora do intervalo de datas suportado")?;
+    Ok(Retention {
+        eligible_date: eligible.format("%Y-%m-%d").to_string(),
+        rule: format!("{} anos após o desligamento ({})", years, source),
+    })
+}
+
+/// Recalcula a data de descarte da pasta no arquivo morto, se houver, depois de
+/// novos documentos ou de mudança na data de desligamento.
+async fn refresh_disposal_date(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    employee_id: i64,
+) -> Result<()> {
+    let item_id: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM dead_archive_items WHERE employee_id = ? AND disposed = 0",
+    )
+    .bind(employee_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let termination_date: Option<String> =
+        sqlx::query_scalar("SELECT termination_date FROM employees WHERE id = ?")
+            .bind(employee_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    let (Some(item_id), Some(termination_date)) = (item_id, termination_date) else {
+        return Ok(());
+    };
+    // Datas legadas fora do padrão AAAA-MM-DD mantêm a data de descarte já gravada,
+    // em vez de impedir a gravação de documentos e tipos
+    if chrono::NaiveDate::parse_from_str(&termination_date, "%Y-%m-%d").is_err() {
+        return Ok(());
+    }
+
+    let before = fetch_archive_item(&mut *conn, item_id).await?;
+    let retention = disposal_eligible_date(conn, employee_id, &termination_date).await?;
+    if before.disposal_eligible_date.as_deref() == Some(retention.eligible_date.as_str())
+        && before.disposal_rule.as_deref() == Some(retention.rule.as_str())
+    {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "UPDATE dead_archive_items SET disposal_eligible_date = ?, disposal_rule = ? WHERE id = ?",
+    )
+    .bind(&retention.eligible_date)
+    .bind(&retention.rule)
+    .bind(item_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let after = fetch_archive_item(&mut *conn, item_id).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "UPDATE",
+        "archive_item",
+        item_id,
+        Some(&before),
+        Some(&after),
+    )
+    .await
+}
+
+/// Localização da pasta no formato `<arquivo>-G<gaveta>-P<posição>`.
+async fn folder_location(conn: &mut SqliteConnection, position_id: i64) -> Result<Option<String>> {
+    let row = sqlx::query(
+        "SELECT fc.number, d.number, dp.position FROM drawer_positions dp
+         JOIN drawers d ON d.id = dp.drawer_id
+         JOIN file_cabinets fc ON fc.id = d.file_cabinet_id
+         WHERE dp.id = ?",
+    )
+    .bind(position_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|row| {
+        format!(
+            "{}-G{}-P{}",
+            row.get::<String, _>(0),
+            row.get::<i64, _>(1),
+            row.get::<i64, _>(2)
+        )
+    }))
+}
+
+/// Caixa do arquivo morto onde está a pasta ainda não descartada, com a data a
+/// partir da qual pode ser eliminada.
+async fn archived_folder(
+    conn: &mut SqliteConnection,
+    employee_id: i64,
+) -> Result<Option<(String, Option<String>)>> {
+    let row = sqlx::query(
+        "SELECT b.box_number, i.disposal_eligible_date FROM dead_archive_items i
+     JOIN dead_archive_boxes b ON b.id = i.box_id
+     WHERE i.employee_id = ? AND i.disposed = 0",
+    )
+    .bind(employee_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.map(|row| (row.get(0), row.get(1))))
+}
+
+/// Data AAAA-MM-DD (ou RFC 3339) no formato dd/mm/aaaa, para mensagens.
+fn display_date(value: &str) -> String {
+    value
+        .get(..10)
+        .and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .map(|date| date.format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Empréstimo que mantém a pasta fora do arquivo: em aberto ou extraviada.
+async fn open_folder_loan(
+    conn: &mut SqliteConnection,
+    employee_id: i64,
+) -> Result<Option<crate::types::LoanRecord>> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM loans l
+         WHERE l.employee_id = ? AND l.status IN ('BORROWED', 'LOST')
+         ORDER BY l.loan_date DESC LIMIT 1",
+        LOAN_COLUMNS
+    ))
+    .bind(employee_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(row.as_ref().map(loan_from_row))
+}
+
+/// Pastas transferidas para o arquivo morto (ou já descartadas) não saem em
+/// empréstimo pelo arquivo ativo.
+async fn ensure_folder_in_active_archive(
+    conn: &mut SqliteConnection,
+    employee: &crate::types::EmployeeRecord,
+) -> Result<()> {
+    if let Some((box_number, _)) = archived_folder(conn, employee.id).await? {
+        bail!(
+            "Pasta de {} está no arquivo morto (caixa {})",
+            employee.full_name,
+            box_number
+        );
+    }
+    let disposal_date: Option<Option<String>> = sqlx::query_scalar(
+        "SELECT disposal_date FROM dead_archive_items WHERE employee_id = ? AND disposed = 1",
+    )
+    .bind(employee.id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(disposal_date) = disposal_date {
+        bail!(
+            "Pasta de {} foi descartada{}",
+            employee.full_name,
+            disposal_date
+                .map(|date| format!(" em {}", display_date(&date)))
+                .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// Confere se a pasta pode sair para `requester_name`: o funcionário existe,
+/// a pasta está no arquivo ativo, não está emprestada e, havendo fila de
+/// espera, o solicitante é o primeiro dela. Devolve a reserva atendida.
+async fn ensure_folder_available(
+    conn: &mut SqliteConnection,
+    employee_id: i64,
+    requester_name: &str,
+) -> Result<Option<crate::types::LoanReservationRecord>> {
+    let employee = fetch_employee(&mut *conn, employee_id)
+        .await
+        .context("Funcionário não encontrado")?;
+    ensure_folder_in_active_archive(conn, &employee).await?;
+
+    if let Some(loan) = open_folder_loan(conn, employee_id).await? {
+        if loan.status == "LOST" {
+            bail!(
+                "Pasta de {} consta como extraviada no empréstimo para {}; registre a devolução quando for encontrada",
+                employee.full_name,
+                loan.requester_name
+            );
+        }
+        bail!(
+            "Pasta de {} já está emprestada para {} até {}",
+            employee.full_name,
+            loan.requester_name,
+            display_date(&loan.expected_return_date)
+        );
+    }
+
+    let Some(next) = active_reservations(conn, employee_id)
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    if !next
+        .requester_name
+        .eq_ignore_ascii_case(requester_name.trim())
+    {
+        bail!(
+            "Pasta de {} está reservada para {} desde {}; cancele a reserva ou empreste a quem reservou",
+            employee.full_name,
+            next.requester_name,
+            display_date(&next.created_at)
+        );
+    }
+    Ok(Some(next))
+}
+
+async fn active_reservations(
+    conn: &mut SqliteConnection,
+    employee_id: i64,
+) -> Result<Vec<crate::types::LoanReservationRecord>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM loan_reservations
+         WHERE employee_id = ? AND status IN ('WAITING', 'NOTIFIED') ORDER BY id",
+        LOAN_RESERVATION_COLUMNS
+    ))
+    .bind(employee_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.iter().map(loan_reservation_from_row).collect())
+}
+
+/// Avisa o primeiro da fila de que a pasta voltou.
+async fn notify_next_reservation(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    employee_id: i64,
+) -> Result<Option<crate::types::LoanReservationRecord>> {
+    let Some(next) = active_reservations(conn, employee_id)
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    if next.status == "NOTIFIED" {
+        return Ok(Some(next));
+    }
+    set_reservation_status(conn, actor, next, "NOTIFIED", None)
+        .await
+        .map(Some)
+}
+
+async fn set_reservation_status(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    before: crate::types::LoanReservationRecord,
+    status: &str,
+    loan_id: Option<i64>,
+) -> Result<crate::types::LoanReservationRecord> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "UPDATE loan_reservations SET status = ?,
+         notified_at = CASE WHEN ? = 'NOTIFIED' THEN ? ELSE notified_at END,
+         loan_id = COALESCE(?, loan_id), updated_at = ? WHERE id = ?",
+    )
+    .bind(status)
+    .bind(status)
+    .bind(&now)
+    .bind(loan_id)
+    .bind(&now)
+    .bind(before.id)
+    .execute(&mut *conn)
+    .await?;
+
+    let after = fetch_loan_reservation(&mut *conn, before.id).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "UPDATE",
+        "loan_reservation",
+        before.id,
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+    Ok(after)
+}
+
+// ========================== AUDIT ==========================
+
+/// Registra uma mutação em `audit_logs`. Quando há estado anterior e posterior,
+/// apenas os campos alterados são gravados em `old_values`/`new_values`.
+async fn write_audit<T: Serialize>(
+    conn: &mut SqliteConnection,
+    user_id: Option<i64>,
+    action: &str,
+    entity_type: &str,
+    entity_id: i64,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<()> {
+    let before = before.map(serde_json::to_value).transpose()?;
+    let after = after.map(serde_json::to_value).transpose()?;
+    let (old_values, new_values) = match (before, after) {
+        (Some(before), Some(after)) => {
+            let (old_values, new_values) = audit_diff(&before, &after);
+            (Some(old_values), Some(new_values))
+        }
+        values => values,
+    };
+
+    sqlx::query(
+        "INSERT INTO audit_logs (user_id, action, entity_type, entity_id, old_values, new_values, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(action)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(old_values.map(|value| value.to_string()))
+    .bind(new_values.map(|value| value.to_string()))
+    .bind(Utc::now().to_rfc3339())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+fn audit_diff(before: &Value, after: &Value) -> (Value, Value) {
+    let (Some(old), Some(new)) = (before.as_object(), after.as_object()) else {
+        return (before.clone(), after.clone());
+    };
+
+    let mut old_changes = Map::new();
+    let mut new_changes = Map::new();
+    for (key, new_value) in new {
+        if key == "updated_at" {
+            continue;
+        }
+        let old_value = old.get(key).unwrap_or(&Value::Null);
+        if old_value != new_value {
+            old_changes.insert(key.clone(), old_value.clone());
+            new_changes.insert(key.clone(), new_value.clone());
+        }
+    }
+    (Value::Object(old_changes), Value::Object(new_changes))
+}
+
+fn audit_log_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<crate::types::AuditLogRecord> {
+    let old_values: Option<String> = row.get(7);
+    let new_values: Option<String> = row.get(8);
+
+    Ok(crate::types::AuditLogRecord {
+        id: row.get(0),
+        user_id: row.get(1),
+        user_name: row.get(2),
+        user_login: row.get(3),
+        action: row.get(4),
+        entity_type: row.get(5),
+        entity_id: row.get(6),
+        old_values: old_values
+            .map(|raw| serde_json::from_str(&raw))
+            .transpose()?,
+        new_values: new_values
+            .map(|raw| serde_json::from_str(&raw))
+            .transpose()?,
+        ip_address: row.get(9),
+        created_at: row.get(10),
+    })
+}
+
+// ========================== LIST FILTERS ==========================
+
+/// Monta o ORDER BY a partir de `sort_by`, aceitando apenas as colunas listadas
+/// (chave do payload, expressão SQL). Sem `sort_by`, usa a ordem padrão.
+fn order_by(
+    paging: &crate::types::PageRequest,
+    columns: &[(&str, &str)],
+    default: &str,
+) -> Result<String> {
+    let Some(sort_by) = paging.sort_by.as_deref() else {
+        return Ok(default.to_string());
+    };
+    let Some((_, column)) = columns.iter().find(|(key, _)| *key == sort_by) else {
+        bail!("Campo de ordenação inválido: {}", sort_by);
+    };
+    let direction = if paging.sort_dir.as_deref() == Some("desc") {
+        "DESC"
+    } else {
+        "ASC"
+    };
+    Ok(format!("{} {}", column, direction))
+}
+
+/// Parâmetros ?1 a ?11 do filtro de `list_employees`, na ordem da cláusula WHERE.
+fn bind_employee_filter<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &'q crate::types::EmployeeFilter,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(non_empty(filter.status.as_deref()))
+        .bind(filter.department_id)
+        .bind(filter.admission_from.as_deref())
+        .bind(filter.admission_to.as_deref())
+        .bind(filter.termination_from.as_deref())
+        .bind(filter.termination_to.as_deref())
+        .bind(filter.file_cabinet_id)
+        .bind(filter.drawer_id)
+        .bind(filter.drawer_position_id)
+        .bind(filter.has_position)
+        .bind(filter.has_open_loan)
+}
+
+fn compliance_rate(compliant: i64, required: i64) -> f32 {
+    if required > 0 {
+        (compliant as f32 / required as f32) * 100.0
+    } else {
+        100.0
+    }
+}
+
+/// Janela de aviso de vencimento usada no painel.
+pub const EXPIRATION_ALERT_DAYS: i64 = 30;
+
+/// Documentos com vencimento que ainda pedem renovação: o funcionário não foi
+/// desligado e não há documento do mesmo tipo com vencimento posterior (a
+/// renovação já arquivada). Documentos excluídos não contam. Espera os aliases
+/// `d` (documents) e `e` (employees).
+const PENDING_EXPIRATION: &str = "d.expiration_date IS NOT NULL
+       AND d.deleted_at IS NULL
+       AND e.status != 'TERMINATED'
+       AND NOT EXISTS (
+         SELECT 1 FROM documents r
+         WHERE r.employee_id = d.employee_id AND r.type_id = d.type_id
+           AND r.deleted_at IS NULL
+           AND DATE(r.expiration_date) > DATE(d.expiration_date)
+       )";
+
+/// Situação do empréstimo. Devolvido e cancelado são finais; a pasta
+/// extraviada ainda pode ser devolvida se for encontrada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoanStatus {
+    Borrowed,
+    Returned,
+    Lost,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoanAction {
+    Return,
+    PartialReturn,
+    Renew,
+    ReportLost,
+    Cancel,
+}
+
+impl LoanStatus {
+    fn parse(status: &str) -> Result<Self> {
+        Ok(match status {
+            "BORROWED" => LoanStatus::Borrowed,
+            "RETURNED" => LoanStatus::Returned,
+            "LOST" => LoanStatus::Lost,
+            "CANCELLED" => LoanStatus::Cancelled,
+            other => bail!("Situação de empréstimo desconhecida: {}", other),
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LoanStatus::Borrowed => "BORROWED",
+            LoanStatus::Returned => "RETURNED",
+            LoanStatus::Lost => "LOST",
+            LoanStatus::Cancelled => "CANCELLED",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LoanStatus::Borrowed => "em aberto",
+            LoanStatus::Returned => "já devolvido",
+            LoanStatus::Lost => "com pasta extraviada",
+            LoanStatus::Cancelled => "cancelado",
+        }
+    }
+
+    /// Situação resultante da ação, ou erro se a transição não é permitida.
+    fn apply(self, action: LoanAction) -> Result<LoanStatus> {
+        let next = match (self, action) {
+            (LoanStatus::Borrowed, LoanAction::Return) => LoanStatus::Returned,
+            (LoanStatus::Borrowed, LoanAction::PartialReturn) => LoanStatus::Borrowed,
+            (LoanStatus::Borrowed, LoanAction::Renew) => LoanStatus::Borrowed,
+            (LoanStatus::Borrowed, LoanAction::ReportLost) => LoanStatus::Lost,
+            (LoanStatus::Borrowed, LoanAction::Cancel) => LoanStatus::Cancelled,
+            (LoanStatus::Lost, LoanAction::Return) => LoanStatus::Returned,
+            (status, action) => bail!(
+                "Empréstimo {} não pode ser {}",
+                status.label(),
+                action.label()
+            ),
+        };
+        Ok(next)
+    }
+}
+
+impl LoanAction {
+    fn label(self) -> &'static str {
+        match self {
+            LoanAction::Return => "devolvido",
+            LoanAction::PartialReturn => "devolvido em parte",
+            LoanAction::Renew => "renovado",
+            LoanAction::ReportLost => "dado como extraviado",
+            LoanAction::Cancel => "cancelado",
+        }
+    }
+}
+
+const LOAN_FILTER: &str = "WHERE (?1 IS NULL OR status = ?1)
+       AND (?2 IS NULL OR requester_name LIKE '%' || ?2 || '%')
+       AND (?3 IS NULL OR requester_department_id = ?3)
+       AND (?4 IS NULL OR substr(loan_date, 1, 10) >= ?4)
+       AND (?5 IS NULL OR substr(loan_date, 1, 10) <= ?5)";
+
+fn bind_loan_filter<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &'q crate::types::LoanFilter,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(non_empty(filter.status.as_deref()))
+        .bind(non_empty(filter.requester.as_deref()))
+        .bind(filter.requester_department_id)
+        .bind(filter.date_from.as_deref())
+        .bind(filter.date_to.as_deref())
+}
+
+const ARCHIVE_BOX_FILTER: &str = "WHERE (?1 IS NULL OR year = ?1)
+       AND (?2 IS NULL OR location LIKE '%' || ?2 || '%')
+       AND (?3 IS NULL
+            OR (?3 = 'empty' AND current_count = 0)
+            OR (?3 = 'partial' AND current_count > 0 AND current_count < capacity)
+            OR (?3 = 'full' AND current_count >= capacity))";
+
+fn bind_archive_box_filter<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &'q crate::types::ArchiveBoxFilter,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(filter.year)
+        .bind(non_empty(filter.location.as_deref()))
+        .bind(filter.fullness.as_deref())
+}
+
+const MOVEMENT_FILTER: &str = "WHERE (?1 IS NULL OR actor = ?1)
+       AND (?2 IS NULL OR action LIKE '%' || ?2 || '%')
+       AND (?3 IS NULL OR substr(created_at, 1, 10) >= ?3)
+       AND (?4 IS NULL OR substr(created_at, 1, 10) <= ?4)";
+
+fn bind_movement_filter<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    filter: &'q crate::types::MovementFilter,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(non_empty(filter.actor.as_deref()))
+        .bind(non_empty(filter.action.as_deref()))
+        .bind(filter.date_from.as_deref())
+        .bind(filter.date_to.as_deref())
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+// ========================== SEARCH ==========================
+
+/// Converte o texto digitado numa consulta FTS5 segura: cada palavra vira um
+/// prefixo entre aspas, exigido em conjunto. Um CPF com máscara vira um único
+/// termo só com dígitos, como está no índice; outros números com pontuação
+/// (ex.: matrícula `12.345-6`) são divididos como o tokenizador do índice faz.
+fn fts_query(input: &str) -> Option<String> {
+    let is_cpf = input
+        .chars()
+        .all(|c| c.is_ascii_digit() || c.is_ascii_punctuation() || c.is_whitespace())
+        && crate::cpf::normalize(input).len() == 11;
+    let terms: Vec<String> = if is_cpf {
+        vec![crate::cpf::normalize(input)]
+    } else {
+        input
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_string)
+            .collect()
+    };
+
+    let terms: Vec<String> = terms
+        .into_iter()
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+// ========================== RECORD LOADERS ==========================
+
+async fn fetch_storage_unit<'e, E>(executor: E, id: i64) -> Result<StorageUnitRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, label, type, section, capacity, occupancy, metadata, created_at, updated_at FROM storage_units WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    let metadata_str: Option<String> = row.get(6);
+    let parsed = metadata_str.and_then(|json| serde_json::from_str::<Value>(&json).ok());
+
+    Ok(StorageUnitRecord {
+        id: row.get(0),
+        label: row.get(1),
+        r#type: row.get(2),
+        section: row.get(3),
+        capacity: row.get(4),
+        occupancy: row.get(5),
+        metadata: parsed,
+        created_at: row.get(7),
+        updated_at: row.get(8),
+    })
+}
+
+async fn fetch_movement<'e, E>(executor: E, id: i64) -> Result<MovementRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, reference, item_label, from_unit, to_unit, action, note, actor, created_at FROM movements WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(MovementRecord {
+        id: row.get(0),
+        reference: row.get(1),
+        item_label: row.get(2),
+        from_unit: row.get(3),
+        to_unit: row.get(4),
+        action: row.get(5),
+        note: row.get(6),
+        actor: row.get(7),
+        created_at: row.get(8),
+    })
+}
+
+async fn fetch_user<'e, E>(executor: E, id: i64) -> Result<UserRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, name, login, role, is_active, must_change_password, created_at
+         FROM users WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(UserRecord {
+        id: row.get(0),
+        name: row.get(1),
+        login: row.get(2),
+        role: row.get(3),
+        is_active: row.get::<i64, _>(4) == 1,
+        must_change_password: row.get::<i64, _>(5) == 1,
+        created_at: row.get(6),
+    })
+}
+
+async fn fetch_department<'e, E>(executor: E, id: i64) -> Result<crate::types::DepartmentRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, name, code, description, is_active, created_at, updated_at
+         FROM departments WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::DepartmentRecord {
+        id: row.get(0),
+        name: row.get(1),
+        code: row.get(2),
+        description: row.get(3),
+        is_active: row.get::<i64, _>(4) == 1,
+        created_at: row.get(5),
+        updated_at: row.get(6),
+    })
+}
+
+async fn fetch_employee<'e, E>(executor: E, id: i64) -> Result<crate::types::EmployeeRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT e.id, e.full_name, e.registration, e.cpf, e.department_id, d.name as department_name,
+         e.admission_date, e.termination_date, e.status, e.drawer_position_id, e.notes,
+         e.created_at, e.updated_at, e.termination_reason
+         FROM employees e
+         LEFT JOIN departments d ON e.department_id = d.id
+         WHERE e.id = ?"
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::EmployeeRecord {
+        id: row.get(0),
+        full_name: row.get(1),
+        registration: row.get(2),
+        cpf: row
+            .get::<Option<String>, _>(3)
+            .map(|cpf| crate::cpf::format(&cpf)),
+        department_id: row.get(4),
+        department_name: row.get(5),
+        admission_date: row.get(6),
+        termination_date: row.get(7),
+        termination_reason: row.get(13),
+        status: row.get(8),
+        drawer_position_id: row.get(9),
+        notes: row.get(10),
+        created_at: row.get(11),
+        updated_at: row.get(12),
+    })
+}
+
+async fn fetch_file_cabinet<'e, E>(executor: E, id: i64) -> Result<crate::types::FileCabinetRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, number, location, num_drawers, description, is_active, created_at, updated_at
+         FROM file_cabinets WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::FileCabinetRecord {
+        id: row.get(0),
+        number: row.get(1),
+        location: row.get(2),
+        num_drawers: row.get(3),
+        description: row.get(4),
+        is_active: row.get::<i64, _>(5) == 1,
+        created_at: row.get(6),
+        updated_at: row.get(7),
+    })
+}
+
+async fn fetch_drawer<'e, E>(executor: E, id: i64) -> Result<crate::types::DrawerRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, file_cabinet_id, number, capacity, label, created_at
+         FROM drawers WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::DrawerRecord {
+        id: row.get(0),
+        file_cabinet_id: row.get(1),
+        number: row.get(2),
+        capacity: row.get(3),
+        label: row.get(4),
+        created_at: row.get(5),
+    })
+}
+
+async fn fetch_drawer_position<'e, E>(
+    executor: E,
+    id: i64,
+) -> Result<crate::types::DrawerPositionRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, drawer_id, position, employee_id, is_occupied, created_at
+         FROM drawer_positions WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::DrawerPositionRecord {
+        id: row.get(0),
+        drawer_id: row.get(1),
+        position: row.get(2),
+        employee_id: row.get(3),
+        is_occupied: row.get::<i64, _>(4) == 1,
+        created_at: row.get(5),
+    })
+}
+
+const DOCUMENT_COLUMNS: &str = "d.id, d.employee_id, d.category_id, d.type_id, d.description,
+     d.document_date, d.filing_date, d.expiration_date, d.notes, d.filed_by, d.created_at,
+     d.updated_at, d.deleted_at, d.deleted_by, d.deletion_reason";
+
+fn document_from_row(row: &sqlx::sqlite::SqliteRow) -> crate::types::DocumentRecord {
+    crate::types::DocumentRecord {
+        id: row.get(0),
+        employee_id: row.get(1),
+        category_id: row.get(2),
+        type_id: row.get(3),
+        description: row.get(4),
+        document_date: row.get(5),
+        filing_date: row.get(6),
+        expiration_date: row.get(7),
+        notes: row.get(8),
+        filed_by: row.get(9),
+        created_at: row.get(10),
+        updated_at: row.get(11),
+        deleted_at: row.get(12),
+        deleted_by: row.get(13),
+        deletion_reason: row.get(14),
+    }
+}
+
+async fn fetch_document<'e, E>(executor: E, id: i64) -> Result<crate::types::DocumentRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM documents d WHERE d.id = ?",
+        DOCUMENT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(document_from_row(&row))
+}
+
+const DOCUMENT_CATEGORY_COLUMNS: &str =
+    "id, name, code, description, icon, color, is_active, created_at";
+
+fn document_category_from_row(
+    row: &sqlx::sqlite::SqliteRow,
+) -> crate::types::DocumentCategoryRecord {
+    crate::types::DocumentCategoryRecord {
+        id: row.get(0),
+        name: row.get(1),
+        code: row.get(2),
+        description: row.get(3),
+        icon: row.get(4),
+        color: row.get(5),
+        is_active: row.get::<i64, _>(6) == 1,
+        created_at: row.get(7),
+    }
+}
+
+async fn fetch_document_category<'e, E>(
+    executor: E,
+    id: i64,
+) -> Result<crate::types::DocumentCategoryRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM document_categories WHERE id = ?",
+        DOCUMENT_CATEGORY_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(document_category_from_row(&row))
+}
+
+const DOCUMENT_TYPE_COLUMNS: &str =
+    "id, category_id, name, retention_years, is_required, is_active, created_at";
+
+fn document_type_from_row(row: &sqlx::sqlite::SqliteRow) -> crate::types::DocumentTypeRecord {
+    crate::types::DocumentTypeRecord {
+        id: row.get(0),
+        category_id: row.get(1),
+        name: row.get(2),
+        retention_years: row.get(3),
+        is_required: row.get::<i64, _>(4) == 1,
+        is_active: row.get::<i64, _>(5) == 1,
+        created_at: row.get(6),
+    }
+}
+
+async fn fetch_document_type<'e, E>(
+    executor: E,
+    id: i64,
+) -> Result<crate::types::DocumentTypeRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM document_types WHERE id = ?",
+        DOCUMENT_TYPE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(document_type_from_row(&row))
+}
+
+/// Nome e código de categoria não se repetem (o nome sem diferenciar maiúsculas).
+async fn ensure_unique_category(
+    conn: &mut SqliteConnection,
+    id: Option<i64>,
+    payload: &crate::types::DocumentCategoryPayload,
+) -> Result<()> {
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT name FROM document_categories
+         WHERE (name = ? COLLATE NOCASE OR code = ?) AND id IS NOT ?",
+    )
+    .bind(payload.name.trim())
+    .bind(&payload.code)
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(name) = existing {
+        bail!("Já existe a categoria {} com este nome ou código", name);
+    }
+    Ok(())
+}
+
+async fn ensure_unique_type(
+    conn: &mut SqliteConnection,
+    id: Option<i64>,
+    payload: &crate::types::DocumentTypePayload,
+) -> Result<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM document_types
+         WHERE category_id = ? AND name = ? COLLATE NOCASE AND id IS NOT ?)",
+    )
+    .bind(payload.category_id)
+    .bind(payload.name.trim())
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if exists {
+        bail!("Já existe um tipo {} nesta categoria", payload.name.trim());
+    }
+    Ok(())
+}
+
+/// Tipo ativo só pode ficar em categoria ativa.
+async fn ensure_type_category(
+    conn: &mut SqliteConnection,
+    category_id: i64,
+    is_active: bool,
+) -> Result<()> {
+    let category = fetch_document_category(&mut *conn, category_id)
+        .await
+        .context("Categoria não encontrada")?;
+    if is_active && !category.is_active {
+        bail!("Categoria {} está desativada", category.name);
+    }
+    Ok(())
+}
+
+/// Confere, antes de gravar um documento, se o tipo existe, está ativo e
+/// pertence à categoria informada.
+async fn ensure_document_type(
+    conn: &mut SqliteConnection,
+    category_id: i64,
+    type_id: i64,
+) -> Result<()> {
+    let document_type = fetch_document_type(&mut *conn, type_id)
+        .await
+        .context("Tipo de documento não encontrado")?;
+    if document_type.category_id != category_id {
+        bail!(
+            "Tipo {} não pertence à categoria informada",
+            document_type.name
+        );
+    }
+    if !document_type.is_active {
+        bail!("Tipo {} está desativado", document_type.name);
+    }
+    Ok(())
+}
+
+async fn set_document_type_inactive(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    before: crate::types::DocumentTypeRecord,
+) -> Result<crate::types::DocumentTypeRecord> {
+    sqlx::query("UPDATE document_types SET is_active = 0 WHERE id = ?")
+        .bind(before.id)
+        .execute(&mut *conn)
+        .await?;
+    let after = fetch_document_type(&mut *conn, before.id).await?;
+    write_audit(
+        conn,
+        Some(actor.id),
+        "UPDATE",
+        "document_type",
+        before.id,
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+    Ok(after)
+}
+
+async fn deactivate_category_types(
+    conn: &mut SqliteConnection,
+    actor: &UserProfile,
+    category_id: i64,
+) -> Result<()> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM document_types WHERE category_id = ? AND is_active = 1",
+        DOCUMENT_TYPE_COLUMNS
+    ))
+    .bind(category_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    for document_type in rows.iter().map(document_type_from_row) {
+        set_document_type_inactive(conn, actor, document_type).await?;
+    }
+    Ok(())
+}
+
+const DOCUMENT_FILE_COLUMNS: &str =
+    "id, document_id, sha256, file_name, mime_type, size_bytes, attached_by, created_at";
+
+fn document_file_from_row(row: &sqlx::sqlite::SqliteRow) -> crate::types::DocumentFileRecord {
+    crate::types::DocumentFileRecord {
+        id: row.get(0),
+        document_id: row.get(1),
+        sha256: row.get(2),
+        file_name: row.get(3),
+        mime_type: row.get(4),
+        size_bytes: row.get(5),
+        attached_by: row.get(6),
+        created_at: row.get(7),
+    }
+}
+
+async fn fetch_document_file<'e, E>(
+    executor: E,
+    id: i64,
+) -> Result<crate::types::DocumentFileRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM document_files WHERE id = ?",
+        DOCUMENT_FILE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(document_file_from_row(&row))
+}
+
+const LOAN_COLUMNS: &str = "l.id, l.employee_id, l.requester_name, l.requester_department_id,
+     l.reason, l.loan_date, l.expected_return_date, l.actual_return_date, l.status,
+     l.return_notes, l.loaned_by, l.returned_by, l.created_at, l.updated_at, l.renewal_count,
+     l.incident_notes, l.cancellation_reason, l.outstanding_items";
+
+fn loan_from_row(row: &sqlx::sqlite::SqliteRow) -> crate::types::LoanRecord {
+    crate::types::LoanRecord {
+        id: row.get(0),
+        employee_id: row.get(1),
+        requester_name: row.get(2),
+        requester_department_id: row.get(3),
+        reason: row.get(4),
+        loan_date: row.get(5),
+        expected_return_date: row.get(6),
+        actual_return_date: row.get(7),
+        status: row.get(8),
+        return_notes: row.get(9),
+        loaned_by: row.get(10),
+        returned_by: row.get(11),
+        created_at: row.get(12),
+        updated_at: row.get(13),
+        renewal_count: row.get(14),
+        incident_notes: row.get(15),
+        cancellation_reason: row.get(16),
+        outstanding_items: row.get(17),
+    }
+}
+
+async fn fetch_loan<'e, E>(executor: E, id: i64) -> Result<crate::types::LoanRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM loans l WHERE l.id = ?",
+        LOAN_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(loan_from_row(&row))
+}
+
+const LOAN_RESERVATION_COLUMNS: &str = "id, employee_id, requester_name, requester_department_id,
+     reason, status, loan_id, reserved_by, notified_at, created_at, updated_at";
+
+fn loan_reservation_from_row(row: &sqlx::sqlite::SqliteRow) -> crate::types::LoanReservationRecord {
+    crate::types::LoanReservationRecord {
+        id: row.get(0),
+        employee_id: row.get(1),
+        requester_name: row.get(2),
+        requester_department_id: row.get(3),
+        reason: row.get(4),
+        status: row.get(5),
+        loan_id: row.get(6),
+        reserved_by: row.get(7),
+        notified_at: row.get(8),
+        created_at: row.get(9),
+        updated_at: row.get(10),
+    }
+}
+
+async fn fetch_loan_reservation<'e, E>(
+    executor: E,
+    id: i64,
+) -> Result<crate::types::LoanReservationRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM loan_reservations WHERE id = ?",
+        LOAN_RESERVATION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(loan_reservation_from_row(&row))
+}
+
+async fn fetch_archive_box<'e, E>(executor: E, id: i64) -> Result<crate::types::ArchiveBoxRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, box_number, year, period, letter_range, location, capacity, current_count, created_at
+         FROM dead_archive_boxes WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::ArchiveBoxRecord {
+        id: row.get(0),
+        box_number: row.get(1),
+        year: row.get(2),
+        period: row.get(3),
+        letter_range: row.get(4),
+        location: row.get(5),
+        capacity: row.get(6),
+        current_count: row.get(7),
+        created_at: row.get(8),
+    })
+}
+
+async fn fetch_archive_item<'e, E>(executor: E, id: i64) -> Result<crate::types::ArchiveItemRecord>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT id, employee_id, box_id, transfer_date, disposal_eligible_date, disposed, disposal_date, disposal_term_number, transferred_by, created_at,
+         disposal_rule
+         FROM dead_archive_items WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(crate::types::ArchiveItemRecord {
+        id: row.get(0),
+        employee_id: row.get(1),
+        box_id: row.get(2),
+        transfer_date: row.get(3),
+        disposal_eligible_date: row.get(4),
+        disposal_rule: row.get(10),
+        disposed: row.get::<i64, _>(5) == 1,
+        disposal_date: row.get(6),
+        disposal_term_number: row.get(7),
+        transferred_by: row.get(8),
+        created_at: row.get(9),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        ArchiveBoxPayload, EmployeePayload, FileCabinetPayload, TerminationPayload,
+    };
+
+    /// Gavetas, pastas e caixas: o que uma operação interrompida não pode deixar
+    /// pela metade.
+    type StorageState = (
+        Vec<(i64, Option<i64>)>,
+        Vec<(i64, Option<i64>)>,
+        Vec<(i64, i64)>,
+    );
+
+    async fn test_db() -> (ArchiveDatabase, UserProfile) {
+        // Uma única conexão que nunca expira: o banco em memória vive nela
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:").unwrap())
+            .await
+            .unwrap();
+        migrations::run(&pool).await.unwrap();
+        let db = ArchiveDatabase { pool };
+        db.bootstrap_admin(Some("admin"), Some("Teste#2026x"))
+            .await
+            .unwrap();
+        let admin = db
+            .verify_login("admin", "Teste#2026x")
+            .await
+            .unwrap()
+            .unwrap();
+        (db, admin)
+    }
+
+    async fn storage_state(db: &ArchiveDatabase) -> StorageState {
+        let positions = sqlx::query_as("SELECT id, employee_id FROM drawer_positions ORDER BY id")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        let employees = sqlx::query_as("SELECT id, drawer_position_id FROM employees ORDER BY id")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        let boxes = sqlx::query_as("SELECT id, current_count FROM dead_archive_boxes ORDER BY id")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        (positions, employees, boxes)
+    }
+
+    async fn employee(
+        db: &ArchiveDatabase,
+        actor: &UserProfile,
+        name: &str,
+        registration: &str,
+        termination_date: Option<&str>,
+    ) -> crate::types::EmployeeRecord {
+        let payload = EmployeePayload {
+            full_name: name.into(),
+            registration: registration.into(),
+            cpf: None,
+            department_id: None,
+            admission_date: "2015-01-05".into(),
+            termination_date: termination_date.map(Into::into),
+            status: None,
+            drawer_position_id: None,
+            notes: None,
+        };
+        db.create_employee(&payload, actor).await.unwrap()
+    }
+
+    async fn drawer(db: &ArchiveDatabase, actor: &UserProfile) -> i64 {
+        let payload = FileCabinetPayload {
+            number: "A1".into(),
+            location: None,
+            num_drawers: Some(1),
+            description: None,
+            is_active: None,
+        };
+        let cabinet = db.create_file_cabinet(&payload, actor).await.unwrap();
+        sqlx::query_scalar("SELECT id FROM drawers WHERE file_cabinet_id = ?")
+            .bind(cabinet.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+    }
+
+    async fn archive_box(db: &ArchiveDatabase, actor: &UserProfile, capacity: i64) -> i64 {
+        let payload = ArchiveBoxPayload {
+            box_number: "CX-01".into(),
+            year: 2024,
+            period: None,
+            letter_range: None,
+            location: None,
+            capacity: Some(capacity),
+        };
+        db.create_archive_box(&payload, actor).await.unwrap().id
+    }
+
+    #[tokio::test]
+    async fn password_change_unblocks_bootstrap_admin() {
+        use crate::permissions::AccessError;
+        use crate::sessions::SessionStore;
+
+        let (db, admin) = test_db().await;
+        assert!(admin.must_change_password);
+        let sessions = SessionStore::default();
+        let session = sessions.create(admin.clone());
+        assert!(matches!(
+            sessions.authorize(&session.token, "list_employees"),
+            Err(AccessError::PasswordChangeRequired)
+        ));
+
+        // Mesmo caminho de `change_own_password`
+        let user = db
+            .change_password(&session.profile, "Teste#2026x", "Nova#Senha2026")
+            .await
+            .unwrap();
+        assert!(!user.must_change_password);
+        sessions.update_profile(
+            &session.token,
+            UserProfile {
+                must_change_password: user.must_change_password,
+                ..session.profile.clone()
+            },
+        );
+
+        assert!(sessions.authorize(&session.token, "list_employees").is_ok());
+        let again = db
+            .verify_login("admin", "Nova#Senha2026")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!again.must_change_password);
+    }
+
+    #[test]
+    fn fts_query_quotes_each_word_as_prefix() {
+        assert_eq!(
+            fts_query("Ana  Souza").as_deref(),
+            Some("\"Ana\"* \"Souza\"*")
+        );
+        assert_eq!(
+            fts_query("ana\"; DROP").as_deref(),
+            Some("\"ana\"* \"DROP\"*")
+        );
+        assert_eq!(fts_query(" -.- "), None);
+    }
+
+    #[test]
+    fn fts_query_collapses_only_cpfs() {
+        assert_eq!(
+            fts_query("123.456.789-09").as_deref(),
+            Some("\"12345678909\"*")
+        );
+        assert_eq!(
+            fts_query("12.345-6").as_deref(),
+            Some("\"12\"* \"345\"* \"6\"*")
+        );
+        assert_eq!(fts_query("123456").as_deref(), Some("\"123456\"*"));
+    }
+
+    #[test]
+    fn loan_status_allows_only_listed_transitions() {
+        use LoanAction::*;
+        use LoanStatus::*;
+
+        assert_eq!(Borrowed.apply(Return).unwrap(), Returned);
+        assert_eq!(Borrowed.apply(PartialReturn).unwrap(), Borrowed);
+        assert_eq!(Borrowed.apply(Renew).unwrap(), Borrowed);
+        assert_eq!(Borrowed.apply(ReportLost).unwrap(), Lost);
+        assert_eq!(Borrowed.apply(Cancel).unwrap(), Cancelled);
+        assert_eq!(Lost.apply(Return).unwrap(), Returned);
+
+        let error = Returned.apply(Return).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Empréstimo já devolvido não pode ser devolvido"
+        );
+        for action in [PartialReturn, Renew, ReportLost, Cancel] {
+            assert!(Lost.apply(action).is_err());
+        }
+        for status in [Returned, Cancelled] {
+            for action in [Return, PartialReturn, Renew, ReportLost, Cancel] {
+                assert!(status.apply(action).is_err());
+            }
+        }
+    }
+
+    async fn loan(
+        db: &ArchiveDatabase,
+        actor: &UserProfile,
+        employee_id: i64,
+        requester_name: &str,
+    ) -> Result<crate::types::LoanRecord> {
+        let payload = crate::types::LoanPayload {
+            token: String::new(),
+            employee_id,
+            requester_name: requester_name.into(),
+            requester_department_id: None,
+            reason: "Conferência de documentos".into(),
+            expected_return_date: "2099-12-31".into(),
+            return_notes: None,
+        };
+        db.create_loan(&payload, actor).await
+    }
+
+    async fn reserve(
+        db: &ArchiveDatabase,
+        actor: &UserProfile,
+        employee_id: i64,
+        requester_name: &str,
+    ) -> crate::types::LoanReservationRecord {
+        let payload = crate::types::LoanReservationPayload {
+            token: String::new(),
+            employee_id,
+            requester_name: requester_name.into(),
+            requester_department_id: None,
+            reason: "Conferência de documentos".into(),
+        };
+        db.reserve_folder(&payload, actor).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn borrowed_folder_cannot_be_lent_again() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        loan(&db, &admin, ana.id, "Setor Jurídico").await.unwrap();
+
+        let error = loan(&db, &admin, ana.id, "Auditoria Externa")
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("já está emprestada para Setor Jurídico"));
+        let open: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM loans WHERE employee_id = ?")
+            .bind(ana.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(open, 1);
+    }
+
+    #[tokio::test]
+    async fn reservations_are_served_in_arrival_order() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let first = loan(&db, &admin, ana.id, "Setor Jurídico").await.unwrap();
+        let payroll = reserve(&db, &admin, ana.id, "Folha de Pagamento").await;
+        let audit = reserve(&db, &admin, ana.id, "Auditoria Externa").await;
+        let queue: Vec<i64> = db
+            .list_folder_reservations(ana.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(queue, [payroll.id, audit.id]);
+
+        let returned = db.return_loan(first.id, None, None, &admin).await.unwrap();
+        let notified = returned.next_reservation.unwrap();
+        assert_eq!(
+            (notified.id, notified.status.as_str()),
+            (payroll.id, "NOTIFIED")
+        );
+
+        // Quem está atrás na fila não passa na frente
+        let error = loan(&db, &admin, ana.id, "Auditoria Externa")
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("reservada para Folha de Pagamento"));
+        let second = loan(&db, &admin, ana.id, "Folha de Pagamento")
+            .await
+            .unwrap();
+        let fulfilled = fetch_loan_reservation(&db.pool, payroll.id).await.unwrap();
+        assert_eq!(fulfilled.status, "FULFILLED");
+        assert_eq!(fulfilled.loan_id, Some(second.id));
+        let waiting = fetch_loan_reservation(&db.pool, audit.id).await.unwrap();
+        assert_eq!(waiting.status, "WAITING");
+    }
+
+    #[tokio::test]
+    async fn cancelling_advances_the_queue() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let wrong = loan(&db, &admin, ana.id, "Setor Jurídico").await.unwrap();
+        let payroll = reserve(&db, &admin, ana.id, "Folha de Pagamento").await;
+        let audit = reserve(&db, &admin, ana.id, "Auditoria Externa").await;
+
+        let cancelled = db
+            .cancel_loan(wrong.id, "Lançado por engano", &admin)
+            .await
+            .unwrap();
+        assert_eq!(cancelled.next_reservation.map(|r| r.id), Some(payroll.id));
+
+        // Desistência de quem foi avisado passa a vez ao próximo
+        db.cancel_reservation(payroll.id, &admin).await.unwrap();
+        let next = fetch_loan_reservation(&db.pool, audit.id).await.unwrap();
+        assert_eq!(next.status, "NOTIFIED");
+    }
+
+    #[tokio::test]
+    async fn partial_return_keeps_folder_out_until_fully_returned() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2020-03-15")).await;
+        let open = loan(&db, &admin, ana.id, "Setor Jurídico").await.unwrap();
+        let payroll = reserve(&db, &admin, ana.id, "Folha de Pagamento").await;
+
+        let payload = crate::types::LoanPartialReturnPayload {
+            token: String::new(),
+            loan_id: open.id,
+            returned_items: "Contrato e ficha de registro".into(),
+            outstanding_items: "ASO admissional".into(),
+            actual_return_date: Some("2024-05-10".into()),
+            notes: None,
+        };
+        let partial = db.partial_return_loan(&payload, &admin).await.unwrap();
+        assert_eq!(partial.status, "BORROWED");
+        assert_eq!(
+            partial.outstanding_items.as_deref(),
+            Some("ASO admissional")
+        );
+        assert_eq!(partial.actual_return_date, None);
+        let history = db.list_loan_partial_returns(open.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].return_date, "2024-05-10");
+
+        // A pasta continua fora: nem novo empréstimo, nem arquivo morto, nem aviso
+        let waiting = fetch_loan_reservation(&db.pool, payroll.id).await.unwrap();
+        assert_eq!(waiting.status, "WAITING");
+        let error = loan(&db, &admin, ana.id, "Folha de Pagamento")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("já está emprestada"));
+        let box_id = archive_box(&db, &admin, 5).await;
+        assert!(db
+            .transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .is_err());
+
+        let returned = db.return_loan(open.id, None, None, &admin).await.unwrap();
+        assert_eq!(returned.loan.status, "RETURNED");
+        assert_eq!(returned.loan.outstanding_items, None);
+        assert_eq!(returned.next_reservation.map(|r| r.id), Some(payroll.id));
+        let error = db.partial_return_loan(&payload, &admin).await.unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("não pode ser devolvido em parte"));
+    }
+
+    #[tokio::test]
+    async fn borrowed_or_lost_folder_cannot_go_to_dead_archive() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2020-03-15")).await;
+        let box_id = archive_box(&db, &admin, 5).await;
+        let open = loan(&db, &admin, ana.id, "Setor Jurídico").await.unwrap();
+
+        let before = storage_state(&db).await;
+        let error = db
+            .transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("está emprestada para Setor Jurídico"));
+
+        db.report_lost_loan(open.id, "Não localizada no setor", &admin)
+            .await
+            .unwrap();
+        let error = db
+            .transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("consta como extraviada"));
+        assert_eq!(storage_state(&db).await, before);
+
+        db.return_loan(open.id, None, Some("Encontrada no RH"), &admin)
+            .await
+            .unwrap();
+        db.transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn assign_to_occupied_position_keeps_storage() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        let drawer_id = drawer(&db, &admin).await;
+        db.assign_employee_position(ana.id, drawer_id, 1, &admin)
+            .await
+            .unwrap();
+        db.assign_employee_position(bia.id, drawer_id, 2, &admin)
+            .await
+            .unwrap();
+
+        let before = storage_state(&db).await;
+        let error = db
+            .assign_employee_position(bia.id, drawer_id, 1, &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("já está ocupada"));
+        assert_eq!(storage_state(&db).await, before);
+    }
+
+    #[tokio::test]
+    async fn failure_after_moving_position_rolls_back() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let drawer_id = drawer(&db, &admin).await;
+        db.assign_employee_position(ana.id, drawer_id, 1, &admin)
+            .await
+            .unwrap();
+
+        // Falha injetada depois de ocupar a nova posição e liberar a antiga
+        sqlx::query(
+            "CREATE TEMP TRIGGER fail_employee_update BEFORE UPDATE ON employees
+             BEGIN SELECT RAISE(ABORT, 'falha injetada'); END",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        let before = storage_state(&db).await;
+        let audits: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+
+        let error = db
+            .assign_employee_position(ana.id, drawer_id, 2, &admin)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("falha injetada"));
+        assert_eq!(storage_state(&db).await, before);
+        let audits_after: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(audits_after, audits);
+    }
+
+    #[tokio::test]
+    async fn transfer_to_full_box_keeps_storage() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2024-01-10")).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", Some("2024-01-10")).await;
+        let box_id = archive_box(&db, &admin, 1).await;
+        db.transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+
+        let before = storage_state(&db).await;
+        let error = db
+            .transfer_to_archive(bia.id, box_id, &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("está cheia"));
+        assert_eq!(storage_state(&db).await, before);
+    }
+
+    #[tokio::test]
+    async fn terminate_into_full_box_keeps_storage() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2024-01-10")).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        let drawer_id = drawer(&db, &admin).await;
+        db.assign_employee_position(bia.id, drawer_id, 1, &admin)
+            .await
+            .unwrap();
+        let box_id = archive_box(&db, &admin, 1).await;
+        db.transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+
+        let before = storage_state(&db).await;
+        let payload = TerminationPayload {
+            token: String::new(),
+            employee_id: bia.id,
+            termination_date: "2024-03-15".into(),
+            reason: None,
+            transfer_to_box_id: Some(box_id),
+        };
+        let error = db.terminate_employee(&payload, &admin).await.unwrap_err();
+        assert!(error.to_string().contains("está cheia"));
+        assert_eq!(storage_state(&db).await, before);
+        let status: String = sqlx::query_scalar("SELECT status FROM employees WHERE id = ?")
+            .bind(bia.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "ACTIVE");
+    }
+
+    #[tokio::test]
+    async fn disposal_with_disposed_item_keeps_storage() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2010-01-10")).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", Some("2010-01-10")).await;
+        let box_id = archive_box(&db, &admin, 5).await;
+        let disposed = db
+            .transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+        let pending = db
+            .transfer_to_archive(bia.id, box_id, &admin)
+            .await
+            .unwrap();
+        db.register_disposal(&[disposed.id], None, &admin)
+            .await
+            .unwrap();
+
+        let before = storage_state(&db).await;
+        let error = db
+            .register_disposal(&[pending.id, disposed.id], None, &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("já foi descartado"));
+        assert_eq!(storage_state(&db).await, before);
+        let item = fetch_archive_item(&db.pool, pending.id).await.unwrap();
+        assert!(!item.disposed);
+    }
+
+    /// (ação, usuário, old_values, new_values) gravados para a entidade.
+    async fn audit_entries(
+        db: &ArchiveDatabase,
+        entity_type: &str,
+        entity_id: i64,
+    ) -> Vec<(String, Option<i64>, Option<Value>, Option<Value>)> {
+        let rows: Vec<(String, Option<i64>, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT action, user_id, old_values, new_values FROM audit_logs
+             WHERE entity_type = ? AND entity_id = ? ORDER BY id",
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        let parse = |json: Option<String>| json.map(|json| serde_json::from_str(&json).unwrap());
+        rows.into_iter()
+            .map(|(action, user_id, old, new)| (action, user_id, parse(old), parse(new)))
+            .collect()
+    }
+
+    async fn audit_count(db: &ArchiveDatabase) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn employee_create_and_update_write_one_audit_row_each() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+
+        let entries = audit_entries(&db, "employee", ana.id).await;
+        assert_eq!(entries.len(), 1);
+        let (action, user_id, old, new) = &entries[0];
+        assert_eq!(action, "CREATE");
+        assert_eq!(*user_id, Some(admin.id));
+        assert!(old.is_none());
+        assert_eq!(new.as_ref().unwrap()["full_name"], "Ana Souza");
+
+        let payload = EmployeePayload {
+            full_name: "Ana Souza".into(),
+            registration: "1001".into(),
+            cpf: None,
+            department_id: None,
+            admission_date: "2015-01-05".into(),
+            termination_date: None,
+            status: None,
+            drawer_position_id: None,
+            notes: Some("pasta revisada".into()),
+        };
+        db.update_employee(ana.id, &payload, &admin).await.unwrap();
+
+        let entries = audit_entries(&db, "employee", ana.id).await;
+        assert_eq!(entries.len(), 2);
+        let (action, _, old, new) = &entries[1];
+        assert_eq!(action, "UPDATE");
+        // Só os campos alterados entram no diff
+        assert_eq!(old.as_ref().unwrap(), &serde_json::json!({ "notes": null }));
+        assert_eq!(
+            new.as_ref().unwrap(),
+            &serde_json::json!({ "notes": "pasta revisada" })
+        );
+    }
+
+    #[tokio::test]
+    async fn category_delete_keeps_the_removed_row() {
+        let (db, admin) = test_db().await;
+        let payload = crate::types::DocumentCategoryPayload {
+            name: "Treinamentos".into(),
+            code: "TREINO".into(),
+            description: None,
+            icon: None,
+            color: None,
+            is_active: None,
+        };
+        let category = db.create_document_category(&payload, &admin).await.unwrap();
+        db.delete_document_category(category.id, &admin)
+            .await
+            .unwrap();
+
+        let entries = audit_entries(&db, "document_category", category.id).await;
+        let actions: Vec<&str> = entries.iter().map(|entry| entry.0.as_str()).collect();
+        assert_eq!(actions, ["CREATE", "DELETE"]);
+        let (_, _, old, new) = &entries[1];
+        assert_eq!(old.as_ref().unwrap()["code"], "TREINO");
+        assert!(new.is_none());
+    }
+
+    #[tokio::test]
+    async fn rolled_back_termination_leaves_no_audit_rows() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2024-01-10")).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        let box_id = archive_box(&db, &admin, 1).await;
+        db.transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+
+        // O TERMINATE é gravado antes de a caixa cheia ser recusada
+        let before = audit_count(&db).await;
+        let payload = TerminationPayload {
+            token: String::new(),
+            employee_id: bia.id,
+            termination_date: "2024-03-15".into(),
+            reason: None,
+            transfer_to_box_id: Some(box_id),
+        };
+        db.terminate_employee(&payload, &admin).await.unwrap_err();
+
+        assert_eq!(audit_count(&db).await, before);
+        let actions: Vec<String> = audit_entries(&db, "employee", bia.id)
+            .await
+            .into_iter()
+            .map(|entry| entry.0)
+            .collect();
+        assert_eq!(actions, ["CREATE"]);
+    }
+
+    async fn category(db: &ArchiveDatabase, actor: &UserProfile, code: &str) -> i64 {
+        let payload = crate::types::DocumentCategoryPayload {
+            name: format!("Categoria {}", code),
+            code: code.into(),
+            description: None,
+            icon: None,
+            color: None,
+            is_active: None,
+        };
+        db.create_document_category(&payload, actor)
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn document_type(
+        db: &ArchiveDatabase,
+        actor: &UserProfile,
+        category_id: i64,
+        name: &str,
+        retention_years: i64,
+        is_required: bool,
+    ) -> i64 {
+        let payload = crate::types::DocumentTypePayload {
+            category_id,
+            name: name.into(),
+            retention_years,
+            is_required,
+            is_active: None,
+        };
+        db.create_document_type(&payload, actor).await.unwrap().id
+    }
+
+    async fn document(
+        db: &ArchiveDatabase,
+        actor: &UserProfile,
+        employee_id: i64,
+        (category_id, type_id): (i64, i64),
+        document_date: Option<&str>,
+        expiration_date: Option<&str>,
+    ) -> crate::types::DocumentRecord {
+        let payload = crate::types::DocumentPayload {
+            token: String::new(),
+            employee_id,
+            category_id,
+            type_id,
+            description: None,
+            document_date: document_date.map(Into::into),
+            expiration_date: expiration_date.map(Into::into),
+            notes: None,
+            filed_by: None,
+        };
+        db.create_document(&payload, actor).await.unwrap()
+    }
+
+    async fn retention(
+        db: &ArchiveDatabase,
+        employee_id: i64,
+        termination_date: &str,
+    ) -> Retention {
+        let mut conn = db.pool.acquire().await.unwrap();
+        disposal_eligible_date(&mut conn, employee_id, termination_date)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn disposal_date_defaults_to_five_years() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+
+        let result = retention(&db, ana.id, "2020-03-15").await;
+        assert_eq!(result.eligible_date, "2025-03-15");
+        assert_eq!(result.rule, "5 anos após o desligamento (prazo padrão)");
+    }
+
+    #[tokio::test]
+    async fn disposal_date_uses_longest_retention() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "GUARDA").await;
+        let short = document_type(&db, &admin, category_id, "Atestado", 10, false).await;
+        let long = document_type(&db, &admin, category_id, "Ficha FGTS", 30, false).await;
+        document(&db, &admin, ana.id, (category_id, short), None, None).await;
+        document(&db, &admin, ana.id, (category_id, long), None, None).await;
+
+        let result = retention(&db, ana.id, "2020-03-15").await;
+        assert_eq!(result.eligible_date, "2050-03-15");
+        assert_eq!(result.rule, "30 anos após o desligamento (Ficha FGTS)");
+    }
+
+    #[tokio::test]
+    async fn disposal_date_with_zero_retention_is_the_termination_date() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "GUARDA").await;
+        let none = document_type(&db, &admin, category_id, "Recado", 0, false).await;
+        document(&db, &admin, ana.id, (category_id, none), None, None).await;
+
+        let result = retention(&db, ana.id, "2020-03-15").await;
+        assert_eq!(result.eligible_date, "2020-03-15");
+    }
+
+    #[tokio::test]
+    async fn disposal_date_from_leap_day_falls_on_february_28() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+
+        let result = retention(&db, ana.id, "2020-02-29").await;
+        assert_eq!(result.eligible_date, "2025-02-28");
+    }
+
+    #[tokio::test]
+    async fn legacy_termination_date_does_not_block_documents() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", Some("2020-03-15")).await;
+        let box_id = archive_box(&db, &admin, 5).await;
+        let item = db
+            .transfer_to_archive(ana.id, box_id, &admin)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE employees SET termination_date = '15/03/2020' WHERE id = ?")
+            .bind(ana.id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let category_id = category(&db, &admin, "GUARDA").await;
+        let long = document_type(&db, &admin, category_id, "Ficha FGTS", 30, false).await;
+        document(&db, &admin, ana.id, (category_id, long), None, None).await;
+
+        let after = fetch_archive_item(&db.pool, item.id).await.unwrap();
+        assert_eq!(after.disposal_eligible_date, item.disposal_eligible_date);
+    }
+
+    #[tokio::test]
+    async fn archive_boxes_page_with_totals_and_offset() {
+        let (db, admin) = test_db().await;
+        for (number, capacity) in [
+            ("CX-05", 2),
+            ("CX-01", 2),
+            ("CX-04", 2),
+            ("CX-02", 2),
+            ("CX-03", 2),
+        ] {
+            let payload = ArchiveBoxPayload {
+                box_number: number.into(),
+                year: 2024,
+                period: None,
+                letter_range: None,
+                location: None,
+                capacity: Some(capacity),
+            };
+            db.create_archive_box(&payload, &admin).await.unwrap();
+        }
+
+        let paging = crate::types::PageRequest {
+            page: Some(2),
+            page_size: Some(2),
+            sort_by: Some("box_number".into()),
+            sort_dir: Some("desc".into()),
+        };
+        let filter = crate::types::ArchiveBoxFilter::default();
+        let page = db.list_archive_boxes(&filter, &paging).await.unwrap();
+        assert_eq!((page.total, page.page, page.total_pages), (5, 2, 3));
+        let numbers: Vec<&str> = page.items.iter().map(|b| b.box_number.as_str()).collect();
+        assert_eq!(numbers, ["CX-03", "CX-02"]);
+
+        let empty = crate::types::ArchiveBoxFilter {
+            fullness: Some("empty".into()),
+            ..Default::default()
+        };
+        let page = db
+            .list_archive_boxes(&empty, &crate::types::PageRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(page.total, 5);
+    }
+
+    #[tokio::test]
+    async fn listings_reject_sort_columns_outside_the_whitelist() {
+        let (db, _) = test_db().await;
+        let paging = crate::types::PageRequest {
+            sort_by: Some("box_number; DROP TABLE users".into()),
+            ..Default::default()
+        };
+        let error = db
+            .list_archive_boxes(&Default::default(), &paging)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Campo de ordenação inválido"));
+        let error = db
+            .list_loans(&Default::default(), &paging)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Campo de ordenação inválido"));
+    }
+
+    #[tokio::test]
+    async fn employee_documents_are_paged() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "GUARDA").await;
+        let type_id = document_type(&db, &admin, category_id, "Atestado", 5, false).await;
+        for date in ["2024-01-10", "2024-02-10", "2024-03-10"] {
+            document(
+                &db,
+                &admin,
+                ana.id,
+                (category_id, type_id),
+                Some(date),
+                None,
+            )
+            .await;
+        }
+
+        let paging = crate::types::PageRequest {
+            page: Some(2),
+            page_size: Some(2),
+            sort_by: Some("document_date".into()),
+            sort_dir: Some("asc".into()),
+        };
+        let page = db.get_employee_documents(ana.id, &paging).await.unwrap();
+        assert_eq!((page.total, page.total_pages), (3, 2));
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].document_date.as_deref(), Some("2024-03-10"));
+    }
+
+    #[tokio::test]
+    async fn employees_filter_by_position_and_open_loan_and_page() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1003", None).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1001", None).await;
+        employee(&db, &admin, "Caio Reis", "1002", None).await;
+        let drawer_id = drawer(&db, &admin).await;
+        db.assign_employee_position(ana.id, drawer_id, 1, &admin)
+            .await
+            .unwrap();
+        db.assign_employee_position(bia.id, drawer_id, 2, &admin)
+            .await
+            .unwrap();
+        loan(&db, &admin, bia.id, "Setor Jurídico").await.unwrap();
+
+        let names = |page: crate::types::Page<crate::types::EmployeeRecord>| {
+            page.items
+                .into_iter()
+                .map(|e| e.full_name)
+                .collect::<Vec<_>>()
+        };
+        let all = crate::types::PageRequest::default();
+        let unplaced = crate::types::EmployeeFilter {
+            has_position: Some(false),
+            ..Default::default()
+        };
+        let page = db.list_employees(&unplaced, &all).await.unwrap();
+        assert_eq!(names(page), ["Caio Reis"]);
+
+        let borrowed = crate::types::EmployeeFilter {
+            has_open_loan: Some(true),
+            drawer_id: Some(drawer_id),
+            ..Default::default()
+        };
+        let page = db.list_employees(&borrowed, &all).await.unwrap();
+        assert_eq!(names(page), ["Bia Lima"]);
+
+        let paging = crate::types::PageRequest {
+            page: Some(1),
+            page_size: Some(2),
+            sort_by: Some("registration".into()),
+            sort_dir: Some("desc".into()),
+        };
+        let page = db
+            .list_employees(&Default::default(), &paging)
+            .await
+            .unwrap();
+        assert_eq!((page.total, page.total_pages), (3, 2));
+        assert_eq!(names(page), ["Ana Souza", "Caio Reis"]);
+
+        let paging = crate::types::PageRequest {
+            sort_by: Some("password_hash".into()),
+            ..Default::default()
+        };
+        let error = db
+            .list_employees(&Default::default(), &paging)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Campo de ordenação inválido"));
+    }
+
+    #[tokio::test]
+    async fn batch_labels_follow_physical_order() {
+        let (db, admin) = test_db().await;
+        let payload = FileCabinetPayload {
+            number: "B2".into(),
+            location: None,
+            num_drawers: Some(2),
+            description: None,
+            is_active: None,
+        };
+        let cabinet = db.create_file_cabinet(&payload, &admin).await.unwrap();
+        let drawers: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM drawers WHERE file_cabinet_id = ? ORDER BY number")
+                .bind(cabinet.id)
+                .fetch_all(&db.pool)
+                .await
+                .unwrap();
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        let caio = employee(&db, &admin, "Caio Reis", "1003", None).await;
+        for (id, drawer_id, position) in [
+            (ana.id, drawers[1], 1),
+            (bia.id, drawers[0], 3),
+            (caio.id, drawers[0], 1),
+        ] {
+            db.assign_employee_position(id, drawer_id, position, &admin)
+                .await
+                .unwrap();
+        }
+
+        let titles = |labels: Vec<crate::types::LabelData>| {
+            labels.into_iter().map(|l| l.title).collect::<Vec<_>>()
+        };
+        let labels = db
+            .generate_folder_labels("drawer", drawers[0])
+            .await
+            .unwrap();
+        assert_eq!(titles(labels), ["Caio Reis", "Bia Lima"]);
+        let labels = db
+            .generate_folder_labels("cabinet", cabinet.id)
+            .await
+            .unwrap();
+        assert_eq!(titles(labels), ["Caio Reis", "Bia Lima", "Ana Souza"]);
+
+        let box_id = archive_box(&db, &admin, 5).await;
+        for (name, registration) in [("Zeca Alves", "2001"), ("Beto Dias", "2002")] {
+            let folder = employee(&db, &admin, name, registration, Some("2020-03-15")).await;
+            db.transfer_to_archive(folder.id, box_id, &admin)
+                .await
+                .unwrap();
+        }
+        let labels = db.generate_folder_labels("box", box_id).await.unwrap();
+        assert_eq!(titles(labels), ["Beto Dias", "Zeca Alves"]);
+
+        let empty = drawer(&db, &admin).await;
+        let error = db
+            .generate_folder_labels("drawer", empty)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Nenhuma pasta guardada"));
+        let error = db.generate_folder_labels("box", 999).await.unwrap_err();
+        assert!(error.to_string().contains("Caixa não encontrada"));
+    }
+
+    #[tokio::test]
+    async fn referenced_categories_and_types_cannot_be_deleted() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "SIGILO").await;
+        let used = document_type(
+            &db,
+            &admin,
+            category_id,
+            "Termo de Confidencialidade",
+            5,
+            true,
+        )
+        .await;
+        let spare = document_type(&db, &admin, category_id, "eSocial S-2220", 20, false).await;
+        document(&db, &admin, ana.id, (category_id, used), None, None).await;
+
+        let error = db.delete_document_type(used, &admin).await.unwrap_err();
+        assert!(error.to_string().contains("Tipo usado em 1 documento(s)"));
+        let error = db
+            .delete_document_category(category_id, &admin)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Categoria usada em 1 documento(s)"));
+
+        db.delete_document_type(spare, &admin).await.unwrap();
+        let empty = category(&db, &admin, "AVULSO").await;
+        document_type(&db, &admin, empty, "Recado", 0, false).await;
+        let error = db
+            .delete_document_category(empty, &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("possui 1 tipo(s)"));
+    }
+
+    #[tokio::test]
+    async fn deactivating_a_category_deactivates_its_types() {
+        let (db, admin) = test_db().await;
+        let category_id = category(&db, &admin, "SIGILO").await;
+        let first = document_type(
+            &db,
+            &admin,
+            category_id,
+            "Termo de Confidencialidade",
+            5,
+            true,
+        )
+        .await;
+        let second = document_type(&db, &admin, category_id, "eSocial S-2220", 20, false).await;
+        let other_category = category(&db, &admin, "AVULSO").await;
+        let other = document_type(&db, &admin, other_category, "Recado", 0, false).await;
+
+        db.deactivate_document_category(category_id, &admin)
+            .await
+            .unwrap();
+        for (id, active) in [(first, false), (second, false), (other, true)] {
+            let document_type = fetch_document_type(&db.pool, id).await.unwrap();
+            assert_eq!(document_type.is_active, active, "{}", document_type.name);
+        }
+        let entries = audit_entries(&db, "document_type", first).await;
+        let (action, _, _, new) = entries.last().unwrap();
+        assert_eq!(action, "UPDATE");
+        assert_eq!(new, &Some(serde_json::json!({ "is_active": false })));
+
+        let payload = crate::types::DocumentTypePayload {
+            category_id,
+            name: "Novo tipo".into(),
+            retention_years: 5,
+            is_required: false,
+            is_active: None,
+        };
+        let error = db.create_document_type(&payload, &admin).await.unwrap_err();
+        assert!(error.to_string().contains("está desativada"));
+    }
+
+    #[tokio::test]
+    async fn deleted_document_leaves_listings_and_reports() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "MEDICO").await;
+        let aso = document_type(&db, &admin, category_id, "ASO", 5, true).await;
+        let due = (Utc::now().date_naive() + chrono::Duration::days(10))
+            .format("%Y-%m-%d")
+            .to_string();
+        let payload = crate::types::DocumentPayload {
+            token: String::new(),
+            employee_id: ana.id,
+            category_id,
+            type_id: aso,
+            description: Some("Exame periódico".into()),
+            document_date: None,
+            expiration_date: Some(due),
+            notes: None,
+            filed_by: None,
+        };
+        let created = db.create_document(&payload, &admin).await.unwrap();
+
+        let document_hits = |hits: Vec<crate::types::SearchHit>| {
+            hits.iter()
+                .filter(|hit| hit.entity_type == "document")
+                .count()
+        };
+        assert_eq!(document_hits(db.search("periodico", 10).await.unwrap()), 1);
+        let expiring = db.list_expiring_documents(30, true, None).await.unwrap();
+        assert_eq!(expiring.expiring_count, 1);
+        let compliance = db.get_employee_compliance(ana.id).await.unwrap();
+        assert_eq!(compliance.compliant_count, 1);
+
+        let deleted = db
+            .delete_document(created.id, "Lançado em duplicidade", &admin)
+            .await
+            .unwrap();
+        assert!(deleted.deleted_at.is_some());
+        let error = db
+            .delete_document(created.id, "Lançado em duplicidade", &admin)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("já foi excluído"));
+
+        let page = db
+            .get_employee_documents(ana.id, &crate::types::PageRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(page.total, 0);
+        assert_eq!(document_hits(db.search("periodico", 10).await.unwrap()), 0);
+        let expiring = db.list_expiring_documents(30, true, None).await.unwrap();
+        assert_eq!(expiring.expiring_count, 0);
+        let compliance = db.get_employee_compliance(ana.id).await.unwrap();
+        assert_eq!(
+            (compliance.compliant_count, compliance.missing.len()),
+            (0, 1)
+        );
+
+        let entries = audit_entries(&db, "document", created.id).await;
+        let (action, _, old, new) = entries.last().unwrap();
+        assert_eq!(action, "DELETE");
+        let (old, new) = (old.as_ref().unwrap(), new.as_ref().unwrap());
+        assert_eq!(old["deleted_at"], Value::Null);
+        assert_eq!(new["deleted_at"], serde_json::json!(deleted.deleted_at));
+        assert_eq!(new["deletion_reason"], "Lançado em duplicidade");
+    }
+
+    #[tokio::test]
+    async fn document_update_rejects_type_from_another_category() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let medical = category(&db, &admin, "MEDICO").await;
+        let aso = document_type(&db, &admin, medical, "ASO", 5, false).await;
+        let training = category(&db, &admin, "CURSOS").await;
+        let nr35 = document_type(&db, &admin, training, "NR-35", 5, false).await;
+        let created = document(&db, &admin, ana.id, (medical, aso), None, None).await;
+
+        let payload = crate::types::DocumentUpdatePayload {
+            token: String::new(),
+            id: created.id,
+            category_id: medical,
+            type_id: nr35,
+            description: None,
+            document_date: None,
+            expiration_date: None,
+            notes: None,
+        };
+        let error = db.update_document(&payload, &admin).await.unwrap_err();
+        assert!(error.to_string().contains("não pertence à categoria"));
+        let after = fetch_document(&db.pool, created.id).await.unwrap();
+        assert_eq!(after.type_id, aso);
+    }
+
+    async fn department(db: &ArchiveDatabase, actor: &UserProfile, name: &str) -> i64 {
+        let payload = crate::types::DepartmentPayload {
+            name: name.into(),
+            code: None,
+            description: None,
+            is_active: None,
+        };
+        db.create_department(&payload, actor).await.unwrap().id
+    }
+
+    async fn move_to_department(db: &ArchiveDatabase, employee_id: i64, department_id: i64) {
+        sqlx::query("UPDATE employees SET department_id = ? WHERE id = ?")
+            .bind(department_id)
+            .bind(employee_id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    fn days_from_today(days: i64) -> String {
+        (Utc::now().date_naive() + chrono::Duration::days(days))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn expiring_documents_group_by_department_and_skip_terminated() {
+        let (db, admin) = test_db().await;
+        let sales = department(&db, &admin, "Vendas").await;
+        let purchasing = department(&db, &admin, "Compras").await;
+        let category_id = category(&db, &admin, "MEDICO").await;
+        let aso = document_type(&db, &admin, category_id, "ASO", 5, false).await;
+        let kinds = (category_id, aso);
+
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let caio = employee(&db, &admin, "Caio Reis", "1003", None).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        let dani = employee(&db, &admin, "Dani Melo", "1004", None).await;
+        let edu = employee(&db, &admin, "Edu Prado", "1005", None).await;
+        move_to_department(&db, ana.id, sales).await;
+        for id in [bia.id, caio.id, edu.id] {
+            move_to_department(&db, id, purchasing).await;
+        }
+        for (id, days) in [
+            (ana.id, 5),
+            (bia.id, -3),
+            (caio.id, 20),
+            (dani.id, 10),
+            (edu.id, 5),
+        ] {
+            let due = days_from_today(days);
+            document(&db, &admin, id, kinds, None, Some(&due)).await;
+        }
+        // A renovação já arquivada tira o ASO de Ana da lista
+        let far = days_from_today(60);
+        document(&db, &admin, ana.id, kinds, Some("2020-01-01"), Some(&far)).await;
+        let termination = TerminationPayload {
+            token: String::new(),
+            employee_id: edu.id,
+            termination_date: "2024-06-30".into(),
+            reason: None,
+            transfer_to_box_id: None,
+        };
+        db.terminate_employee(&termination, &admin).await.unwrap();
+
+        let report = db.list_expiring_documents(30, true, None).await.unwrap();
+        assert_eq!((report.expired_count, report.expiring_count), (1, 2));
+        let groups: Vec<(Option<&str>, i64, i64, Vec<&str>)> = report
+            .departments
+            .iter()
+            .map(|group| {
+                (
+                    group.department_name.as_deref(),
+                    group.expired_count,
+                    group.expiring_count,
+                    group
+                        .employees
+                        .iter()
+                        .map(|e| e.full_name.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (Some("Compras"), 1, 1, vec!["Bia Lima", "Caio Reis"]),
+                (None, 0, 1, vec!["Dani Melo"]),
+            ]
+        );
+
+        let report = db.list_expiring_documents(30, false, None).await.unwrap();
+        assert_eq!((report.expired_count, report.expiring_count), (0, 2));
+        let report = db
+            .list_expiring_documents(30, true, Some(purchasing))
+            .await
+            .unwrap();
+        assert_eq!(report.departments.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn compliance_uses_latest_document_by_date() {
+        let (db, admin) = test_db().await;
+        let category_id = category(&db, &admin, "MEDICO").await;
+        let aso = document_type(&db, &admin, category_id, "ASO", 5, true).await;
+        let optional = document_type(&db, &admin, category_id, "Recado", 0, false).await;
+        let kinds = (category_id, aso);
+
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let bia = employee(&db, &admin, "Bia Lima", "1002", None).await;
+        employee(&db, &admin, "Caio Reis", "1003", None).await;
+        let dani = employee(&db, &admin, "Dani Melo", "1004", None).await;
+        let edu = employee(&db, &admin, "Edu Prado", "1005", None).await;
+
+        // O exame mais recente de Ana venceu, mesmo lançado antes de um antigo válido
+        let latest = document(
+            &db,
+            &admin,
+            ana.id,
+            kinds,
+            Some("2024-06-01"),
+            Some("2024-12-01"),
+        )
+        .await;
+        document(
+            &db,
+            &admin,
+            ana.id,
+            kinds,
+            Some("2023-01-01"),
+            Some("2099-12-31"),
+        )
+        .await;
+        // Sem data do documento, vale a de arquivamento (hoje)
+        document(
+            &db,
+            &admin,
+            bia.id,
+            kinds,
+            Some("2020-01-01"),
+            Some("2099-12-31"),
+        )
+        .await;
+        document(&db, &admin, bia.id, kinds, None, Some("2021-01-01")).await;
+        document(&db, &admin, dani.id, kinds, Some("2024-01-01"), None).await;
+        document(
+            &db,
+            &admin,
+            dani.id,
+            (category_id, optional),
+            None,
+            Some("2021-01-01"),
+        )
+        .await;
+        let termination = TerminationPayload {
+            token: String::new(),
+            employee_id: edu.id,
+            termination_date: "2024-06-30".into(),
+            reason: None,
+            transfer_to_box_id: None,
+        };
+        db.terminate_employee(&termination, &admin).await.unwrap();
+
+        let report = db.get_compliance_report(None, false).await.unwrap();
+        assert_eq!(report.required_types, 1);
+        assert_eq!((report.employees, report.compliant_employees), (4, 1));
+        assert_eq!(report.compliance_rate, 25.0);
+        let pending: Vec<(&str, usize, usize)> = report
+            .details
+            .iter()
+            .map(|e| (e.full_name.as_str(), e.missing.len(), e.expired.len()))
+            .collect();
+        assert_eq!(
+            pending,
+            [("Ana Souza", 0, 1), ("Bia Lima", 0, 1), ("Caio Reis", 1, 0)]
+        );
+        assert_eq!(report.details[0].expired[0].document_id, Some(latest.id));
+
+        // Consultado diretamente, o desligado continua com a própria situação
+        let compliance = db.get_employee_compliance(edu.id).await.unwrap();
+        assert_eq!(compliance.missing.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn interleaved_attach_and_remove_keep_shared_content() {
+        let (db, admin) = test_db().await;
+        let ana = employee(&db, &admin, "Ana Souza", "1001", None).await;
+        let category_id = category(&db, &admin, "MEDICO").await;
+        let aso = document_type(&db, &admin, category_id, "ASO", 5, false).await;
+        let mut documents = Vec::new();
+        for _ in 0..3 {
+            let created = document(&db, &admin, ana.id, (category_id, aso), None, None).await;
+            documents.push(created.id);
+        }
+        let root = std::env::temp_dir().join(format!("anexos-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let source = root.join("aso.pdf");
+        std::fs::write(&source, b"%PDF-1.4 conteudo").unwrap();
+        let store = AttachmentStore::new(root.join("store"));
+
+        let first = db
+            .attach_document_file(&store, documents[0], &source, &admin)
+            .await
+            .unwrap()
+            .file;
+        // O mesmo conteúdo sendo removido de um documento e anexado a outro
+        let (removed, attached) = tokio::join!(
+            db.remove_document_file(&store, first.id, &admin),
+            db.attach_document_file(&store, documents[1], &source, &admin),
+        );
+        removed.unwrap();
+        let second = attached.unwrap().file;
+        assert_eq!(store.verify(&second.sha256).unwrap(), BlobStatus::Intact);
+
+        let (attached, removed) = tokio::join!(
+            db.attach_document_file(&store, documents[2], &source, &admin),
+            db.remove_document_file(&store, second.id, &admin),
+        );
+        let third = attached.unwrap().file;
+        removed.unwrap();
+        assert_eq!(store.verify(&third.sha256).unwrap(), BlobStatus::Intact);
+
+        db.remove_document_file(&store, third.id, &admin)
+            .await
+            .unwrap();
+        assert_eq!(store.verify(&third.sha256).unwrap(), BlobStatus::Missing);
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use rust_xlsxwriter::{ColNum, Color, Format, RowNum, Workbook, Worksheet};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::types::{
+    ArchiveBoxRecord, AuditLogRecord, EmployeeRecord, LoanRecord, MovementRecord, OccupationMap,
+};
+
+/// Resolve o arquivo de destino. Sem caminho informado, grava em
+/// `<app_data>/exportacoes/<prefixo>-<data>.<extensão>`.
+pub fn export_path(
+    app: &AppHandle,
+    requested: Option<&str>,
+    prefix: &str,
+    extension: &str,
+) -> Result<PathBuf> {
+    let path = match requested.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(requested) => PathBuf::from(requested),
+        None => app
+            .path()
+            .app_data_dir()
+            .context("Não foi possível localizar a pasta de dados do aplicativo")?
+            .join("exportacoes")
+            .join(format!(
+                "{}-{}.{}",
+                prefix,
+                Local::now().format("%Y%m%d-%H%M%S"),
+                extension
+            )),
+    };
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+/// CSV separado por `;` com BOM, para abrir direto no Excel em português.
+pub fn write_audit_csv(path: &Path, logs: &[AuditLogRecord]) -> Result<()> {
+    let mut buffer = "\u{feff}".as_bytes().to_vec();
+    {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(&mut buffer);
+        writer.write_record([
+            "ID",
+            "Data",
+            "Usuário",
+            "Login",
+            "Ação",
+            "Entidade",
+            "ID da entidade",
+            "Valores anteriores",
+            "Valores novos",
+        ])?;
+        for log in logs {
+            writer.write_record([
+                log.id.to_string(),
+                log.created_at.clone(),
+                log.user_name
+                    .clone()
+                    .unwrap_or_else(|| "Sistema".to_string()),
+                log.user_login.clone().unwrap_or_default(),
+                log.action.clone(),
+                log.entity_type.clone(),
+                log.entity_id.map(|id| id.to_string()).unwrap_or_default(),
+                log.old_values
+                    .as_ref()
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                log.new_values
+                    .as_ref()
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+            ])?;
+        }
+        writer.flush()?;
+    }
+
+    fs::write(path, buffer)?;
+    Ok(())
+}
+
+/// Dados reunidos para o relatório geral em Excel.
+pub struct ExcelReport {
+    pub employees: Vec<EmployeeRecord>,
+    pub open_loans: Vec<LoanRecord>,
+    pub occupation: OccupationMap,
+    pub boxes: Vec<ArchiveBoxRecord>,
+    pub movements: Vec<MovementRecord>,
+}
+
+pub fn write_excel_report(path: &Path, report: &ExcelReport) -> Result<()> {
+    let header = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9E1F2));
+    let date = Format::new().set_num_format("dd/mm/yyyy");
+    let datetime = Format::new().set_num_format("dd/mm/yyyy hh:mm");
+    let percent = Format::new().set_num_format("0.0%");
+    let today = Local::now().date_naive();
+
+    let employee_names: HashMap<i64, &str> = report
+        .employees
+        .iter()
+        .map(|employee| (employee.id, employee.full_name.as_str()))
+        .collect();
+
+    let mut workbook = Workbook::new();
+
+    let sheet = workbook.add_worksheet().set_name("Funcionários")?;
+    write_header(
+        sheet,
+        &header,
+        &[
+            "Matrícula",
+            "Nome",
+            "CPF",
+            "Departamento",
+            "Admissão",
+            "Desligamento",
+            "Situação",
+            "Observações",
+        ],
+    )?;
+    for (index, employee) in report.employees.iter().enumerate() {
+        let row = index as RowNum + 1;
+        sheet.write_string(row, 0, &employee.registration)?;
+        sheet.write_string(row, 1, &employee.full_name)?;
+        sheet.write_string(row, 2, employee.cpf.as_deref().unwrap_or_default())?;
+        sheet.write_string(
+            row,
+            3,
+            employee.department_name.as_deref().unwrap_or_default(),
+        )?;
+        write_date(sheet, row, 4, Some(&employee.admission_date), &date)?;
+        write_date(sheet, row, 5, employee.termination_date.as_deref(), &date)?;
+        sheet.write_string(row, 6, employee_status_label(&employee.status))?;
+        sheet.write_string(row, 7, employee.notes.as_deref().unwrap_or_default())?;
+    }
+    finish_sheet(sheet, report.employees.len(), 7)?;
+
+    let sheet = workbook.add_worksheet().set_name("Empréstimos")?;
+    write_header(
+        sheet,
+        &header,
+        &[
+            "Funcionário",
+            "Solicitante",
+            "Motivo",
+            "Data do empréstimo",
+            "Devolução prevista",
+            "Situação",
+            "Emprestado por",
+        ],
+    )?;
+    for (index, loan) in report.open_loans.iter().enumerate() {
+        let row = index as RowNum + 1;
+        let overdue = parse_date(&loan.expected_return_date).is_some_and(|due| due < today);
+        sheet.write_string(
+            row,
+            0,
+            employee_names
+                .get(&loan.employee_id)
+                .copied()
+                .unwrap_or_default(),
+        )?;
+        sheet.write_string(row, 1, &loan.requester_name)?;
+        sheet.write_string(row, 2, &loan.reason)?;
+        write_date(sheet, row, 3, Some(&loan.loan_date), &date)?;
+        write_date(sheet, row, 4, Some(&loan.expected_return_date), &date)?;
+        sheet.write_string(row, 5, if overdue { "Em atraso" } else { "Em aberto" })?;
+        sheet.write_string(row, 6, &loan.loaned_by)?;
+    }
+    finish_sheet(sheet, report.open_loans.len(), 6)?;
+
+    let sheet = workbook.add_worksheet().set_name("Ocupação")?;
+    write_header(
+        sheet,
+        &header,
+        &[
+            "Arquivo",
+            "Gaveta",
+            "Ocupadas",
+            "Capacidade",
+            "Ocupação",
+            "Situação",
+        ],
+    )?;
+    let mut row: RowNum = 0;
+    for cabinet in &report.occupation.cabinets {
+        for drawer in &cabinet.drawers {
+            row += 1;
+            sheet.write_string(row, 0, &cabinet.cabinet_label)?;
+            sheet.write_number(row, 1, drawer.drawer.number as f64)?;
+            sheet.write_number(row, 2, drawer.occupied as f64)?;
+            sheet.write_number(row, 3, drawer.capacity as f64)?;
+            sheet.write_number_with_format(
+                row,
+                4,
+                f64::from(drawer.occupancy_rate) / 100.0,
+                &percent,
+            )?;
+            sheet.write_string(
+                row,
+                5,
+                if drawer.critical {
+                    "Crítica"
+                } else {
+                    "Normal"
+                },
+            )?;
+        }
+    }
+    finish_sheet(sheet, row as usize, 5)?;
+
+    let sheet = workbook.add_worksheet().set_name("Caixas")?;
+    write_header(
+        sheet,
+        &header,
+        &[
+            "Caixa",
+            "Ano",
+            "Período",
+            "Faixa de letras",
+            "Local",
+            "Pastas",
+            "Capacidade",
+            "Criada em",
+        ],
+    )?;
+    for (index, archive_box) in report.boxes.iter().enumerate() {
+        let row = index as RowNum + 1;
+        sheet.write_string(row, 0, &archive_box.box_number)?;
+        sheet.write_number(row, 1, archive_box.year as f64)?;
+        sheet.write_string(row, 2, archive_box.period.as_deref().unwrap_or_default())?;
+        sheet.write_string(
+            row,
+            3,
+            archive_box.letter_range.as_deref().unwrap_or_default(),
+        )?;
+        sheet.write_string(row, 4, archive_box.location.as_deref().unwrap_or_default())?;
+        sheet.write_number(row, 5, archive_box.current_count as f64)?;
+        sheet.write_number(row, 6, archive_box.capacity as f64)?;
+        write_date(sheet, row, 7, Some(&archive_box.created_at), &date)?;
+    }
+    finish_sheet(sheet, report.boxes.len(), 7)?;
+
+    let sheet = workbook.add_worksheet().set_name("Movimentações")?;
+    write_header(
+        sheet,
+        &header,
+        &[
+            "Data",
+            "Ação",
+            "Referência",
+            "Item",
+            "Origem",
+            "Destino",
+            "Responsável",
+            "Observação",
+        ],
+    )?;
+    for (index, movement) in report.movements.iter().enumerate() {
+        let row = index as RowNum + 1;
+        match DateTime::parse_from_rfc3339(&movement.created_at) {
+            Ok(moment) => {
+                sheet.write_datetime_with_format(
+                    row,
+                    0,
+                    moment.with_timezone(&Local).naive_local(),
+                    &datetime,
+                )?;
+            }
+            Err(_) => write_date(sheet, row, 0, Some(&movement.created_at), &date)?,
+        }
+        sheet.write_string(row, 1, &movement.action)?;
+        sheet.write_string(row, 2, movement.reference.as_deref().unwrap_or_default())?;
+        sheet.write_string(row, 3, movement.item_label.as_deref().unwrap_or_default())?;
+        sheet.write_string(row, 4, movement.from_unit.as_deref().unwrap_or_default())?;
+        sheet.write_string(row, 5, movement.to_unit.as_deref().unwrap_or_default())?;
+        sheet.write_string(row, 6, &movement.actor)?;
+        sheet.write_string(row, 7, movement.note.as_deref().unwrap_or_default())?;
+    }
+    finish_sheet(sheet, report.movements.len(), 7)?;
+
+    workbook.save(path)?;
+    Ok(())
+}
+
+fn write_header(sheet: &mut Worksheet, format: &Format, titles: &[&str]) -> Result<()> {
+    for (col, title) in titles.iter().enumerate() {
+        sheet.write_string_with_format(0, col as ColNum, *title, format)?;
+    }
+    Ok(())
+}
+
+fn finish_sheet(sheet: &mut Worksheet, rows: usize, last_col: ColNum) -> Result<()> {
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofilter(0, 0, rows as RowNum, last_col)?;
+    sheet.autofit();
+    Ok(())
+}
+
+/// Aceita `AAAA-MM-DD` e timestamps RFC 3339; considera apenas a parte da data.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    value
+        .get(..10)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+}
+
+/// Grava a data como data do Excel (dd/mm/aaaa); valores fora do padrão vão como texto.
+fn write_date(
+    sheet: &mut Worksheet,
+    row: RowNum,
+    col: ColNum,
+    value: Option<&str>,
+    format: &Format,
+) -> Result<()> {
+    match value.map(|raw| (raw, parse_date(raw))) {
+        Some((_, Some(date))) => {
+            sheet.write_datetime_with_format(row, col, date, format)?;
+        }
+        Some((raw, None)) => {
+            sheet.write_string(row, col, raw)?;
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+fn employee_status_label(status: &str) -> &str {
+    match status {
+        "ACTIVE" => "Ativo",
+        "TERMINATED" => "Desligado",
+        "ON_LEAVE" => "Afastado",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::types::OccupationTotals;
+
+    fn part(path: &Path, name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+        let mut xml = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        xml
+    }
+
+    #[test]
+    fn excel_report_has_portuguese_sheets_and_dates() {
+        let employee = EmployeeRecord {
+            id: 1,
+            full_name: "Ana Souza".into(),
+            registration: "1001".into(),
+            cpf: None,
+            department_id: None,
+            department_name: None,
+            admission_date: "2015-01-05".into(),
+            termination_date: None,
+            termination_reason: None,
+            status: "ACTIVE".into(),
+            drawer_position_id: None,
+            notes: None,
+            created_at: "2024-01-01T10:00:00Z".into(),
+            updated_at: "2024-01-01T10:00:00Z".into(),
+        };
+        let report = ExcelReport {
+            employees: vec![employee],
+            open_loans: Vec::new(),
+            occupation: OccupationMap {
+                cabinets: Vec::new(),
+                totals: OccupationTotals {
+                    total_positions: 0,
+                    occupied_positions: 0,
+                    warnings: 0,
+                    critical: 0,
+                },
+            },
+            boxes: Vec::new(),
+            movements: Vec::new(),
+        };
+        let path = std::env::temp_dir().join(format!("relatorio-{}.xlsx", uuid::Uuid::new_v4()));
+        write_excel_report(&path, &report).unwrap();
+
+        let workbook = part(&path, "xl/workbook.xml");
+        let sheets: Vec<&str> = workbook
+            .split("<sheet name=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect();
+        assert_eq!(
+            sheets,
+            [
+                "Funcionários",
+                "Empréstimos",
+                "Ocupação",
+                "Caixas",
+                "Movimentações"
+            ]
+        );
+
+        let strings = part(&path, "xl/sharedStrings.xml");
+        for title in [
+            "Matrícula",
+            "Admissão",
+            "Devolução prevista",
+            "Faixa de letras",
+        ] {
+            assert!(strings.contains(&format!("<t>{}</t>", title)), "{}", title);
+        }
+        assert!(part(&path, "xl/styles.xml").contains("formatCode=\"dd/mm/yyyy\""));
+        // 05/01/2015 vai como data do Excel, não como texto
+        assert!(part(&path, "xl/worksheets/sheet1.xml").contains("<v>42009</v>"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+
+use crate::types::EmployeePayload;
+
+/// Linha do CSV convertida para `EmployeePayload`, com os erros de conversão encontrados.
+pub struct EmployeeImportRow {
+    pub line: usize,
+    pub department: Option<String>,
+    pub payload: EmployeePayload,
+    pub errors: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Registration,
+    FullName,
+    Cpf,
+    Department,
+    AdmissionDate,
+    TerminationDate,
+    Status,
+    Notes,
+}
+
+/// Cabeçalhos aceitos (já normalizados), no formato exportado pela folha de pagamento.
+const HEADER_ALIASES: &[(&str, Column)] = &[
+    ("matricula", Column::Registration),
+    ("registro", Column::Registration),
+    ("chapa", Column::Registration),
+    ("nome", Column::FullName),
+    ("nome completo", Column::FullName),
+    ("funcionario", Column::FullName),
+    ("colaborador", Column::FullName),
+    ("cpf", Column::Cpf),
+    ("departamento", Column::Department),
+    ("setor", Column::Department),
+    ("lotacao", Column::Department),
+    ("admissao", Column::AdmissionDate),
+    ("data admissao", Column::AdmissionDate),
+    ("data de admissao", Column::AdmissionDate),
+    ("desligamento", Column::TerminationDate),
+    ("data desligamento", Column::TerminationDate),
+    ("data de desligamento", Column::TerminationDate),
+    ("demissao", Column::TerminationDate),
+    ("data de demissao", Column::TerminationDate),
+    ("situacao", Column::Status),
+    ("status", Column::Status),
+    ("observacoes", Column::Notes),
+    ("observacao", Column::Notes),
+    ("obs", Column::Notes),
+];
+
+/// Lê o arquivo como UTF-8 (com ou sem BOM) e, se não for válido, como
+/// Windows-1252, a codificação das exportações da folha feitas no Windows.
+pub fn decode_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1252
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned(),
+    }
+}
+
+/// Converte o CSV separado por `;` em linhas de importação. Erros de estrutura
+/// (cabeçalho ausente, arquivo ilegível) abortam; erros de conteúdo ficam na linha.
+pub fn parse_employee_csv(bytes: &[u8]) -> Result<Vec<EmployeeImportRow>> {
+    let text = decode_text(bytes);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    let columns: Vec<Option<Column>> = reader
+        .headers()?
+        .iter()
+        .map(|header| {
+            let normalized = normalize_header(header);
+            HEADER_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == normalized)
+                .map(|(_, column)| *column)
+        })
+        .collect();
+
+    for (column, label) in [
+        (Column::Registration, "Matrícula"),
+        (Column::FullName, "Nome"),
+        (Column::AdmissionDate, "Admissão"),
+    ] {
+        if !columns.contains(&Some(column)) {
+            bail!("Coluna obrigatória ausente no arquivo: {}", label);
+        }
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        // O cabeçalho é a linha 1; a contagem de linhas do leitor se perde com CRLF
+        let line = record
+            .position()
+            .map(|p| p.record() as usize + 1)
+            .unwrap_or(0);
+        if record.iter().all(|value| value.is_empty()) {
+            continue;
+        }
+
+        let value = |wanted: Column| -> Option<String> {
+            columns
+                .iter()
+                .position(|column| *column == Some(wanted))
+                .and_then(|index| record.get(index))
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+
+        let mut errors = Vec::new();
+        let admission_date = match value(Column::AdmissionDate) {
+            Some(raw) => parse_date(&raw, "Admissão", &mut errors).unwrap_or(raw),
+            None => String::new(),
+        };
+        let termination_date = value(Column::TerminationDate)
+            .and_then(|raw| parse_date(&raw, "Desligamento", &mut errors));
+        let status = match value(Column::Status) {
+            Some(raw) => parse_status(&raw).or_else(|| {
+                errors.push(format!("Situação desconhecida: '{}'", raw));
+                None
+            }),
+            None if termination_date.is_some() => Some("TERMINATED".to_string()),
+            None => Some("ACTIVE".to_string()),
+        };
+
+        rows.push(EmployeeImportRow {
+            line,
+            department: value(Column::Department),
+            payload: EmployeePayload {
+                full_name: value(Column::FullName).unwrap_or_default(),
+                registration: value(Column::Registration).unwrap_or_default(),
+                cpf: value(Column::Cpf),
+                department_id: None,
+                admission_date,
+                termination_date,
+                status,
+                drawer_position_id: None,
+                notes: value(Column::Notes),
+            },
+            errors,
+        });
+    }
+    Ok(rows)
+}
+
+/// Minúsculas, sem acentos e com espaços simples: "Data de Admissão" vira "data de admissao".
+fn normalize_header(header: &str) -> String {
+    header
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Aceita dd/mm/aaaa (padrão da folha) ou AAAA-MM-DD e devolve no formato ISO.
+fn parse_date(raw: &str, field: &str, errors: &mut Vec<String>) -> Option<String> {
+    let parsed = NaiveDate::parse_from_str(raw, "%d/%m/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(raw, "%Y-%m-%d"));
+    match parsed {
+        Ok(date) => Some(date.format("%Y-%m-%d").to_string()),
+        Err(_) => {
+            errors.push(format!("Data de {} inválida: '{}'", field, raw));
+            None
+        }
+    }
+}
+
+fn parse_status(raw: &str) -> Option<String> {
+    let status = match normalize_header(raw).as_str() {
+        "ativo" | "active" => "ACTIVE",
+        "desligado" | "demitido" | "inativo" | "terminated" => "TERMINATED",
+        "afastado" | "on leave" => "ON_LEAVE",
+        _ => return None,
+    };
+    Some(status.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_windows_1252_when_not_utf8() {
+        assert_eq!(
+            decode_text(b"Admiss\xE3o;Situa\xE7\xE3o"),
+            "Admissão;Situação"
+        );
+        // 0x80–0x9F: travessão e aspas curvas do Word/Excel, que o Latin-1 não tem
+        assert_eq!(
+            decode_text(b"Jo\xE3o \x96 \x93obs\x94"),
+            "João – \u{201C}obs\u{201D}"
+        );
+        assert_eq!(decode_text("\u{FEFF}Admissão".as_bytes()), "Admissão");
+        let rows = parse_employee_csv(
+            b"Matr\xEDcula;Nome;Admiss\xE3o;Obs\n1001;Jo\xE3o Silva;05/01/2015;f\xE9rias \x96 retorno\n",
+        )
+        .unwrap();
+        assert_eq!(rows[0].payload.full_name, "João Silva");
+        assert_eq!(rows[0].payload.notes.as_deref(), Some("férias – retorno"));
+    }
+
+    #[test]
+    fn maps_header_aliases() {
+        let csv = "Chapa;Colaborador;Lotação;Data de Admissão;Data de Demissão;Obs\n\
+                   1001;Ana Souza;RH;05/01/2015;10/03/2024;pasta antiga\n";
+        let row = &parse_employee_csv(csv.as_bytes()).unwrap()[0];
+        assert_eq!(row.payload.registration, "1001");
+        assert_eq!(row.payload.full_name, "Ana Souza");
+        assert_eq!(row.department.as_deref(), Some("RH"));
+        assert_eq!(row.payload.notes.as_deref(), Some("pasta antiga"));
+        assert_eq!(row.payload.status.as_deref(), Some("TERMINATED"));
+    }
+
+    #[test]
+    fn converts_dates_to_iso() {
+        let csv = "matricula;nome;admissao;desligamento\n\
+                   1001;Ana Souza;05/01/2015;2024-03-10\n\
+                   1002;Bia Lima;31/02/2015;\n";
+        let rows = parse_employee_csv(csv.as_bytes()).unwrap();
+        assert_eq!(rows[0].payload.admission_date, "2015-01-05");
+        assert_eq!(
+            rows[0].payload.termination_date.as_deref(),
+            Some("2024-03-10")
+        );
+        assert!(rows[0].errors.is_empty());
+        assert_eq!(rows[1].payload.status.as_deref(), Some("ACTIVE"));
+        assert_eq!(rows[1].errors, ["Data de Admissão inválida: '31/02/2015'"]);
+    }
+
+    #[test]
+    fn numbers_lines_from_the_header_skipping_blank_ones() {
+        let csv = "matricula;nome;admissao\r\n\
+                   1001;Ana Souza;05/01/2015\r\n\
+                   ;;\r\n\
+                   1002;Bia Lima;06/01/2015\r\n";
+        let rows = parse_employee_csv(csv.as_bytes()).unwrap();
+        let lines: Vec<usize> = rows.iter().map(|row| row.line).collect();
+        assert_eq!(lines, [2, 4]);
+    }
+
+    #[test]
+    fn missing_required_column_fails() {
+        let error = parse_employee_csv(b"matricula;nome\n1001;Ana Souza\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Coluna obrigatória ausente no arquivo: Admissão"
+        );
+    }
+}
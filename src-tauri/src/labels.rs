@@ -0,0 +1,668 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use qrcode::{Color, QrCode};
+
+use crate::types::LabelData;
+
+/// Identificador impresso no código da pasta/envelope do funcionário.
+pub fn employee_code(employee_id: i64) -> String {
+    format!("EMP-{}", employee_id)
+}
+
+/// Identificador impresso no código da caixa do arquivo morto.
+pub fn box_code(box_number: &str) -> String {
+    format!("BOX-{}", box_number)
+}
+
+/// Conteúdo lido pelo leitor. Sem prefixo conhecido, o valor é tratado como matrícula.
+pub enum ScanCode {
+    Employee(i64),
+    Box(String),
+    Registration(String),
+}
+
+/// `None` quando o prefixo é conhecido, mas o restante não forma um código válido.
+pub fn parse_scan(raw: &str) -> Option<ScanCode> {
+    let raw = raw.trim();
+    let prefixed = |prefix: &str| {
+        raw.get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix))
+            .map(|_| raw[prefix.len()..].trim())
+    };
+
+    if let Some(id) = prefixed("EMP-") {
+        return id.parse().ok().map(ScanCode::Employee);
+    }
+    if let Some(number) = prefixed("BOX-") {
+        return Some(number)
+            .filter(|number| !number.is_empty())
+            .map(|number| ScanCode::Box(number.to_string()));
+    }
+    Some(ScanCode::Registration(raw.to_string()))
+}
+
+/// Código de barras impresso junto do texto da etiqueta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbology {
+    Qr,
+    Code128,
+    None,
+}
+
+impl Symbology {
+    pub fn from_name(name: Option<&str>) -> Self {
+        match name {
+            Some("code128") => Symbology::Code128,
+            Some("none") => Symbology::None,
+            _ => Symbology::Qr,
+        }
+    }
+}
+
+/// Folha de etiquetas A4, com medidas em mm tiradas das especificações Pimaco.
+pub struct SheetLayout {
+    pub code: &'static str,
+    pub columns: usize,
+    pub rows: usize,
+    pub label_width: f32,
+    pub label_height: f32,
+    pub margin_left: f32,
+    pub margin_top: f32,
+    /// Distância entre o início de duas colunas / linhas vizinhas.
+    pub pitch_x: f32,
+    pub pitch_y: f32,
+}
+
+impl SheetLayout {
+    fn per_sheet(&self) -> usize {
+        self.columns * self.rows
+    }
+}
+
+/// O primeiro modelo é o padrão.
+const LAYOUTS: &[SheetLayout] = &[
+    // 14 por folha, 38,1 x 99,0 mm: pastas e envelopes
+    SheetLayout {
+        code: "A4263",
+        columns: 2,
+        rows: 7,
+        label_width: 99.0,
+        label_height: 38.1,
+        margin_left: 4.7,
+        margin_top: 15.15,
+        pitch_x: 101.6,
+        pitch_y: 38.1,
+    },
+    // 21 por folha, 38,1 x 63,5 mm
+    SheetLayout {
+        code: "A4260",
+        columns: 3,
+        rows: 7,
+        label_width: 63.5,
+        label_height: 38.1,
+        margin_left: 7.25,
+        margin_top: 15.15,
+        pitch_x: 66.0,
+        pitch_y: 38.1,
+    },
+    // 8 por folha, 67,7 x 99,0 mm: lateral das caixas de arquivo morto
+    SheetLayout {
+        code: "A4265",
+        columns: 2,
+        rows: 4,
+        label_width: 99.0,
+        label_height: 67.7,
+        margin_left: 4.7,
+        margin_top: 13.1,
+        pitch_x: 101.6,
+        pitch_y: 67.7,
+    },
+    // 33 por folha, 25,4 x 63,5 mm
+    SheetLayout {
+        code: "A4256",
+        columns: 3,
+        rows: 11,
+        label_width: 63.5,
+        label_height: 25.4,
+        margin_left: 7.25,
+        margin_top: 8.8,
+        pitch_x: 66.0,
+        pitch_y: 25.4,
+    },
+];
+
+pub fn sheet_layout(code: &str) -> Option<&'static SheetLayout> {
+    LAYOUTS
+        .iter()
+        .find(|layout| layout.code.eq_ignore_ascii_case(code.trim()))
+}
+
+pub fn sheet_layout_or_default(code: Option<&str>) -> &'static SheetLayout {
+    code.and_then(sheet_layout).unwrap_or(&LAYOUTS[0])
+}
+
+pub fn layout_codes() -> Vec<&'static str> {
+    LAYOUTS.iter().map(|layout| layout.code).collect()
+}
+
+const A4_WIDTH: f32 = 210.0;
+const A4_HEIGHT: f32 = 297.0;
+const PADDING: f32 = 3.0;
+
+fn pt(mm: f32) -> f32 {
+    mm * 72.0 / 25.4
+}
+
+/// Grava as etiquetas em folhas A4 do modelo informado. `skip` pula as primeiras
+/// posições da primeira folha, já usadas em outra impressão.
+pub fn write_pdf(
+    path: &Path,
+    labels: &[LabelData],
+    layout: &SheetLayout,
+    skip: usize,
+    symbology: Symbology,
+) -> Result<()> {
+    let skip = skip % layout.per_sheet();
+    let sheets = (skip + labels.len()).div_ceil(layout.per_sheet()).max(1);
+
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let page_ids: Vec<Ref> = (0..sheets).map(|i| Ref::new(5 + 2 * i as i32)).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(sheets as i32);
+    for (id, base_font) in [(regular_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+        pdf.type1_font(id)
+            .base_font(Name(base_font.as_bytes()))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    let mut contents: Vec<Content> = (0..sheets).map(|_| Content::new()).collect();
+    for (index, label) in labels.iter().enumerate() {
+        let slot = skip + index;
+        let cell = slot % layout.per_sheet();
+        let left = layout.margin_left + (cell % layout.columns) as f32 * layout.pitch_x;
+        let top = layout.margin_top + (cell / layout.columns) as f32 * layout.pitch_y;
+        draw_label(
+            &mut contents[slot / layout.per_sheet()],
+            label,
+            layout,
+            symbology,
+            (left, top),
+        );
+    }
+
+    for (page_id, content) in page_ids.iter().zip(contents) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, pt(A4_WIDTH), pt(A4_HEIGHT)))
+            .parent(tree_id)
+            .contents(content_id);
+        page.resources()
+            .fonts()
+            .pair(Name(b"F1"), regular_id)
+            .pair(Name(b"F2"), bold_id);
+        page.finish();
+        pdf.stream(content_id, &content.finish());
+    }
+
+    fs::write(path, pdf.finish())?;
+    Ok(())
+}
+
+/// Título em negrito, subtítulo e um detalhe por linha; o que não couber na altura
+/// da etiqueta é omitido e textos largos demais terminam em reticências. O QR Code
+/// ocupa a lateral direita e o Code128, a faixa de baixo.
+fn draw_label(
+    content: &mut Content,
+    label: &LabelData,
+    layout: &SheetLayout,
+    symbology: Symbology,
+    (left, top): (f32, f32),
+) {
+    let scale = (layout.label_height / 38.1).min(1.0);
+    let inner_width = layout.label_width - 2.0 * PADDING;
+    let inner_height = layout.label_height - 2.0 * PADDING;
+    let inner_left = left + PADDING;
+    let inner_bottom = A4_HEIGHT - top - layout.label_height + PADDING;
+
+    let mut text_width_mm = inner_width;
+    let mut text_bottom = inner_bottom;
+    match symbology {
+        Symbology::Qr => {
+            let side = inner_height.min(inner_width * 0.4);
+            if draw_qr(
+                content,
+                &label.code,
+                inner_left + inner_width - side,
+                inner_bottom + inner_height - side,
+                side,
+            ) {
+                text_width_mm -= side + 2.0;
+            }
+        }
+        Symbology::Code128 => {
+            let bar_height = (inner_height * 0.3).min(10.0);
+            if draw_code128(
+                content,
+                &label.code,
+                inner_left,
+                inner_bottom,
+                inner_width,
+                bar_height,
+                6.0 * scale,
+            ) {
+                text_bottom += bar_height + 6.0 * scale * 25.4 / 72.0 + 1.5;
+            }
+        }
+        Symbology::None => {}
+    }
+
+    let x = pt(inner_left);
+    let max_width = pt(text_width_mm);
+    let bottom = pt(text_bottom);
+    let mut baseline = pt(A4_HEIGHT - top - PADDING);
+
+    let mut lines = vec![(true, 12.0 * scale, label.title.clone())];
+    if let Some(subtitle) = &label.subtitle {
+        lines.push((false, 9.0 * scale, subtitle.clone()));
+    }
+    for field in &label.details {
+        lines.push((
+            false,
+            8.0 * scale,
+            format!("{}: {}", field.label, field.value),
+        ));
+    }
+
+    for (bold, size, text) in lines {
+        baseline -= size;
+        if baseline < bottom {
+            break;
+        }
+        show_text(
+            content,
+            &fit_width(&text, size, max_width, bold),
+            x,
+            baseline,
+            size,
+            bold,
+        );
+        baseline -= size * 0.3;
+    }
+}
+
+fn show_text(content: &mut Content, text: &str, x: f32, baseline: f32, size: f32, bold: bool) {
+    content.begin_text();
+    content.set_font(Name(if bold { b"F2" } else { b"F1" }), size);
+    content.next_line(x, baseline);
+    content.show(Str(&win_ansi(text)));
+    content.end_text();
+}
+
+/// Desenha o QR Code no quadrado de lado `side` (mm) com canto inferior em (`x`, `y`).
+fn draw_qr(content: &mut Content, data: &str, x: f32, y: f32, side: f32) -> bool {
+    let Ok(code) = QrCode::new(data.as_bytes()) else {
+        return false;
+    };
+    let width = code.width();
+    let module = side / width as f32;
+    let colors = code.to_colors();
+    // Um retângulo por sequência de módulos escuros na linha, para o PDF não crescer demais
+    for (row, line) in colors.chunks(width).enumerate() {
+        let mut col = 0;
+        while col < width {
+            if line[col] != Color::Dark {
+                col += 1;
+                continue;
+            }
+            let run = line[col..]
+                .iter()
+                .take_while(|c| **c == Color::Dark)
+                .count();
+            content.rect(
+                pt(x + col as f32 * module),
+                pt(y + side - (row + 1) as f32 * module),
+                pt(run as f32 * module),
+                pt(module),
+            );
+            col += run;
+        }
+    }
+    content.fill_nonzero();
+    true
+}
+
+/// Desenha as barras (conjunto B) e o texto legível embaixo. Conteúdo fora do
+/// ASCII imprimível não tem representação no conjunto B e fica sem código.
+fn draw_code128(
+    content: &mut Content,
+    data: &str,
+    x: f32,
+    y: f32,
+    max_width: f32,
+    bar_height: f32,
+    text_size: f32,
+) -> bool {
+    let Some(modules) = code128_modules(data) else {
+        return false;
+    };
+    // Zona de silêncio de 10 módulos de cada lado
+    let module = (max_width / (modules.len() + 20) as f32).min(0.33);
+    let text_height = text_size * 25.4 / 72.0;
+    let bars_bottom = y + text_height + 0.5;
+    let mut cursor = x + 10.0 * module;
+    for dark in modules {
+        if dark {
+            content.rect(pt(cursor), pt(bars_bottom), pt(module), pt(bar_height));
+        }
+        cursor += module;
+    }
+    content.fill_nonzero();
+    show_text(
+        content,
+        data,
+        pt(x + 10.0 * module),
+        pt(y),
+        text_size,
+        false,
+    );
+    true
+}
+
+/// Padrões de barra/espaço do Code128 (valores 0 a 105), em larguras de módulo.
+const CODE128_PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const CODE128_START_B: usize = 104;
+const CODE128_STOP: &str = "2331112";
+
+/// Sequência de módulos (true = barra) do texto no conjunto B, com dígito verificador.
+fn code128_modules(data: &str) -> Option<Vec<bool>> {
+    let values = data
+        .chars()
+        .map(|c| match c as u32 {
+            code @ 32..=126 => Some(code as usize - 32),
+            _ => None,
+        })
+        .collect::<Option<Vec<usize>>>()?;
+    if values.is_empty() {
+        return None;
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .fold(CODE128_START_B, |sum, (index, value)| {
+            sum + (index + 1) * value
+        })
+        % 103;
+
+    let mut modules = Vec::new();
+    let symbols = std::iter::once(CODE128_START_B)
+        .chain(values)
+        .chain(std::iter::once(checksum))
+        .map(|value| CODE128_PATTERNS[value])
+        .chain(std::iter::once(CODE128_STOP));
+    for pattern in symbols {
+        for (index, width) in pattern.bytes().enumerate() {
+            let dark = index % 2 == 0;
+            modules.extend(std::iter::repeat_n(dark, usize::from(width - b'0')));
+        }
+    }
+    Some(modules)
+}
+
+fn fit_width(text: &str, size: f32, max_width: f32, bold: bool) -> String {
+    if text_width(text, size, bold) <= max_width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&format!("{}…", fitted), size, bold) > max_width {
+        fitted.pop();
+    }
+    format!("{}…", fitted.trim_end())
+}
+
+fn text_width(text: &str, size: f32, bold: bool) -> f32 {
+    let units: u32 = text.chars().map(|c| glyph_width(c, bold)).sum();
+    units as f32 * size / 1000.0
+}
+
+/// Larguras das fontes padrão do PDF (Helvetica AFM), em milésimos do corpo.
+/// Letras acentuadas usam a largura da letra base.
+fn glyph_width(c: char, bold: bool) -> u32 {
+    const REGULAR: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+    const BOLD: [u16; 95] = [
+        278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722,
+        722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611,
+        611, 278, 278, 556, 278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556,
+        500, 389, 280, 389, 584,
+    ];
+
+    let base = match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'Ç' => 'C',
+        '…' | '—' => return 1000,
+        other => other,
+    };
+    let table = if bold { &BOLD } else { &REGULAR };
+    match base as u32 {
+        code @ 32..=126 => u32::from(table[code as usize - 32]),
+        _ => 556,
+    }
+}
+
+/// As fontes padrão só aceitam WinAnsi: Latin-1 mais alguns sinais tipográficos.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '–' => 0x96,
+            '—' => 0x97,
+            c if (c as u32) < 0x80 || (0xA0..=0xFF).contains(&(c as u32)) => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Etiqueta térmica de 100 x 50 mm a 203 dpi (8 pontos por mm).
+const ZPL_WIDTH: u32 = 800;
+const ZPL_HEIGHT: u32 = 400;
+const ZPL_MARGIN: u32 = 24;
+
+/// Gera um bloco `^XA ... ^XZ` por etiqueta, em UTF-8 (`^CI28`). Os códigos usam
+/// os comandos nativos da impressora (`^BQ` e `^BC`).
+pub fn render_zpl(labels: &[LabelData], symbology: Symbology) -> String {
+    let mut zpl = String::new();
+    for label in labels {
+        zpl.push_str(&format!(
+            "^XA\n^CI28\n^PW{}\n^LL{}\n^LH0,0\n",
+            ZPL_WIDTH, ZPL_HEIGHT
+        ));
+
+        let mut text_right = ZPL_WIDTH - ZPL_MARGIN;
+        let mut text_bottom = ZPL_HEIGHT - ZPL_MARGIN;
+        match symbology {
+            Symbology::Qr => {
+                // Ampliação 7: ~175 pontos para os códigos curtos das etiquetas
+                zpl.push_str(&format!(
+                    "^FO{},{}^BQN,2,7^FH^FDMA,{}^FS\n",
+                    ZPL_WIDTH - ZPL_MARGIN - 200,
+                    ZPL_MARGIN,
+                    zpl_escape(&label.code)
+                ));
+                text_right -= 210;
+            }
+            Symbology::Code128 => {
+                zpl.push_str(&format!(
+                    "^FO{},{}^BY2^BCN,60,Y,N,N^FH^FD{}^FS\n",
+                    ZPL_MARGIN,
+                    ZPL_HEIGHT - ZPL_MARGIN - 90,
+                    zpl_escape(&label.code)
+                ));
+                text_bottom -= 100;
+            }
+            Symbology::None => {}
+        }
+
+        let mut lines = vec![(44, label.title.clone())];
+        if let Some(subtitle) = &label.subtitle {
+            lines.push((30, subtitle.clone()));
+        }
+        for field in &label.details {
+            lines.push((26, format!("{}: {}", field.label, field.value)));
+        }
+
+        let mut y = ZPL_MARGIN;
+        for (height, text) in lines {
+            if y + height > text_bottom {
+                break;
+            }
+            // A fonte 0 é proporcional; 0,6 da altura por caractere é uma estimativa folgada
+            let max_chars = ((text_right - ZPL_MARGIN) * 10 / (height * 6)) as usize;
+            zpl.push_str(&format!(
+                "^FO{},{}^A0N,{},{}^FH^FD{}^FS\n",
+                ZPL_MARGIN,
+                y,
+                height,
+                height,
+                zpl_escape(&truncate_chars(&text, max_chars))
+            ));
+            y += height + height / 3;
+        }
+        zpl.push_str("^XZ\n");
+    }
+    zpl
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Com `^FH`, `_` inicia um código hexadecimal; assim `^` e `~` no texto não viram comandos.
+fn zpl_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '^' => escaped.push_str("_5E"),
+            '~' => escaped.push_str("_7E"),
+            '_' => escaped.push_str("_5F"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code128_matches_reference_modules() {
+        // Start B, E, M, P, -, 4, 2, verificador 17 e parada (tabela ISO/IEC 15417)
+        let expected = [
+            "11010010000",
+            "10001101000",
+            "10111011000",
+            "11101110110",
+            "10011011100",
+            "11001001110",
+            "11001110010",
+            "10011100110",
+            "1100011101011",
+        ]
+        .concat();
+        let modules: String = code128_modules("EMP-42")
+            .unwrap()
+            .iter()
+            .map(|dark| if *dark { '1' } else { '0' })
+            .collect();
+        assert_eq!(modules, expected);
+        assert!(code128_modules("").is_none());
+        assert!(code128_modules("Ação").is_none());
+    }
+
+    #[test]
+    fn parse_scan_accepts_prefixes_in_any_case() {
+        assert!(matches!(parse_scan("EMP-42"), Some(ScanCode::Employee(42))));
+        assert!(matches!(
+            parse_scan(" emp- 42 "),
+            Some(ScanCode::Employee(42))
+        ));
+        assert!(matches!(
+            parse_scan("box-CX-01"),
+            Some(ScanCode::Box(number)) if number == "CX-01"
+        ));
+        assert!(matches!(
+            parse_scan("Box-7"),
+            Some(ScanCode::Box(number)) if number == "7"
+        ));
+    }
+
+    #[test]
+    fn parse_scan_rejects_malformed_codes() {
+        assert!(parse_scan("EMP-abc").is_none());
+        assert!(parse_scan("EMP-").is_none());
+        assert!(parse_scan("BOX-").is_none());
+    }
+
+    #[test]
+    fn parse_scan_falls_back_to_registration() {
+        assert!(matches!(
+            parse_scan(" 12.345-6 "),
+            Some(ScanCode::Registration(registration)) if registration == "12.345-6"
+        ));
+        assert!(matches!(
+            parse_scan("EMPRESA"),
+            Some(ScanCode::Registration(registration)) if registration == "EMPRESA"
+        ));
+    }
+}
@@ -1,13 +1,21 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod attachments;
 mod commands;
+mod cpf;
 mod db;
+mod exports;
+mod imports;
+mod labels;
+mod migrations;
+mod permissions;
 mod sessions;
 mod types;
 
 use std::fs;
 
 use anyhow::Context;
+use attachments::AttachmentStore;
 use commands::auth::LoginRateLimiter;
 use db::ArchiveDatabase;
 use sessions::SessionStore;
@@ -29,10 +37,18 @@ fn main() -> anyhow::Result<()> {
             // Initialize DB asynchronously
             let db = tauri::async_runtime::block_on(async {
                 let db = ArchiveDatabase::connect(db_path).await?;
+                db.bootstrap_admin(
+                    std::env::var("ARCHIVE_DEFAULT_ADMIN_LOGIN").ok().as_deref(),
+                    std::env::var("ARCHIVE_DEFAULT_ADMIN_PASSWORD")
+                        .ok()
+                        .as_deref(),
+                )
+                .await?;
                 Ok::<_, anyhow::Error>(db)
             })?;
 
             app.manage(db);
+            app.manage(AttachmentStore::new(data_dir.join("anexos")));
             app.manage(SessionStore::default());
             app.manage(LoginRateLimiter::default());
             Ok(())
@@ -41,12 +57,24 @@ fn main() -> anyhow::Result<()> {
             commands::auth::auth_login,
             commands::auth::auth_session,
             commands::auth::auth_logout,
+            commands::users::list_users,
+            commands::users::create_user,
+            commands::users::update_user_role,
+            commands::users::deactivate_user,
+            commands::users::activate_user,
+            commands::users::reset_user_password,
+            commands::users::change_own_password,
+            commands::audit::list_audit_logs,
+            commands::audit::get_entity_history,
+            commands::audit::export_audit_logs,
             commands::storage::storage_list,
             commands::storage::storage_create,
             commands::movements::movements_list,
             commands::movements::movements_record,
             commands::employees::create_employee,
             commands::employees::update_employee,
+            commands::employees::import_employees,
+            commands::employees::list_cpf_conflicts,
             commands::employees::terminate_employee,
             commands::employees::list_employees,
             commands::employees::search_employees,
@@ -62,10 +90,37 @@ fn main() -> anyhow::Result<()> {
             commands::file_cabinets::suggest_reorganization,
             commands::documents::list_document_categories,
             commands::documents::list_document_types,
+            commands::documents::create_document_category,
+            commands::documents::update_document_category,
+            commands::documents::deactivate_document_category,
+            commands::documents::delete_document_category,
+            commands::documents::create_document_type,
+            commands::documents::update_document_type,
+            commands::documents::deactivate_document_type,
+            commands::documents::delete_document_type,
             commands::documents::create_document,
+            commands::documents::update_document,
+            commands::documents::delete_document,
             commands::documents::list_employee_documents,
+            commands::documents::list_expiring_documents,
+            commands::documents::get_employee_compliance,
+            commands::documents::get_compliance_report,
+            commands::documents::attach_document_file,
+            commands::documents::list_document_files,
+            commands::documents::open_document_file,
+            commands::documents::remove_document_file,
+            commands::documents::verify_document_files,
             commands::loans::create_loan,
             commands::loans::return_loan,
+            commands::loans::partial_return_loan,
+            commands::loans::renew_loan,
+            commands::loans::report_lost_loan,
+            commands::loans::cancel_loan,
+            commands::loans::list_loan_renewals,
+            commands::loans::list_loan_partial_returns,
+            commands::loans::reserve_folder,
+            commands::loans::list_folder_reservations,
+            commands::loans::cancel_reservation,
             commands::loans::list_loans,
             commands::loans::get_pending_loans,
             commands::loans::get_overdue_loans,
@@ -78,9 +133,12 @@ fn main() -> anyhow::Result<()> {
             commands::reports::get_movements_report,
             commands::reports::get_loans_report,
             commands::reports::export_to_excel,
+            commands::search::search,
             commands::labels::generate_folder_label,
             commands::labels::generate_envelope_label,
-            commands::labels::generate_box_label
+            commands::labels::generate_box_label,
+            commands::labels::generate_batch_labels,
+            commands::labels::resolve_scan
         ])
         .plugin(tauri_plugin_sql::Builder::default().build())
         .run(tauri::generate_context!())?;
@@ -0,0 +1,265 @@
+use anyhow::{bail, Result};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+/// Migração numerada, aplicada uma única vez e registrada em `schema_migrations`.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Novas migrações entram sempre no final, com a próxima versão.
+/// Arquivos já publicados não podem ser alterados (o checksum é conferido).
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        sql: include_str!("../migrations/0001_baseline.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "termination_reason",
+        sql: include_str!("../migrations/0002_termination_reason.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "disposal_rule",
+        sql: include_str!("../migrations/0003_disposal_rule.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "cpf_digits",
+        sql: include_str!("../migrations/0004_cpf_digits.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "search_index",
+        sql: include_str!("../migrations/0005_search_index.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "document_files",
+        sql: include_str!("../migrations/0006_document_files.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "required_documents",
+        sql: include_str!("../migrations/0007_required_documents.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "document_catalog",
+        sql: include_str!("../migrations/0008_document_catalog.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "document_soft_delete",
+        sql: include_str!("../migrations/0009_document_soft_delete.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "loan_states",
+        sql: include_str!("../migrations/0010_loan_states.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "loan_reservations",
+        sql: include_str!("../migrations/0011_loan_reservations.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "loan_partial_returns",
+        sql: include_str!("../migrations/0012_loan_partial_returns.sql"),
+    },
+];
+
+impl Migration {
+    /// SHA-256 do SQL, ignorando `\r` para não variar entre checkouts Windows e Unix.
+    pub fn checksum(&self) -> String {
+        let normalized = self.sql.replace('\r', "");
+        Sha256::digest(normalized.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+pub async fn run(pool: &SqlitePool) -> Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    let applied: Vec<(i64, String, String)> =
+        sqlx::query("SELECT version, name, checksum FROM schema_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(|row| (row.get(0), row.get(1), row.get(2)))
+            .collect();
+
+    let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+    if let Some((version, _, _)) = applied.iter().find(|(version, _, _)| *version > latest) {
+        bail!(
+            "O banco de dados está na versão {} do esquema, mais nova que a suportada por este aplicativo ({}). Atualize o aplicativo antes de abrir este arquivo.",
+            version,
+            latest
+        );
+    }
+
+    for (version, name, checksum) in &applied {
+        match MIGRATIONS.iter().find(|m| m.version == *version) {
+            Some(migration) if migration.checksum() == *checksum => {}
+            Some(_) => bail!(
+                "A migração {} ({}) foi alterada depois de aplicada; checksum divergente",
+                version,
+                name
+            ),
+            None => bail!(
+                "Migração {} ({}) desconhecida por este aplicativo",
+                version,
+                name
+            ),
+        }
+    }
+
+    // Bancos criados antes do controle de versão já têm tabelas, mas nenhum registro
+    let legacy = applied.is_empty() && table_exists(pool, "users").await?;
+
+    for migration in MIGRATIONS {
+        if applied
+            .iter()
+            .any(|(version, _, _)| *version == migration.version)
+        {
+            continue;
+        }
+
+        let mut tx = pool.begin().await?;
+        if legacy && migration.version == 1 {
+            upgrade_legacy_schema(&mut tx).await?;
+        }
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+async fn table_exists(pool: &SqlitePool, table: &str) -> Result<bool> {
+    let row = sqlx::query("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table)
+        .fetch_one(pool)
+        .await?;
+    Ok(row.get::<i64, _>(0) > 0)
+}
+
+/// Ajustes que versões sem controle de esquema aplicavam a cada inicialização.
+async fn upgrade_legacy_schema(conn: &mut SqliteConnection) -> Result<()> {
+    let columns = table_columns(conn, "users").await?;
+    if !columns.iter().any(|c| c == "login") && columns.iter().any(|c| c == "email") {
+        sqlx::query("ALTER TABLE users RENAME COLUMN email TO login")
+            .execute(&mut *conn)
+            .await?;
+    }
+    if !columns.iter().any(|c| c == "is_active") {
+        sqlx::query("ALTER TABLE users ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1")
+            .execute(&mut *conn)
+            .await?;
+    }
+    if !columns.iter().any(|c| c == "must_change_password") {
+        sqlx::query("ALTER TABLE users ADD COLUMN must_change_password INTEGER NOT NULL DEFAULT 0")
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|row| row.get::<String, _>(1)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::str::FromStr;
+
+    async fn memory_pool() -> SqlitePool {
+        // Uma única conexão que nunca expira: o banco em memória vive nela
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:").unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn applied(pool: &SqlitePool) -> Vec<(i64, String)> {
+        sqlx::query_as("SELECT version, applied_at FROM schema_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rerun_is_a_no_op() {
+        let pool = memory_pool().await;
+        run(&pool).await.unwrap();
+        let first = applied(&pool).await;
+        assert_eq!(first.len(), MIGRATIONS.len());
+
+        run(&pool).await.unwrap();
+        assert_eq!(applied(&pool).await, first);
+    }
+
+    #[tokio::test]
+    async fn changed_checksum_fails() {
+        let pool = memory_pool().await;
+        run(&pool).await.unwrap();
+        sqlx::query("UPDATE schema_migrations SET checksum = 'alterado' WHERE version = 2")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let error = run(&pool).await.unwrap_err();
+        assert!(error.to_string().contains("checksum divergente"));
+    }
+
+    #[tokio::test]
+    async fn newer_database_fails() {
+        let pool = memory_pool().await;
+        run(&pool).await.unwrap();
+        let latest = MIGRATIONS.last().unwrap().version;
+        sqlx::query(
+            "INSERT INTO schema_migrations (version, name, checksum, applied_at)
+             VALUES (?, 'futura', '', '')",
+        )
+        .bind(latest + 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let error = run(&pool).await.unwrap_err();
+        assert!(error.to_string().contains("mais nova"));
+    }
+}
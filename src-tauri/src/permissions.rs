@@ -0,0 +1,246 @@
+use crate::types::ApiResponse;
+
+/// Perfis de acesso atribuídos em `users.role`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    Archivist,
+    HrReader,
+    Auditor,
+}
+
+impl Role {
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "admin" => Some(Role::Admin),
+            "archivist" => Some(Role::Archivist),
+            "hr_reader" => Some(Role::HrReader),
+            "auditor" => Some(Role::Auditor),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Archivist => "archivist",
+            Role::HrReader => "hr_reader",
+            Role::Auditor => "auditor",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::Admin => "Administrador",
+            Role::Archivist => "Arquivista",
+            Role::HrReader => "Consulta RH",
+            Role::Auditor => "Auditor",
+        }
+    }
+
+    pub fn grants(&self, permission: Permission) -> bool {
+        use Permission::*;
+
+        match self {
+            Role::Admin => true,
+            Role::Archivist => !matches!(permission, ManageUsers | RegisterDisposal | ViewAudit),
+            Role::HrReader => matches!(permission, ViewRecords | ViewReports),
+            Role::Auditor => {
+                matches!(
+                    permission,
+                    ViewRecords | ViewReports | ExportData | ViewAudit
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ViewRecords,
+    ViewReports,
+    ExportData,
+    ManageEmployees,
+    TerminateEmployees,
+    ManageDepartments,
+    ManageDocuments,
+    ManageStorage,
+    ManageLoans,
+    ManageArchive,
+    RegisterDisposal,
+    PrintLabels,
+    ManageUsers,
+    ViewAudit,
+}
+
+/// Permissão exigida por cada comando Tauri. Comandos ausentes da tabela são negados.
+const COMMAND_PERMISSIONS: &[(&str, Permission)] = &[
+    ("storage_list", Permission::ViewRecords),
+    ("storage_create", Permission::ManageStorage),
+    ("movements_list", Permission::ViewRecords),
+    ("movements_record", Permission::ManageStorage),
+    ("create_employee", Permission::ManageEmployees),
+    ("update_employee", Permission::ManageEmployees),
+    ("import_employees", Permission::ManageEmployees),
+    ("list_cpf_conflicts", Permission::ManageEmployees),
+    ("terminate_employee", Permission::TerminateEmployees),
+    ("list_employees", Permission::ViewRecords),
+    ("search_employees", Permission::ViewRecords),
+    ("search", Permission::ViewRecords),
+    ("get_employee", Permission::ViewRecords),
+    ("list_departments", Permission::ViewRecords),
+    ("create_department", Permission::ManageDepartments),
+    ("update_department", Permission::ManageDepartments),
+    ("create_file_cabinet", Permission::ManageStorage),
+    ("create_drawer", Permission::ManageStorage),
+    ("list_file_cabinets", Permission::ViewRecords),
+    ("get_occupation_map", Permission::ViewRecords),
+    ("assign_employee_position", Permission::ManageStorage),
+    ("suggest_reorganization", Permission::ViewRecords),
+    ("list_document_categories", Permission::ViewRecords),
+    ("list_document_types", Permission::ViewRecords),
+    ("create_document_category", Permission::ManageDocuments),
+    ("update_document_category", Permission::ManageDocuments),
+    ("deactivate_document_category", Permission::ManageDocuments),
+    ("delete_document_category", Permission::ManageDocuments),
+    ("create_document_type", Permission::ManageDocuments),
+    ("update_document_type", Permission::ManageDocuments),
+    ("deactivate_document_type", Permission::ManageDocuments),
+    ("delete_document_type", Permission::ManageDocuments),
+    ("create_document", Permission::ManageDocuments),
+    ("update_document", Permission::ManageDocuments),
+    ("delete_document", Permission::ManageDocuments),
+    ("list_employee_documents", Permission::ViewRecords),
+    ("list_expiring_documents", Permission::ViewRecords),
+    ("get_employee_compliance", Permission::ViewRecords),
+    ("get_compliance_report", Permission::ViewReports),
+    ("attach_document_file", Permission::ManageDocuments),
+    ("list_document_files", Permission::ViewRecords),
+    ("open_document_file", Permission::ViewRecords),
+    ("remove_document_file", Permission::ManageDocuments),
+    ("verify_document_files", Permission::ManageDocuments),
+    ("create_loan", Permission::ManageLoans),
+    ("return_loan", Permission::ManageLoans),
+    ("partial_return_loan", Permission::ManageLoans),
+    ("renew_loan", Permission::ManageLoans),
+    ("report_lost_loan", Permission::ManageLoans),
+    ("cancel_loan", Permission::ManageLoans),
+    ("list_loan_renewals", Permission::ViewRecords),
+    ("list_loan_partial_returns", Permission::ViewRecords),
+    ("reserve_folder", Permission::ManageLoans),
+    ("list_folder_reservations", Permission::ViewRecords),
+    ("cancel_reservation", Permission::ManageLoans),
+    ("list_loans", Permission::ViewRecords),
+    ("get_pending_loans", Permission::ViewRecords),
+    ("get_overdue_loans", Permission::ViewRecords),
+    ("create_archive_box", Permission::ManageArchive),
+    ("list_archive_boxes", Permission::ViewRecords),
+    ("transfer_to_archive", Permission::ManageArchive),
+    ("get_disposal_candidates", Permission::ViewRecords),
+    ("register_disposal", Permission::RegisterDisposal),
+    ("get_dashboard_stats", Permission::ViewReports),
+    ("get_movements_report", Permission::ViewReports),
+    ("get_loans_report", Permission::ViewReports),
+    ("export_to_excel", Permission::ExportData),
+    ("generate_folder_label", Permission::PrintLabels),
+    ("generate_envelope_label", Permission::PrintLabels),
+    ("generate_box_label", Permission::PrintLabels),
+    ("generate_batch_labels", Permission::PrintLabels),
+    ("resolve_scan", Permission::ViewRecords),
+    ("list_users", Permission::ManageUsers),
+    ("create_user", Permission::ManageUsers),
+    ("update_user_role", Permission::ManageUsers),
+    ("deactivate_user", Permission::ManageUsers),
+    ("activate_user", Permission::ManageUsers),
+    ("reset_user_password", Permission::ManageUsers),
+    ("list_audit_logs", Permission::ViewAudit),
+    ("get_entity_history", Permission::ViewAudit),
+    ("export_audit_logs", Permission::ViewAudit),
+];
+
+pub fn required_permission(command: &str) -> Option<Permission> {
+    COMMAND_PERMISSIONS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, permission)| *permission)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccessError {
+    #[error("{0}")]
+    Session(&'static str),
+    #[error("Altere sua senha provisória antes de continuar.")]
+    PasswordChangeRequired,
+    #[error("Seu perfil ({role}) não tem permissão para executar esta ação ({command}).")]
+    Forbidden { role: String, command: String },
+}
+
+/// Confere se o perfil informado pode executar o comando.
+pub fn check(role: &str, command: &str) -> Result<(), AccessError> {
+    let allowed = match (Role::from_code(role), required_permission(command)) {
+        (Some(role), Some(permission)) => role.grants(permission),
+        _ => false,
+    };
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(AccessError::Forbidden {
+            role: Role::from_code(role)
+                .map(|r| r.label().to_string())
+                .unwrap_or_else(|| role.to_string()),
+            command: command.to_string(),
+        })
+    }
+}
+
+impl<T> From<AccessError> for ApiResponse<T> {
+    fn from(error: AccessError) -> Self {
+        match error {
+            AccessError::Session(message) => ApiResponse::error(message),
+            AccessError::PasswordChangeRequired => ApiResponse {
+                code: Some("PASSWORD_CHANGE_REQUIRED".to_string()),
+                ..ApiResponse::error(error)
+            },
+            AccessError::Forbidden { .. } => ApiResponse::forbidden(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_grant_expected_permissions() {
+        use Permission::*;
+
+        assert!(Role::Admin.grants(ManageUsers));
+        assert!(Role::Archivist.grants(TerminateEmployees));
+        assert!(!Role::Archivist.grants(RegisterDisposal));
+        assert!(!Role::Archivist.grants(ViewAudit));
+        assert!(Role::HrReader.grants(ViewRecords));
+        assert!(!Role::HrReader.grants(ExportData));
+        assert!(!Role::HrReader.grants(TerminateEmployees));
+        assert!(!Role::HrReader.grants(RegisterDisposal));
+        assert!(Role::Auditor.grants(ViewAudit));
+        assert!(!Role::Auditor.grants(ManageLoans));
+    }
+
+    #[test]
+    fn hr_reader_cannot_run_write_commands() {
+        assert!(check("hr_reader", "list_employees").is_ok());
+        for command in ["register_disposal", "terminate_employee"] {
+            let error = check("hr_reader", command).unwrap_err();
+            assert!(matches!(error, AccessError::Forbidden { .. }));
+            let response: ApiResponse<()> = error.into();
+            assert_eq!(response.code.as_deref(), Some("FORBIDDEN"));
+        }
+    }
+
+    #[test]
+    fn unknown_roles_and_commands_are_denied() {
+        assert!(check("admin", "comando_inexistente").is_err());
+        assert!(check("visitante", "list_employees").is_err());
+    }
+}
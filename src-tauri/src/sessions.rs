@@ -4,6 +4,7 @@ use chrono::Utc;
 use std::sync::RwLock;
 use uuid::Uuid;
 
+use crate::permissions::{self, AccessError};
 use crate::types::UserProfile;
 
 #[derive(Clone)]
@@ -45,7 +46,7 @@ impl SessionStore {
                 // 24 hours expiration
                 const SESSION_DURATION_MS: i64 = 24 * 60 * 60 * 1000;
                 let now = Utc::now().timestamp_millis();
-                
+
                 if now - session.issued_at > SESSION_DURATION_MS {
                     self.revoke(token);
                     Err("Sessão expirada. Faça login novamente.")
@@ -57,7 +58,91 @@ impl SessionStore {
         }
     }
 
+    /// Valida a sessão e confere se o perfil pode executar o comando informado.
+    pub fn authorize(&self, token: &str, command: &str) -> Result<ActiveSession, AccessError> {
+        let session = self.require(token).map_err(AccessError::Session)?;
+        if session.profile.must_change_password {
+            return Err(AccessError::PasswordChangeRequired);
+        }
+        permissions::check(&session.profile.role, command)?;
+        Ok(session)
+    }
+
+    pub fn update_profile(&self, token: &str, profile: UserProfile) {
+        if let Some(session) = self.sessions.write().unwrap().get_mut(token) {
+            session.profile = profile;
+        }
+    }
+
     pub fn revoke(&self, token: &str) {
         self.sessions.write().unwrap().remove(token);
     }
+
+    /// Encerra todas as sessões abertas de um usuário (desativação, troca de perfil ou senha).
+    pub fn revoke_user(&self, user_id: i64) {
+        self.sessions
+            .write()
+            .unwrap()
+            .retain(|_, session| session.profile.id != user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ApiResponse;
+
+    fn profile(role: &str, must_change_password: bool) -> UserProfile {
+        UserProfile {
+            id: 7,
+            name: "Maria Silva".into(),
+            login: "maria".into(),
+            role: role.into(),
+            must_change_password,
+        }
+    }
+
+    fn denied_code(error: AccessError) -> Option<String> {
+        let response: ApiResponse<()> = error.into();
+        response.code
+    }
+
+    #[test]
+    fn authorize_forbids_commands_outside_the_role() {
+        let store = SessionStore::default();
+        let session = store.create(profile("hr_reader", false));
+
+        assert!(store.authorize(&session.token, "list_employees").is_ok());
+        for command in ["register_disposal", "terminate_employee"] {
+            let error = store.authorize(&session.token, command).err().unwrap();
+            assert_eq!(denied_code(error).as_deref(), Some("FORBIDDEN"));
+        }
+    }
+
+    #[test]
+    fn authorize_requires_password_change_first() {
+        let store = SessionStore::default();
+        let session = store.create(profile("admin", true));
+
+        let error = store
+            .authorize(&session.token, "list_employees")
+            .err()
+            .unwrap();
+        assert_eq!(
+            denied_code(error).as_deref(),
+            Some("PASSWORD_CHANGE_REQUIRED")
+        );
+    }
+
+    #[test]
+    fn authorize_rejects_unknown_and_revoked_tokens() {
+        let store = SessionStore::default();
+        let session = store.create(profile("admin", false));
+        store.revoke_user(7);
+
+        for token in ["token-inexistente", session.token.as_str()] {
+            let error = store.authorize(token, "list_employees").err().unwrap();
+            assert!(matches!(error, AccessError::Session(_)));
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::permissions::Role;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageUnitRecord {
@@ -44,6 +46,7 @@ pub struct LoginResult {
     pub token: String,
     pub profile: UserProfile,
     pub snapshot: SnapshotSummary,
+    pub must_change_password: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +55,7 @@ pub struct UserProfile {
     pub name: String,
     pub login: String,
     pub role: String,
+    pub must_change_password: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -70,6 +74,88 @@ pub struct CredentialsPayload {
     pub password: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub id: i64,
+    pub name: String,
+    pub login: String,
+    pub role: String,
+    pub is_active: bool,
+    pub must_change_password: bool,
+    pub created_at: String,
+}
+
+fn validate_role(role: &str) -> Result<(), ValidationError> {
+    match Role::from_code(role) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("role").with_message("Perfil inválido".into())),
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserPayload {
+    #[validate(length(min = 3, max = 200, message = "Nome deve ter entre 3 e 200 caracteres"))]
+    pub name: String,
+    #[validate(length(
+        min = 3,
+        max = 100,
+        message = "Login deve ter entre 3 e 100 caracteres"
+    ))]
+    pub login: String,
+    #[validate(length(
+        min = 8,
+        max = 100,
+        message = "Senha deve ter entre 8 e 100 caracteres"
+    ))]
+    pub password: String,
+    #[validate(custom(function = "validate_role"))]
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserCreatePayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(nested)]
+    pub data: UserPayload,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserRolePayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub user_id: i64,
+    #[validate(custom(function = "validate_role"))]
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserPasswordResetPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub user_id: i64,
+    #[validate(length(
+        min = 8,
+        max = 100,
+        message = "Senha deve ter entre 8 e 100 caracteres"
+    ))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PasswordChangePayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(length(min = 1, message = "Informe a senha atual"))]
+    pub current_password: String,
+    #[validate(length(
+        min = 8,
+        max = 100,
+        message = "Senha deve ter entre 8 e 100 caracteres"
+    ))]
+    pub new_password: String,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct TokenPayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
@@ -148,6 +234,31 @@ pub struct MovementData {
     pub note: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct MovementFilter {
+    #[validate(length(max = 200, message = "Responsável deve ter no máximo 200 caracteres"))]
+    pub actor: Option<String>,
+    /// Trecho da descrição da ação
+    #[validate(length(max = 500, message = "Ação deve ter no máximo 500 caracteres"))]
+    pub action: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_from: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MovementListPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: MovementFilter,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub paging: PageRequest,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
@@ -155,6 +266,8 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
 impl<T> ApiResponse<T> {
@@ -163,6 +276,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
 
@@ -171,6 +285,91 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(error.to_string()),
+            code: None,
+        }
+    }
+
+    pub fn forbidden<E: ToString>(error: E) -> Self {
+        Self {
+            code: Some("FORBIDDEN".to_string()),
+            ..Self::error(error)
+        }
+    }
+}
+
+// ------------------------------ Pagination ------------------------------
+
+/// Paginação e ordenação comuns às listagens. `sort_by` é conferido pela
+/// listagem, contra as colunas que ela aceita.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct PageRequest {
+    #[validate(range(min = 1, message = "Página deve ser positiva"))]
+    pub page: Option<i64>,
+    #[validate(range(
+        min = 1,
+        max = 500,
+        message = "Tamanho de página deve estar entre 1 e 500"
+    ))]
+    pub page_size: Option<i64>,
+    #[validate(length(max = 50, message = "Campo de ordenação inválido"))]
+    pub sort_by: Option<String>,
+    #[validate(custom(function = "validate_sort_dir"))]
+    pub sort_dir: Option<String>,
+}
+
+impl PageRequest {
+    /// Sem limite, para relatórios e exportações que precisam de tudo.
+    pub fn all() -> Self {
+        Self {
+            page_size: Some(i64::MAX),
+            ..Self::default()
+        }
+    }
+
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1)
+    }
+
+    pub fn page_size(&self) -> i64 {
+        self.page_size.unwrap_or(50)
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page() - 1).saturating_mul(self.page_size())
+    }
+}
+
+fn validate_sort_dir(direction: &str) -> Result<(), ValidationError> {
+    match direction {
+        "asc" | "desc" => Ok(()),
+        _ => Err(ValidationError::new("sort_dir")
+            .with_message("Direção de ordenação deve ser asc ou desc".into())),
+    }
+}
+
+/// Página de resultados com o total, para a navegação "página 3 de 12".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+    pub total_pages: i64,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, paging: &PageRequest) -> Self {
+        let page_size = paging.page_size();
+        Self {
+            items,
+            total,
+            page: paging.page(),
+            page_size,
+            total_pages: if total == 0 {
+                0
+            } else {
+                (total - 1) / page_size + 1
+            },
         }
     }
 }
@@ -219,6 +418,7 @@ pub struct EmployeeRecord {
     pub department_name: Option<String>,
     pub admission_date: String,
     pub termination_date: Option<String>,
+    pub termination_reason: Option<String>,
     pub status: String,
     pub drawer_position_id: Option<i64>,
     pub notes: Option<String>,
@@ -234,6 +434,14 @@ pub struct EmployeeDetail {
     pub drawer_position: Option<DrawerPositionRecord>,
 }
 
+fn validate_cpf(cpf: &str) -> Result<(), ValidationError> {
+    if crate::cpf::is_valid(cpf) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("cpf").with_message("CPF inválido".into()))
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct EmployeePayload {
     #[validate(length(
@@ -248,11 +456,15 @@ pub struct EmployeePayload {
         message = "Matrícula deve ter entre 3 e 50 caracteres"
     ))]
     pub registration: String,
-    #[validate(length(min = 11, max = 14, message = "CPF inválido"))]
+    #[validate(custom(function = "validate_cpf"))]
     pub cpf: Option<String>,
     pub department_id: Option<i64>,
-    #[validate(length(min = 4, message = "Data de admissão é obrigatória"))]
+    #[validate(
+        length(min = 4, message = "Data de admissão é obrigatória"),
+        custom(function = "validate_iso_date")
+    )]
     pub admission_date: String,
+    #[validate(custom(function = "validate_iso_date"))]
     pub termination_date: Option<String>,
     #[validate(length(min = 4, max = 20, message = "Status deve ter entre 4 e 20 caracteres"))]
     pub status: Option<String>,
@@ -277,18 +489,50 @@ pub struct EmployeeUpdatePayload {
     pub data: EmployeePayload,
 }
 
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct EmployeeFilter {
+    pub status: Option<String>,
+    pub department_id: Option<i64>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub admission_from: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub admission_to: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub termination_from: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub termination_to: Option<String>,
+    pub file_cabinet_id: Option<i64>,
+    pub drawer_id: Option<i64>,
+    pub drawer_position_id: Option<i64>,
+    /// `false` lista apenas pastas ainda sem posição em gaveta.
+    pub has_position: Option<bool>,
+    /// Pastas com empréstimo em aberto (`true`) ou sem nenhum (`false`).
+    pub has_open_loan: Option<bool>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct EmployeeFilterPayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
     pub token: String,
-    pub status: Option<String>,
-    pub department_id: Option<i64>,
-    #[allow(dead_code)]
-    pub drawer_position_id: Option<i64>,
-    #[validate(range(min = 1, max = 500, message = "Page size inválido"))]
-    pub page_size: Option<i64>,
-    #[validate(range(min = 1, message = "Página deve ser positiva"))]
-    pub page: Option<i64>,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: EmployeeFilter,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub paging: PageRequest,
+}
+
+/// Resultado da busca global. `entity_type` é employee, document, loan ou box;
+/// `employee_id` aponta a pasta relacionada, quando houver. No `snippet`, os
+/// termos encontrados vêm entre colchetes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub employee_id: Option<i64>,
+    pub title: String,
+    pub snippet: String,
+    pub score: f64,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -310,19 +554,60 @@ pub struct TerminationPayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
     pub token: String,
     pub employee_id: i64,
-    #[validate(length(min = 4, message = "Data de demissão é obrigatória"))]
+    #[validate(custom(function = "validate_iso_date"))]
     pub termination_date: String,
     #[validate(length(max = 500, message = "Motivo deve ter no máximo 500 caracteres"))]
     pub reason: Option<String>,
-    #[allow(dead_code)]
     pub transfer_to_box_id: Option<i64>,
 }
 
+/// Desligamento registrado. Com transferência para o arquivo morto, traz as
+/// etiquetas da pasta (`label`) e da caixa (`box_label`); falha ao gerá-las não
+/// desfaz o desligamento, apenas deixa a etiqueta vazia.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminationResult {
     pub employee: EmployeeRecord,
     pub archive_item: Option<ArchiveItemRecord>,
     pub label: Option<LabelData>,
+    pub box_label: Option<LabelData>,
+}
+
+/// Funcionário cujo CPF não pôde ser normalizado por coincidir com o de outro cadastro.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpfConflictRecord {
+    pub employee_id: i64,
+    pub full_name: String,
+    pub registration: String,
+    pub original_cpf: String,
+    pub normalized_cpf: String,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EmployeeImportPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(length(min = 1, message = "Arquivo de importação é obrigatório"))]
+    pub path: String,
+    /// Apenas valida e devolve o relatório, sem gravar nada.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeImportLine {
+    pub line: usize,
+    pub registration: String,
+    pub full_name: String,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub imported_rows: usize,
+    pub lines: Vec<EmployeeImportLine>,
 }
 
 // ------------------------------ File Cabinets ------------------------------
@@ -504,6 +789,7 @@ pub struct DocumentCategoryRecord {
     pub description: Option<String>,
     pub icon: Option<String>,
     pub color: Option<String>,
+    pub is_active: bool,
     pub created_at: String,
 }
 
@@ -514,9 +800,73 @@ pub struct DocumentTypeRecord {
     pub name: String,
     pub retention_years: i64,
     pub is_required: bool,
+    pub is_active: bool,
     pub created_at: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentCategoryPayload {
+    #[validate(length(min = 2, max = 100, message = "Nome deve ter entre 2 e 100 caracteres"))]
+    pub name: String,
+    /// Sigla em maiúsculas, sem espaços (ex.: PESSOAL, ESOCIAL).
+    #[validate(custom(function = "validate_category_code"))]
+    pub code: String,
+    #[validate(length(max = 500, message = "Descrição deve ter no máximo 500 caracteres"))]
+    pub description: Option<String>,
+    #[validate(length(max = 50, message = "Ícone deve ter no máximo 50 caracteres"))]
+    pub icon: Option<String>,
+    #[validate(length(max = 20, message = "Cor deve ter no máximo 20 caracteres"))]
+    pub color: Option<String>,
+    /// Ausente na criação vale ativo; na edição mantém a situação atual.
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentCategoryUpsertPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(nested)]
+    pub data: DocumentCategoryPayload,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentTypePayload {
+    pub category_id: i64,
+    #[validate(length(min = 2, max = 200, message = "Nome deve ter entre 2 e 200 caracteres"))]
+    pub name: String,
+    /// Anos de guarda após o desligamento; define a data de descarte da pasta.
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Prazo de guarda deve ficar entre 0 e 100 anos"
+    ))]
+    pub retention_years: i64,
+    pub is_required: bool,
+    /// Ausente na criação vale ativo; na edição mantém a situação atual.
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentTypeUpsertPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(nested)]
+    pub data: DocumentTypePayload,
+}
+
+fn validate_category_code(code: &str) -> Result<(), ValidationError> {
+    let valid = (2..=30).contains(&code.len())
+        && code
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("code")
+            .with_message("Código deve ter de 2 a 30 letras maiúsculas, números ou _".into()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentRecord {
     pub id: i64,
@@ -530,6 +880,42 @@ pub struct DocumentRecord {
     pub notes: Option<String>,
     pub filed_by: Option<String>,
     pub created_at: String,
+    pub updated_at: Option<String>,
+    /// Preenchidos quando o documento foi excluído.
+    pub deleted_at: Option<String>,
+    pub deleted_by: Option<String>,
+    pub deletion_reason: Option<String>,
+}
+
+/// Correção de um documento já arquivado. O funcionário não muda.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentUpdatePayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub id: i64,
+    pub category_id: i64,
+    pub type_id: i64,
+    #[validate(length(max = 500, message = "Descrição deve ter no máximo 500 caracteres"))]
+    pub description: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub document_date: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub expiration_date: Option<String>,
+    #[validate(length(max = 500, message = "Notas deve ter no máximo 500 caracteres"))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentDeletePayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub id: i64,
+    #[validate(length(
+        min = 5,
+        max = 500,
+        message = "Motivo deve ter entre 5 e 500 caracteres"
+    ))]
+    pub reason: String,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -537,6 +923,9 @@ pub struct EmployeeDocumentsPayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
     pub token: String,
     pub employee_id: i64,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub paging: PageRequest,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -548,7 +937,9 @@ pub struct DocumentPayload {
     pub type_id: i64,
     #[validate(length(max = 500, message = "Descrição deve ter no máximo 500 caracteres"))]
     pub description: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
     pub document_date: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
     pub expiration_date: Option<String>,
     #[validate(length(max = 500, message = "Notas deve ter no máximo 500 caracteres"))]
     pub notes: Option<String>,
@@ -556,41 +947,223 @@ pub struct DocumentPayload {
     pub filed_by: Option<String>,
 }
 
-// ------------------------------ Loans ------------------------------
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExpiringDocumentsPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    /// Janela de vencimento a partir de hoje (padrão 30 dias).
+    #[validate(range(min = 0, max = 365, message = "Prazo deve ficar entre 0 e 365 dias"))]
+    pub days: Option<i64>,
+    /// Inclui os já vencidos (padrão sim).
+    pub include_expired: Option<bool>,
+    pub department_id: Option<i64>,
+}
 
+/// Documento a renovar. `days_remaining` negativo indica há quantos dias venceu.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LoanRecord {
-    pub id: i64,
+pub struct ExpiringDocument {
+    pub document: DocumentRecord,
+    pub category_name: String,
+    pub type_name: String,
+    pub days_remaining: i64,
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringEmployeeGroup {
     pub employee_id: i64,
-    pub requester_name: String,
-    pub requester_department_id: Option<i64>,
-    pub reason: String,
-    pub loan_date: String,
-    pub expected_return_date: String,
-    pub actual_return_date: Option<String>,
-    pub status: String,
-    pub return_notes: Option<String>,
-    pub loaned_by: String,
-    pub returned_by: Option<String>,
-    pub created_at: String,
-    pub updated_at: String,
+    pub full_name: String,
+    pub registration: String,
+    pub documents: Vec<ExpiringDocument>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LoanWithEmployee {
-    pub loan: LoanRecord,
-    pub employee: EmployeeRecord,
+pub struct ExpiringDepartmentGroup {
+    pub department_id: Option<i64>,
+    pub department_name: Option<String>,
+    pub expired_count: i64,
+    pub expiring_count: i64,
+    pub employees: Vec<ExpiringEmployeeGroup>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
-pub struct LoanPayload {
-    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
-    pub token: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringDocumentsReport {
+    pub days: i64,
+    pub expired_count: i64,
+    pub expiring_count: i64,
+    pub departments: Vec<ExpiringDepartmentGroup>,
+}
+
+/// Tipo obrigatório sem documento válido: ausente ou com o documento mais recente vencido.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceGap {
+    pub type_id: i64,
+    pub type_name: String,
+    pub category_name: String,
+    pub document_id: Option<i64>,
+    pub expiration_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeeCompliance {
     pub employee_id: i64,
-    #[validate(length(
-        min = 3,
-        max = 200,
-        message = "Solicitante deve ter entre 3 e 200 caracteres"
+    pub full_name: String,
+    pub registration: String,
+    pub department_id: Option<i64>,
+    pub department_name: Option<String>,
+    pub required_count: i64,
+    pub compliant_count: i64,
+    pub missing: Vec<ComplianceGap>,
+    pub expired: Vec<ComplianceGap>,
+    /// Percentual de tipos obrigatórios em dia (100 quando não há obrigatórios).
+    pub compliance_rate: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepartmentCompliance {
+    pub department_id: Option<i64>,
+    pub department_name: Option<String>,
+    pub employees: i64,
+    pub compliant_employees: i64,
+    pub missing_documents: i64,
+    pub expired_documents: i64,
+    pub compliance_rate: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub generated_at: String,
+    pub required_types: i64,
+    pub employees: i64,
+    pub compliant_employees: i64,
+    pub compliance_rate: f32,
+    pub departments: Vec<DepartmentCompliance>,
+    /// Funcionários com pendências (ou todos, com `include_compliant`).
+    pub details: Vec<EmployeeCompliance>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ComplianceReportPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub department_id: Option<i64>,
+    pub include_compliant: Option<bool>,
+}
+
+/// Arquivo digitalizado anexado a um documento. O conteúdo fica no armazenamento
+/// local, identificado por `sha256`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFileRecord {
+    pub id: i64,
+    pub document_id: i64,
+    pub sha256: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub attached_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentFileAttachPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub document_id: i64,
+    #[validate(length(
+        min = 1,
+        max = 1000,
+        message = "Caminho deve ter entre 1 e 1000 caracteres"
+    ))]
+    pub path: String,
+}
+
+/// Anexo gravado. `duplicates` lista outros documentos que já tinham o mesmo conteúdo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFileAttachResult {
+    pub file: DocumentFileRecord,
+    pub duplicates: Vec<DocumentFileRecord>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DocumentFileVerifyPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    /// Sem documento, confere todos os anexos.
+    pub document_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFileIntegrityReport {
+    pub checked: i64,
+    pub intact: i64,
+    pub missing: Vec<DocumentFileRecord>,
+    pub corrupted: Vec<DocumentFileRecord>,
+}
+
+// ------------------------------ Loans ------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanRecord {
+    pub id: i64,
+    pub employee_id: i64,
+    pub requester_name: String,
+    pub requester_department_id: Option<i64>,
+    pub reason: String,
+    pub loan_date: String,
+    pub expected_return_date: String,
+    pub actual_return_date: Option<String>,
+    pub status: String,
+    pub return_notes: Option<String>,
+    pub loaned_by: String,
+    pub returned_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub renewal_count: i64,
+    /// Relato do extravio (status LOST).
+    pub incident_notes: Option<String>,
+    pub cancellation_reason: Option<String>,
+    /// O que ainda está com o solicitante depois de uma devolução parcial.
+    pub outstanding_items: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanRenewalRecord {
+    pub id: i64,
+    pub loan_id: i64,
+    pub previous_return_date: String,
+    pub new_return_date: String,
+    pub reason: String,
+    pub renewed_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanPartialReturnRecord {
+    pub id: i64,
+    pub loan_id: i64,
+    pub returned_items: String,
+    pub outstanding_items: String,
+    pub return_date: String,
+    pub notes: Option<String>,
+    pub received_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanWithEmployee {
+    pub loan: LoanRecord,
+    pub employee: EmployeeRecord,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub employee_id: i64,
+    #[validate(length(
+        min = 3,
+        max = 200,
+        message = "Solicitante deve ter entre 3 e 200 caracteres"
     ))]
     pub requester_name: String,
     pub requester_department_id: Option<i64>,
@@ -600,7 +1173,10 @@ pub struct LoanPayload {
         message = "Motivo deve ter entre 5 e 500 caracteres"
     ))]
     pub reason: String,
-    #[validate(length(min = 4, message = "Data prevista é obrigatória"))]
+    #[validate(
+        length(min = 4, message = "Data prevista é obrigatória"),
+        custom(function = "validate_iso_date")
+    )]
     pub expected_return_date: String,
     #[validate(length(max = 500, message = "Observações deve ter no máximo 500 caracteres"))]
     pub return_notes: Option<String>,
@@ -616,6 +1192,142 @@ pub struct LoanReturnPayload {
     pub return_notes: Option<String>,
 }
 
+/// Pedido na fila de espera por uma pasta emprestada. `status` é WAITING,
+/// NOTIFIED (pasta devolvida, aguardando retirada), FULFILLED ou CANCELLED.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanReservationRecord {
+    pub id: i64,
+    pub employee_id: i64,
+    pub requester_name: String,
+    pub requester_department_id: Option<i64>,
+    pub reason: String,
+    pub status: String,
+    pub loan_id: Option<i64>,
+    pub reserved_by: String,
+    pub notified_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanReservationPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub employee_id: i64,
+    #[validate(length(
+        min = 3,
+        max = 200,
+        message = "Solicitante deve ter entre 3 e 200 caracteres"
+    ))]
+    pub requester_name: String,
+    pub requester_department_id: Option<i64>,
+    #[validate(length(
+        min = 5,
+        max = 500,
+        message = "Motivo deve ter entre 5 e 500 caracteres"
+    ))]
+    pub reason: String,
+}
+
+/// Devolução ou cancelamento registrado. `next_reservation` é quem deve ser
+/// avisado de que a pasta está disponível.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoanReturnResult {
+    pub loan: LoanRecord,
+    pub next_reservation: Option<LoanReservationRecord>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanRenewPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub loan_id: i64,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub new_expected_return_date: String,
+    #[validate(length(
+        min = 5,
+        max = 500,
+        message = "Motivo deve ter entre 5 e 500 caracteres"
+    ))]
+    pub reason: String,
+}
+
+/// Devolução de parte da pasta; o empréstimo segue em aberto pelo restante.
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanPartialReturnPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub loan_id: i64,
+    #[validate(length(
+        min = 3,
+        max = 500,
+        message = "Itens devolvidos deve ter entre 3 e 500 caracteres"
+    ))]
+    pub returned_items: String,
+    #[validate(length(
+        min = 3,
+        max = 500,
+        message = "Itens pendentes deve ter entre 3 e 500 caracteres"
+    ))]
+    pub outstanding_items: String,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub actual_return_date: Option<String>,
+    #[validate(length(max = 500, message = "Observações deve ter no máximo 500 caracteres"))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanLostPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub loan_id: i64,
+    #[validate(length(
+        min = 10,
+        max = 1000,
+        message = "Relato do extravio deve ter entre 10 e 1000 caracteres"
+    ))]
+    pub incident_notes: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanCancelPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    pub loan_id: i64,
+    #[validate(length(
+        min = 5,
+        max = 500,
+        message = "Motivo deve ter entre 5 e 500 caracteres"
+    ))]
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct LoanFilter {
+    #[validate(length(max = 20, message = "Status deve ter no máximo 20 caracteres"))]
+    pub status: Option<String>,
+    /// Trecho do nome do solicitante
+    #[validate(length(max = 200, message = "Solicitante deve ter no máximo 200 caracteres"))]
+    pub requester: Option<String>,
+    pub requester_department_id: Option<i64>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_from: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoanListPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: LoanFilter,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub paging: PageRequest,
+}
+
 // ------------------------------ Dead Archive ------------------------------
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -651,6 +1363,37 @@ pub struct ArchiveBoxPayload {
     pub capacity: Option<i64>,
 }
 
+fn validate_fullness(fullness: &str) -> Result<(), ValidationError> {
+    match fullness {
+        "empty" | "partial" | "full" => Ok(()),
+        _ => Err(ValidationError::new("fullness")
+            .with_message("Ocupação deve ser empty, partial ou full".into())),
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ArchiveBoxFilter {
+    pub year: Option<i64>,
+    /// Trecho do local de guarda
+    #[validate(length(max = 200, message = "Local deve ter no máximo 200 caracteres"))]
+    pub location: Option<String>,
+    /// `empty` (sem pastas), `partial` ou `full` (na capacidade)
+    #[validate(custom(function = "validate_fullness"))]
+    pub fullness: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ArchiveBoxListPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: ArchiveBoxFilter,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub paging: PageRequest,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct ArchiveBoxCreatePayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
@@ -659,7 +1402,6 @@ pub struct ArchiveBoxCreatePayload {
     pub data: ArchiveBoxPayload,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoxWithCount {
     #[serde(rename = "box")]
@@ -674,6 +1416,8 @@ pub struct ArchiveItemRecord {
     pub box_id: i64,
     pub transfer_date: String,
     pub disposal_eligible_date: Option<String>,
+    /// Regra de temporalidade usada no cálculo de `disposal_eligible_date`
+    pub disposal_rule: Option<String>,
     pub disposed: bool,
     pub disposal_date: Option<String>,
     pub disposal_term_number: Option<String>,
@@ -687,7 +1431,6 @@ pub struct ArchiveTransferPayload {
     pub token: String,
     pub employee_id: i64,
     pub box_id: i64,
-    pub disposal_eligible_date: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -728,6 +1471,9 @@ pub struct DashboardStats {
     pub overdue_loans: i64,
     pub critical_cabinets: Vec<CabinetOccupationNode>,
     pub archive_boxes: i64,
+    /// Documentos que vencem nos próximos 30 dias.
+    pub expiring_documents: i64,
+    pub expired_documents: i64,
     pub last_sync: String,
 }
 
@@ -735,7 +1481,7 @@ pub struct DashboardStats {
 pub struct MovementsReport {
     pub total_movements: i64,
     pub by_action: HashMap<String, i64>,
-    pub latest: Vec<MovementRecord>,
+    pub latest: Page<MovementRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -746,25 +1492,296 @@ pub struct LoansReport {
     pub returned_today: i64,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExcelExportPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(length(max = 1000, message = "Caminho deve ter no máximo 1000 caracteres"))]
+    pub path: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileExportResult {
     pub path: String,
     pub generated_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelField {
+    pub label: String,
+    pub value: String,
+}
+
+impl LabelField {
+    pub fn new(label: &str, value: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Conteúdo de uma etiqueta. Os detalhes saem na ordem em que são impressos.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LabelData {
+    /// Identificador estável codificado no QR/Code128 e lido pelo `resolve_scan`.
+    pub code: String,
     pub title: String,
     pub subtitle: Option<String>,
-    pub details: HashMap<String, String>,
+    pub details: Vec<LabelField>,
     pub generated_at: String,
 }
 
+/// Opções de impressão comuns às etiquetas avulsas e em lote.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct LabelPrintOptions {
+    /// `data`, `pdf` ou `zpl`.
+    #[validate(custom(function = "validate_label_format"))]
+    pub format: Option<String>,
+    /// Modelo da folha Pimaco para o PDF (padrão A4263).
+    #[validate(custom(function = "validate_label_layout"))]
+    pub layout: Option<String>,
+    /// Código impresso: `qr` (padrão), `code128` ou `none`.
+    #[validate(custom(function = "validate_label_barcode"))]
+    pub barcode: Option<String>,
+    /// Primeira posição livre da folha (1 = canto superior esquerdo), para reaproveitar folhas.
+    #[validate(range(min = 1, message = "Posição inicial deve ser maior que zero"))]
+    pub start_position: Option<i64>,
+    #[validate(length(max = 1000, message = "Caminho deve ter no máximo 1000 caracteres"))]
+    pub path: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct LabelRequestPayload {
     #[validate(length(min = 1, message = "Token não pode ser vazio"))]
     pub token: String,
     pub entity_id: i64,
-    #[validate(length(max = 20, message = "Formato deve ter no máximo 20 caracteres"))]
-    pub format: Option<String>,
+    /// Categoria impressa no envelope (padrão "Pessoal").
+    #[validate(length(max = 100, message = "Categoria deve ter no máximo 100 caracteres"))]
+    pub category: Option<String>,
+    /// Sem `format`, devolve apenas os dados da etiqueta.
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub options: LabelPrintOptions,
+}
+
+/// Etiquetas de pasta para todas as posições ocupadas de uma gaveta (`drawer`) ou
+/// arquivo (`cabinet`), ou para as pastas guardadas numa caixa do arquivo morto (`box`).
+#[derive(Debug, Deserialize, Validate)]
+pub struct LabelBatchPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(custom(function = "validate_label_scope"))]
+    pub scope: String,
+    pub scope_id: i64,
+    /// Sem `format`, gera a folha em PDF.
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub options: LabelPrintOptions,
+}
+
+fn validate_label_scope(scope: &str) -> Result<(), ValidationError> {
+    match scope {
+        "drawer" | "cabinet" | "box" => Ok(()),
+        _ => Err(ValidationError::new("scope")
+            .with_message("Escopo deve ser drawer, cabinet ou box".into())),
+    }
+}
+
+fn validate_label_format(format: &str) -> Result<(), ValidationError> {
+    match format {
+        "data" | "pdf" | "zpl" => Ok(()),
+        _ => {
+            Err(ValidationError::new("format")
+                .with_message("Formato deve ser data, pdf ou zpl".into()))
+        }
+    }
+}
+
+fn validate_label_barcode(barcode: &str) -> Result<(), ValidationError> {
+    match barcode {
+        "qr" | "code128" | "none" => Ok(()),
+        _ => Err(ValidationError::new("barcode")
+            .with_message("Código deve ser qr, code128 ou none".into())),
+    }
+}
+
+fn validate_label_layout(layout: &str) -> Result<(), ValidationError> {
+    match crate::labels::sheet_layout(layout) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("layout").with_message(
+            format!(
+                "Modelo de etiqueta desconhecido; use {}",
+                crate::labels::layout_codes().join(", ")
+            )
+            .into(),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ScanPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(length(
+        min = 1,
+        max = 200,
+        message = "Código deve ter entre 1 e 200 caracteres"
+    ))]
+    pub code: String,
+}
+
+/// O que foi encontrado pelo código lido: `employee` traz a ficha e onde a pasta
+/// está guardada; `box` traz a caixa do arquivo morto com as pastas que contém.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub kind: String,
+    pub code: String,
+    pub employee: Option<EmployeeDetail>,
+    pub location: Option<String>,
+    pub archive_box: Option<BoxWithCount>,
+}
+
+/// Etiquetas geradas. Em PDF/ZPL o arquivo fica em `path`; o ZPL também vem em
+/// `content`, para envio direto à impressora.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelOutput {
+    pub format: String,
+    pub labels: Vec<LabelData>,
+    pub path: Option<String>,
+    pub content: Option<String>,
+}
+
+// ------------------------------ Audit ------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogRecord {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub user_login: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<i64>,
+    pub old_values: Option<Value>,
+    pub new_values: Option<Value>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub items: Vec<AuditLogRecord>,
+    pub next_cursor: Option<i64>,
+}
+
+fn validate_iso_date(value: &str) -> Result<(), ValidationError> {
+    match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ValidationError::new("date")
+            .with_message("Data deve estar no formato AAAA-MM-DD".into())),
+    }
+}
+
+fn validate_export_format(format: &str) -> Result<(), ValidationError> {
+    match format {
+        "csv" | "json" => Ok(()),
+        _ => {
+            Err(ValidationError::new("format").with_message("Formato deve ser csv ou json".into()))
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct AuditLogFilter {
+    pub user_id: Option<i64>,
+    #[validate(length(max = 50, message = "Entidade deve ter no máximo 50 caracteres"))]
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    #[validate(length(max = 50, message = "Ação deve ter no máximo 50 caracteres"))]
+    pub action: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_from: Option<String>,
+    #[validate(custom(function = "validate_iso_date"))]
+    pub date_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AuditLogQueryPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: AuditLogFilter,
+    /// `next_cursor` da página anterior
+    #[validate(range(min = 1, message = "Cursor inválido"))]
+    pub cursor: Option<i64>,
+    #[validate(range(min = 1, max = 500, message = "Limite deve estar entre 1 e 500"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AuditExportPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: AuditLogFilter,
+    #[validate(custom(function = "validate_export_format"))]
+    pub format: String,
+    #[validate(length(max = 1000, message = "Caminho deve ter no máximo 1000 caracteres"))]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EntityHistoryPayload {
+    #[validate(length(min = 1, message = "Token não pode ser vazio"))]
+    pub token: String,
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Entidade deve ter entre 1 e 50 caracteres"
+    ))]
+    pub entity_type: String,
+    pub entity_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityHistoryEntry {
+    pub log: AuditLogRecord,
+    /// Estado do registro logo após esta alteração
+    pub state: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityHistory {
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub current_state: Option<Value>,
+    pub entries: Vec<EntityHistoryEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_counts_pages_and_offsets() {
+        let paging = PageRequest {
+            page: Some(3),
+            page_size: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(paging.offset(), 40);
+        let page = Page::new(vec![1, 2], 42, &paging);
+        assert_eq!((page.page, page.page_size, page.total_pages), (3, 20, 3));
+        assert_eq!(Page::<i32>::new(Vec::new(), 0, &paging).total_pages, 0);
+        assert_eq!(Page::<i32>::new(Vec::new(), 40, &paging).total_pages, 2);
+
+        let defaults = PageRequest::default();
+        assert_eq!(
+            (defaults.page(), defaults.page_size(), defaults.offset()),
+            (1, 50, 0)
+        );
+    }
 }
@@ -4,6 +4,7 @@ import type {
     LoginResult,
     MovementPayload,
     MovementRecord,
+    Page,
     SnapshotSummary,
     StoragePayload,
     StorageUnitRecord,
@@ -19,6 +20,11 @@ export const archiveApi = {
     logout(token: string) {
         return invoke<ApiResponse<null>>('auth_logout', { payload: { token } });
     },
+    changePassword(token: string, currentPassword: string, newPassword: string) {
+        return invoke<ApiResponse<unknown>>('change_own_password', {
+            payload: { token, current_password: currentPassword, new_password: newPassword },
+        });
+    },
     storage: {
         list(token: string) {
             return invoke<ApiResponse<StorageUnitRecord[]>>('storage_list', { payload: { token } });
@@ -33,8 +39,10 @@ export const archiveApi = {
         },
     },
     movements: {
-        list(token: string) {
-            return invoke<ApiResponse<MovementRecord[]>>('movements_list', { payload: { token } });
+        list(token: string, pageSize = 25) {
+            return invoke<ApiResponse<Page<MovementRecord>>>('movements_list', {
+                payload: { token, page_size: pageSize },
+            });
         },
         record(token: string, data: MovementPayload) {
             return invoke<ApiResponse<{ movement: MovementRecord; snapshot: SnapshotSummary }>>(
@@ -3,6 +3,7 @@ import { dom, setAuthError, updateAuthUI, setGuardState, setSectionVisibility, s
 import type { AppState } from '../app.js';
 import { refreshStorage } from './storage.js';
 import { refreshMovements } from './movements.js';
+import { requirePasswordChange } from './password-change.js';
 
 const STORAGE_KEY = 'archive_token';
 
@@ -21,13 +22,15 @@ export async function handleLogin(state: AppState) {
             setAuthError(response.error ?? 'Falha ao autenticar.');
             return;
         }
-        state.token = response.data.token;
-        state.snapshot = response.data.snapshot;
-        state.profile = response.data.profile;
-        if (state.token) {
-            localStorage.setItem(STORAGE_KEY, state.token);
+        let session = response.data;
+        localStorage.setItem(STORAGE_KEY, session.token);
+        if (session.must_change_password) {
+            session = await requirePasswordChange(session.token, password);
         }
-        updateAuthUI(response.data.profile);
+        state.token = session.token;
+        state.snapshot = session.snapshot;
+        state.profile = session.profile;
+        updateAuthUI(session.profile);
         await Promise.all([refreshStorage(state), refreshMovements(state)]);
         renderSummary(state.snapshot);
         setGuardState(false);
@@ -69,9 +72,13 @@ export async function resumeSession(state: AppState) {
         setSectionVisibility(false);
         return;
     }
-    state.snapshot = response.data.snapshot;
-    state.profile = response.data.profile;
-    updateAuthUI(response.data.profile);
+    let session = response.data;
+    if (session.must_change_password) {
+        session = await requirePasswordChange(state.token);
+    }
+    state.snapshot = session.snapshot;
+    state.profile = session.profile;
+    updateAuthUI(session.profile);
     await Promise.all([refreshStorage(state), refreshMovements(state)]);
     renderSummary(state.snapshot);
     setGuardState(false);
@@ -6,7 +6,7 @@ export async function refreshMovements(state: AppState) {
     if (!state.token) return;
     const response = await archiveApi.movements.list(state.token);
     if (response.success && response.data) {
-        state.movements = response.data;
+        state.movements = response.data.items;
         renderMovements(state);
     }
 }
@@ -0,0 +1,96 @@
+import { archiveApi } from '../archive-api.js';
+import type { LoginResult } from '../../types/archive.js';
+
+/**
+ * Senha provisória (primeiro administrador ou redefinida por um admin): o backend
+ * recusa qualquer comando com PASSWORD_CHANGE_REQUIRED até a troca. Abre um modal
+ * que só fecha depois que `change_own_password` funciona e a sessão recarregada
+ * deixa de exigir a troca. Com `currentPassword` (recém-digitada no login), o
+ * campo de senha atual não é pedido de novo.
+ */
+export function requirePasswordChange(token: string, currentPassword?: string): Promise<LoginResult> {
+    const dialog = document.createElement('dialog');
+    dialog.className = 'password-change-modal';
+    dialog.innerHTML = `
+        <form class="login-form" method="dialog">
+            <h2>Troque sua senha</h2>
+            <p>Sua senha é provisória. Defina uma nova senha para continuar.</p>
+            <div class="form-group" ${currentPassword ? 'style="display: none"' : ''}>
+                <label for="pc-current">Senha atual</label>
+                <input type="password" id="pc-current" autocomplete="current-password" />
+            </div>
+            <div class="form-group">
+                <label for="pc-new">Nova senha</label>
+                <input type="password" id="pc-new" autocomplete="new-password" minlength="8" maxlength="100" required />
+            </div>
+            <div class="form-group">
+                <label for="pc-confirm">Confirme a nova senha</label>
+                <input type="password" id="pc-confirm" autocomplete="new-password" required />
+            </div>
+            <button type="submit" class="login-button">Alterar senha</button>
+            <span class="auth-error" hidden></span>
+        </form>
+    `;
+    document.body.appendChild(dialog);
+
+    const form = dialog.querySelector('form') as HTMLFormElement;
+    const currentInput = dialog.querySelector('#pc-current') as HTMLInputElement;
+    const newInput = dialog.querySelector('#pc-new') as HTMLInputElement;
+    const confirmInput = dialog.querySelector('#pc-confirm') as HTMLInputElement;
+    const submitButton = dialog.querySelector('button[type="submit"]') as HTMLButtonElement;
+    const errorSpan = dialog.querySelector('.auth-error') as HTMLSpanElement;
+
+    function showError(message: string) {
+        errorSpan.textContent = message;
+        errorSpan.hidden = !message;
+    }
+
+    // Sem a troca, nenhuma tela funciona: o modal não pode ser fechado com Esc
+    dialog.addEventListener('cancel', (e) => e.preventDefault());
+
+    return new Promise((resolve) => {
+        form.addEventListener('submit', async (e) => {
+            e.preventDefault();
+            const current = currentPassword ?? currentInput.value;
+            const next = newInput.value;
+            if (!current) {
+                showError('Informe a senha atual.');
+                return;
+            }
+            if (next.length < 8) {
+                showError('A nova senha deve ter pelo menos 8 caracteres.');
+                return;
+            }
+            if (next !== confirmInput.value) {
+                showError('As senhas não conferem.');
+                return;
+            }
+
+            showError('');
+            submitButton.disabled = true;
+            try {
+                const changed = await archiveApi.changePassword(token, current, next);
+                if (!changed.success) {
+                    showError(changed.error ?? 'Não foi possível alterar a senha.');
+                    return;
+                }
+                const session = await archiveApi.session(token);
+                if (!session.success || !session.data || session.data.must_change_password) {
+                    showError(session.error ?? 'A sessão ainda exige troca de senha. Faça login novamente.');
+                    return;
+                }
+                dialog.close();
+                dialog.remove();
+                resolve(session.data);
+            } catch (err) {
+                console.error('Password change error:', err);
+                showError('Erro de conexão. Verifique se o aplicativo está funcionando.');
+            } finally {
+                submitButton.disabled = false;
+            }
+        });
+
+        dialog.showModal();
+        (currentPassword ? newInput : currentInput).focus();
+    });
+}
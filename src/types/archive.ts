@@ -38,8 +38,10 @@ export interface LoginResult {
         name: string;
         login: string;
         role: string;
+        must_change_password: boolean;
     };
     snapshot: SnapshotSummary;
+    must_change_password: boolean;
 }
 
 export interface StoragePayload {
@@ -59,8 +61,17 @@ export interface MovementPayload {
     note?: string;
 }
 
+export interface Page<T> {
+    items: T[];
+    total: number;
+    page: number;
+    page_size: number;
+    total_pages: number;
+}
+
 export interface ApiResponse<T> {
     success: boolean;
     data?: T;
     error?: string;
+    code?: 'FORBIDDEN' | 'PASSWORD_CHANGE_REQUIRED';
 }